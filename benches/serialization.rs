@@ -0,0 +1,17 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use wascc_codec::conformance;
+
+fn encode_decode(c: &mut Criterion) {
+    for sample in conformance::samples() {
+        let bytes = sample.encode().unwrap();
+        let mut group = c.benchmark_group(format!("{}/{}", sample.module, sample.name));
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        // Encoding includes constructing the sample value, as the sample types are not known here
+        group.bench_function("encode", |b| b.iter(|| sample.encode().unwrap()));
+        group.bench_function("decode", |b| b.iter(|| sample.decode(&bytes).unwrap()));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, encode_decode);
+criterion_main!(benches);
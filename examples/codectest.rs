@@ -1,15 +1,11 @@
-use serde::Deserialize;
 use std::fs::File;
 use std::io::Read;
-use std::io::Write;
+use std::io::{BufReader, Write};
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
-#[macro_use]
-extern crate serde_json;
-
 extern crate wascc_codec as codec;
-use codec::Sample;
+use codec::conformance;
 
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(
@@ -30,6 +26,14 @@ enum CliCommand {
     /// Generate a codec output file
     #[structopt(name = "generate")]
     Generate(GenerateCommand),
+
+    /// Export codec samples as JSON lines, one record per payload type
+    #[structopt(name = "export")]
+    Export(ExportCommand),
+
+    /// Validate a JSON lines export, printing a machine-readable JSON report
+    #[structopt(name = "check")]
+    Check(CheckCommand),
 }
 
 #[derive(Debug, Clone, StructOpt)]
@@ -46,6 +50,28 @@ struct ValidateCommand {
     path: String,
 }
 
+#[derive(Debug, Clone, StructOpt)]
+struct ExportCommand {
+    /// Path of output file
+    #[structopt(short = "p", long = "path")]
+    path: String,
+
+    /// Only export samples from these modules (e.g. httpserver)
+    #[structopt(short = "m", long = "module")]
+    modules: Vec<String>,
+}
+
+#[derive(Debug, Clone, StructOpt)]
+struct CheckCommand {
+    /// Path of file to validate
+    #[structopt(short = "p", long = "path")]
+    path: String,
+
+    /// Only validate samples from these modules (e.g. httpserver)
+    #[structopt(short = "m", long = "module")]
+    modules: Vec<String>,
+}
+
 fn main() -> Result<(), Box<dyn ::std::error::Error>> {
     let args = Cli::from_args();
     let cmd = args.command;
@@ -63,26 +89,40 @@ fn handle_command(cmd: CliCommand) -> Result<(), Box<dyn ::std::error::Error>> {
     match cmd {
         CliCommand::Validate(valcmd) => validate_file(&valcmd),
         CliCommand::Generate(gencmd) => generate_file(&gencmd),
+        CliCommand::Export(expcmd) => export_file(&expcmd),
+        CliCommand::Check(checkcmd) => check_file(&checkcmd),
     }
 }
 
 fn generate_file(cmd: &GenerateCommand) -> Result<(), Box<dyn ::std::error::Error>> {
-    let output = json!({
-        "version" : codec::VERSION,
-        "httpserver": generate_httpserver_sample(),
-        "keyvalue": generate_keyvalue_sample(),
-        "blobstore": generate_blobstore_sample(),
-        "messaging": generate_messaging_sample(),
-        "extras": generate_extras_sample(),
-        "logging": generate_logging_sample(),
-        "eventstreams": generate_eventstreams_sample()
-    });
+    let mut output = serde_json::Map::new();
+    output.insert("version".to_string(), codec::VERSION.into());
+    for sample in conformance::samples() {
+        let module = output
+            .entry(sample.module)
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+        module[sample.name] = base64::encode(sample.encode()?).into();
+    }
     let mut buffer = File::create(&cmd.path)?;
     buffer.write_all(&serde_json::to_vec(&output).unwrap())?;
     buffer.flush()?;
     Ok(())
 }
 
+// The samples written by the original generator, which files from every SDK contain. Samples added
+// since are validated when present and reported as missing otherwise
+const REQUIRED_SAMPLES: &[(&str, &str)] = &[
+    ("httpserver", "request"),
+    ("httpserver", "response"),
+    ("keyvalue", "setrequest"),
+    ("blobstore", "filechunk"),
+    ("blobstore", "containerlist"),
+    ("messaging", "requestmessage"),
+    ("extras", "result_guid"),
+    ("logging", "writelogrequest"),
+    ("eventstreams", "streamquery"),
+];
+
 fn validate_file(cmd: &ValidateCommand) -> Result<(), Box<dyn ::std::error::Error>> {
     let mut f = File::open(&cmd.path)?;
     let mut buffer = Vec::new();
@@ -91,98 +131,66 @@ fn validate_file(cmd: &ValidateCommand) -> Result<(), Box<dyn ::std::error::Erro
     f.read_to_end(&mut buffer)?;
     let raw: serde_json::Value = serde_json::from_str(::std::str::from_utf8(&buffer)?)?;
 
-    assert(
-        &raw["httpserver"]["request"],
-        codec::http::Request::sample(),
-    )?;
-    assert(
-        &raw["httpserver"]["response"],
-        codec::http::Response::sample(),
-    )?;
-    assert(
-        &raw["blobstore"]["filechunk"],
-        codec::blobstore::FileChunk::sample(),
-    )?;
-    assert(
-        &raw["blobstore"]["containerlist"],
-        codec::blobstore::ContainerList::sample(),
-    )?;
-    assert(
-        &raw["extras"]["result_guid"],
-        codec::extras::GeneratorResult::sample(),
-    )?;
-    assert(
-        &raw["eventstreams"]["streamquery"],
-        codec::eventstreams::StreamQuery::sample(),
-    )?;
-    assert(
-        &raw["keyvalue"]["setrequest"],
-        codec::keyvalue::SetRequest::sample(),
-    )?;
-    assert(
-        &raw["messaging"]["requestmessage"],
-        codec::messaging::RequestMessage::sample(),
-    )?;
-    assert(
-        &raw["logging"]["writelogrequest"],
-        codec::logging::WriteLogRequest::sample(),
-    )?;
+    let mut missing = Vec::new();
+    let mut failed = Vec::new();
+    for sample in conformance::samples() {
+        let id = format!("{}.{}", sample.module, sample.name);
+        let encoded = match raw[sample.module][sample.name].as_str() {
+            Some(encoded) => encoded,
+            None if REQUIRED_SAMPLES.contains(&(sample.module, sample.name)) => {
+                failed.push(format!("{} is missing", id));
+                continue;
+            }
+            None => {
+                missing.push(id);
+                continue;
+            }
+        };
+        let payload = match base64::decode(encoded) {
+            Ok(payload) => payload,
+            Err(e) => {
+                failed.push(format!("{} is not valid base64: {}", id, e));
+                continue;
+            }
+        };
+        let result = sample.validate(&payload);
+        if result.status != conformance::SampleStatus::Passed {
+            failed.push(format!(
+                "{} failed validation: {}",
+                id,
+                serde_json::to_string(&result)?
+            ));
+        }
+    }
+    if !missing.is_empty() {
+        println!(
+            "Skipped {} samples missing from the file: {}",
+            missing.len(),
+            missing.join(", ")
+        );
+    }
+    if !failed.is_empty() {
+        return Err(failed.join("\n").into());
+    }
     println!("Valid!");
     Ok(())
 }
 
-fn generate_httpserver_sample() -> serde_json::Value {
-    json!({
-        "request": base64::encode(codec::serialize(codec::http::Request::sample()).unwrap()),
-        "response": base64::encode(codec::serialize(codec::http::Response::sample()).unwrap())
-    })
-}
-
-fn generate_keyvalue_sample() -> serde_json::Value {
-    json!({
-        "setrequest": base64::encode(codec::serialize(codec::keyvalue::SetRequest::sample()).unwrap())
-    })
-}
-
-fn generate_blobstore_sample() -> serde_json::Value {
-    json!({
-        "filechunk": base64::encode(codec::serialize(codec::blobstore::FileChunk::sample()).unwrap()),
-        "containerlist": base64::encode(codec::serialize(codec::blobstore::ContainerList::sample()).unwrap())
-    })
-}
-
-fn generate_messaging_sample() -> serde_json::Value {
-    json!({
-        "requestmessage": base64::encode(codec::serialize(codec::messaging::RequestMessage::sample()).unwrap())
-    })
-}
-
-fn generate_extras_sample() -> serde_json::Value {
-    json!({
-        "result_guid": base64::encode(codec::serialize(codec::extras::GeneratorResult::sample()).unwrap()),
-    })
-}
-
-fn generate_logging_sample() -> serde_json::Value {
-    json!({
-        "writelogrequest": base64::encode(codec::serialize(codec::logging::WriteLogRequest::sample()).unwrap()),
-    })
-}
-
-fn generate_eventstreams_sample() -> serde_json::Value {
-    json!({
-        "streamquery": base64::encode(codec::serialize(codec::eventstreams::StreamQuery::sample()).unwrap())
-    })
+fn export_file(cmd: &ExportCommand) -> Result<(), Box<dyn ::std::error::Error>> {
+    let modules: Vec<&str> = cmd.modules.iter().map(|m| m.as_str()).collect();
+    let mut buffer = File::create(&cmd.path)?;
+    conformance::export_jsonl(&mut buffer, &modules)?;
+    buffer.flush()?;
+    Ok(())
 }
 
-fn assert<'de, T: Deserialize<'de> + PartialEq + std::fmt::Debug>(
-    value: &serde_json::Value,
-    expected: T,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let encoded = value.to_string().replace("\"", "");
-    let bytes = base64::decode(&encoded)?;
-
-    let val: T = codec::deserialize(&bytes)?;
-    assert_eq!(val, expected);
-    Ok(())
+fn check_file(cmd: &CheckCommand) -> Result<(), Box<dyn ::std::error::Error>> {
+    let modules: Vec<&str> = cmd.modules.iter().map(|m| m.as_str()).collect();
+    let report = conformance::validate_jsonl(BufReader::new(File::open(&cmd.path)?), &modules)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if report.passed() {
+        Ok(())
+    } else {
+        Err("One or more samples failed validation".into())
+    }
 }
@@ -0,0 +1,257 @@
+//! # Attribute-Based Access Control
+//!
+//! This module contains policy documents and evaluation helpers that actors can use to make
+//! fine-grained access decisions locally, without a round trip to a policy service. Policies are
+//! typically delivered to an actor at bind time as a JSON document stored under the
+//! `CONFIG_ACCESS_POLICY` configuration key. Evaluation is pure Rust with no host dependencies,
+//! so it can run inside wasm guests
+
+use crate::core::CapabilityConfiguration;
+use crate::Sample;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Configuration key under which a JSON-encoded `PolicyDocument` is delivered at bind time
+pub const CONFIG_ACCESS_POLICY: &str = "__wascc_access_policy";
+
+/// The current version of the policy document format
+pub const POLICY_VERSION: &str = "1";
+
+/// Whether a matching permission grants or denies access
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// The outcome of evaluating a request against a set of permissions
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Decision {
+    /// At least one permission allowed the request and none denied it
+    Allow,
+    /// A permission explicitly denied the request
+    Deny,
+    /// No permission applied to the request. Callers should treat this as a denial
+    NotApplicable,
+}
+
+/// Grants or denies an action on the resources matching a pattern. Patterns may contain `*`, which
+/// matches any sequence of characters (including none), e.g. `orders/*` or `*.read`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Permission {
+    /// Pattern matched against the resource being accessed
+    pub resource: String,
+    /// Pattern matched against the action being performed, e.g. `read` or `*`
+    pub action: String,
+    pub effect: Effect,
+    /// Attributes the caller must have, with exactly these values, for the permission to apply
+    #[serde(default)]
+    pub conditions: HashMap<String, String>,
+}
+
+impl Permission {
+    /// Creates a permission allowing an action on the matching resources
+    pub fn allow(resource: &str, action: &str) -> Permission {
+        Permission {
+            resource: resource.to_string(),
+            action: action.to_string(),
+            effect: Effect::Allow,
+            conditions: HashMap::new(),
+        }
+    }
+
+    /// Creates a permission denying an action on the matching resources
+    pub fn deny(resource: &str, action: &str) -> Permission {
+        Permission {
+            effect: Effect::Deny,
+            ..Permission::allow(resource, action)
+        }
+    }
+
+    /// Adds a condition on an attribute of the caller
+    pub fn when(mut self, attribute: &str, value: &str) -> Permission {
+        self.conditions
+            .insert(attribute.to_string(), value.to_string());
+        self
+    }
+
+    /// Indicates whether this permission applies to the given request
+    pub fn applies_to(
+        &self,
+        resource: &str,
+        action: &str,
+        attributes: &HashMap<String, String>,
+    ) -> bool {
+        pattern_matches(&self.resource, resource)
+            && pattern_matches(&self.action, action)
+            && self
+                .conditions
+                .iter()
+                .all(|(k, v)| attributes.get(k) == Some(v))
+    }
+}
+
+/// A collection of permissions evaluated together. Explicit denials take precedence over grants,
+/// and requests that no permission applies to are not allowed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionSet {
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+impl PermissionSet {
+    pub fn new(permissions: Vec<Permission>) -> PermissionSet {
+        PermissionSet { permissions }
+    }
+
+    /// Evaluates a request to perform an action on a resource by a caller with the given attributes
+    pub fn evaluate(
+        &self,
+        resource: &str,
+        action: &str,
+        attributes: &HashMap<String, String>,
+    ) -> Decision {
+        let mut decision = Decision::NotApplicable;
+        for p in self
+            .permissions
+            .iter()
+            .filter(|p| p.applies_to(resource, action, attributes))
+        {
+            match p.effect {
+                Effect::Deny => return Decision::Deny,
+                Effect::Allow => decision = Decision::Allow,
+            }
+        }
+        decision
+    }
+
+    /// Shorthand for checking whether `evaluate` produces `Decision::Allow`
+    pub fn is_allowed(
+        &self,
+        resource: &str,
+        action: &str,
+        attributes: &HashMap<String, String>,
+    ) -> bool {
+        self.evaluate(resource, action, attributes) == Decision::Allow
+    }
+}
+
+/// Associates a set of permissions with the subjects (callers) they apply to
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyStatement {
+    /// Patterns matched against the subject making the request, e.g. a user ID or actor public key
+    pub subjects: Vec<String>,
+    pub permissions: Vec<Permission>,
+}
+
+/// A serializable access policy, made up of statements that grant permissions to subjects
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDocument {
+    pub version: String,
+    #[serde(default)]
+    pub statements: Vec<PolicyStatement>,
+}
+
+impl Sample for PolicyDocument {
+    fn sample() -> Self {
+        PolicyDocument {
+            version: POLICY_VERSION.to_string(),
+            statements: vec![PolicyStatement {
+                subjects: vec!["user:*".to_string()],
+                permissions: vec![
+                    Permission::allow("orders/*", "read"),
+                    Permission::allow("orders/*", "*").when("role", "admin"),
+                    Permission::deny("orders/archived/*", "write"),
+                ],
+            }],
+        }
+    }
+}
+
+impl PolicyDocument {
+    /// Collects the permissions from every statement that applies to the given subject
+    pub fn permissions_for(&self, subject: &str) -> PermissionSet {
+        PermissionSet::new(
+            self.statements
+                .iter()
+                .filter(|s| s.subjects.iter().any(|p| pattern_matches(p, subject)))
+                .flat_map(|s| s.permissions.iter().cloned())
+                .collect(),
+        )
+    }
+
+    /// Reads the policy document delivered in an actor's binding configuration, if one was supplied
+    pub fn from_configuration(
+        config: &CapabilityConfiguration,
+    ) -> Result<Option<PolicyDocument>, Box<dyn Error>> {
+        match config.values.get(CONFIG_ACCESS_POLICY) {
+            Some(raw) => Ok(Some(serde_json::from_str(raw)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Matches a value against a pattern in which `*` matches any sequence of characters
+pub fn pattern_matches(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let mut rest = match value.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let middle: Vec<&str> = parts.collect();
+    let (last, middle) = match middle.split_last() {
+        Some((last, middle)) => (*last, middle),
+        // no wildcard in the pattern
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{pattern_matches, Decision, PolicyDocument};
+    use crate::Sample;
+    use std::collections::HashMap;
+
+    #[test]
+    fn policy_evaluation() {
+        assert!(pattern_matches("orders/*", "orders/42"));
+        assert!(pattern_matches("*.read", "inventory.read"));
+        assert!(pattern_matches("a*b*c", "abbbc"));
+        assert!(!pattern_matches("a*b*c", "acb"));
+        assert!(!pattern_matches("orders", "orders/42"));
+
+        let policy = PolicyDocument::sample();
+        let user = policy.permissions_for("user:alice");
+        let none = HashMap::new();
+        let mut admin = HashMap::new();
+        admin.insert("role".to_string(), "admin".to_string());
+
+        assert_eq!(user.evaluate("orders/42", "read", &none), Decision::Allow);
+        assert_eq!(
+            user.evaluate("orders/42", "write", &none),
+            Decision::NotApplicable
+        );
+        assert!(user.is_allowed("orders/42", "write", &admin));
+        assert_eq!(
+            user.evaluate("orders/archived/7", "write", &admin),
+            Decision::Deny
+        );
+        assert!(policy
+            .permissions_for("service:billing")
+            .permissions
+            .is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! # Stream Analytics
+//!
+//! This module contains data types for the `wascc:anomaly` capability provider, which fronts time-series
+//! analytics services. Monitoring actors ingest data points, ask for the anomalies detected in a series
+//! and request forecasts of its future values
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends an IngestDataPoints to the capability provider, lack of error indicates success
+pub const OP_INGEST_DATA_POINTS: &str = "IngestDataPoints";
+/// Guest sends an AnomalyQuery to the capability provider, receives an AnomalyReport back
+pub const OP_QUERY_ANOMALIES: &str = "QueryAnomalies";
+/// Guest sends a ForecastRequest to the capability provider, receives a Forecast back
+pub const OP_FORECAST: &str = "Forecast";
+
+/// A single observation of a series
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestDataPoint {
+    /// The name of the series, e.g. `api.latency_ms`
+    pub series: String,
+    pub timestamp: Timestamp,
+    pub value: f64,
+    /// Dimensions of the observation, e.g. `region` or `endpoint`. Points with different tags belong to
+    /// different instances of the series
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+impl Sample for IngestDataPoint {
+    fn sample() -> Self {
+        IngestDataPoint::new("api.latency_ms", Timestamp::from_secs(1_588_291_200), 182.5)
+            .with_tag("region", "eu-west")
+    }
+}
+
+impl IngestDataPoint {
+    pub fn new(series: &str, timestamp: Timestamp, value: f64) -> IngestDataPoint {
+        IngestDataPoint {
+            series: series.to_string(),
+            timestamp,
+            value,
+            tags: HashMap::new(),
+        }
+    }
+
+    pub fn with_tag(mut self, name: &str, value: &str) -> IngestDataPoint {
+        self.tags.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// A batch of data points to ingest. Points need not be in timestamp order
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestDataPoints {
+    pub points: Vec<IngestDataPoint>,
+}
+
+/// A request for the anomalies detected in a series within a time window
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyQuery {
+    pub series: String,
+    /// Only instances of the series with all of these tags are examined. Empty - all instances
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    pub from: Timestamp,
+    pub to: Timestamp,
+    /// How unusual a point must be to be reported, from 0.0 (report everything) to 1.0 (report only
+    /// the most extreme points). None - the provider's default
+    #[serde(default)]
+    pub sensitivity: Option<f64>,
+}
+
+impl Sample for AnomalyQuery {
+    fn sample() -> Self {
+        AnomalyQuery {
+            series: "api.latency_ms".to_string(),
+            tags: HashMap::new(),
+            from: Timestamp::from_secs(1_588_287_600),
+            to: Timestamp::from_secs(1_588_291_200),
+            sensitivity: Some(0.9),
+        }
+    }
+}
+
+/// The anomalies detected in a series, in timestamp order
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnomalyReport {
+    pub series: String,
+    #[serde(default)]
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// A point that deviates from the behavior the provider expected
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Anomaly {
+    pub timestamp: Timestamp,
+    pub value: f64,
+    /// The value the provider expected at this time
+    pub expected: f64,
+    /// How unusual the point is, from 0.0 to 1.0
+    pub score: f64,
+    /// The tags of the series instance the point belongs to
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+impl Anomaly {
+    /// Indicates whether the value is above the expected value
+    pub fn is_spike(&self) -> bool {
+        self.value > self.expected
+    }
+}
+
+/// A request to forecast the future values of a series
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastRequest {
+    pub series: String,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// How far beyond the latest ingested point to forecast
+    pub horizon: DurationMs,
+    /// The time between forecast points
+    pub interval: DurationMs,
+    /// The probability covered by each point's bounds, e.g. 0.95. None - the provider's default
+    #[serde(default)]
+    pub confidence: Option<f64>,
+}
+
+impl Sample for ForecastRequest {
+    fn sample() -> Self {
+        ForecastRequest {
+            series: "orders.per_minute".to_string(),
+            tags: HashMap::new(),
+            horizon: DurationMs::from_secs(6 * 3600),
+            interval: DurationMs::from_secs(300),
+            confidence: Some(0.95),
+        }
+    }
+}
+
+/// The forecast values of a series
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    pub series: String,
+    /// The probability covered by the bounds of the points
+    pub confidence: f64,
+    #[serde(default)]
+    pub points: Vec<ForecastPoint>,
+}
+
+/// A forecast value, with the bounds the actual value is expected to fall within
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastPoint {
+    pub timestamp: Timestamp,
+    pub value: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
@@ -0,0 +1,130 @@
+//! # Remote Attestation
+//!
+//! This module contains data types for the `wascc:attestation` capability provider. Providers backed
+//! by a trusted execution environment (e.g. SGX, SEV-SNP) or a TPM produce signed reports proving
+//! which code is running and on what platform, and verify reports produced elsewhere, so that actors
+//! can decide whether to trust a remote party before handing it secrets or work
+
+use crate::core::Timestamp;
+use crate::Sample;
+
+/// Guest sends a RemoteAttestationRequest to the capability provider, receives an AttestationReport back
+pub const OP_REQUEST_ATTESTATION: &str = "RequestAttestation";
+/// Guest sends a VerifyAttestation request to the capability provider, receives a VerifyAttestationResponse back
+pub const OP_VERIFY_ATTESTATION: &str = "VerifyAttestation";
+
+/// The kind of hardware root of trust that produced a report
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum TeeKind {
+    Sgx,
+    Tdx,
+    SevSnp,
+    Tpm,
+    Nitro,
+    Other(String),
+}
+
+/// Asks the provider to produce an attestation report for the platform it runs on
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteAttestationRequest {
+    /// A value chosen by the verifier, included in the report to prove it is fresh
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub nonce: Vec<u8>,
+    /// Application data to bind into the report, typically the hash of a public key the attested
+    /// party will use to establish a secure channel
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub report_data: Vec<u8>,
+}
+
+impl Sample for RemoteAttestationRequest {
+    fn sample() -> Self {
+        RemoteAttestationRequest {
+            nonce: vec![0x5a; 16],
+            report_data: vec![0x01, 0x02, 0x03, 0x04],
+        }
+    }
+}
+
+/// A single measurement (for TPMs, a platform configuration register) recorded in a report
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Measurement {
+    /// The name or index of the measurement, e.g. `mrenclave` or `pcr7`
+    pub name: String,
+    /// The digest algorithm of the measurement, e.g. `sha256`
+    pub algorithm: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub value: Vec<u8>,
+}
+
+/// A signed statement about the platform on which a provider runs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationReport {
+    pub kind: TeeKind,
+    /// The raw quote produced by the hardware, in its native format
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub quote: Vec<u8>,
+    /// The measurements contained in the quote, decoded for convenience
+    #[serde(default)]
+    pub measurements: Vec<Measurement>,
+    /// The PEM-encoded certificates that chain the quote's signing key to the hardware vendor's
+    /// root, leaf first
+    #[serde(default)]
+    pub signer_chain: Vec<String>,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub nonce: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub report_data: Vec<u8>,
+    pub created_at: Timestamp,
+}
+
+impl AttestationReport {
+    /// Returns the names of the expected measurements that are missing from the report or whose values
+    /// differ from it
+    pub fn mismatched_measurements(&self, expected: &[Measurement]) -> Vec<String> {
+        expected
+            .iter()
+            .filter(|e| {
+                !self
+                    .measurements
+                    .iter()
+                    .any(|m| m.name == e.name && m.algorithm == e.algorithm && m.value == e.value)
+            })
+            .map(|e| e.name.to_string())
+            .collect()
+    }
+}
+
+/// Asks the provider to verify a report produced by another party
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyAttestation {
+    pub report: AttestationReport,
+    /// The nonce the verifier sent when requesting the report
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub expected_nonce: Vec<u8>,
+    /// Measurements that must appear in the report with exactly these values
+    #[serde(default)]
+    pub expected_measurements: Vec<Measurement>,
+}
+
+/// The outcome of verifying a report
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyAttestationResponse {
+    /// Indicates whether the report's signature chain, nonce and measurements were all verified
+    pub verified: bool,
+    /// Describes each check that failed
+    #[serde(default)]
+    pub failures: Vec<String>,
+}
@@ -0,0 +1,139 @@
+//! # Barcodes
+//!
+//! This module contains data types for the `wascc:barcodes` capability provider, which renders content
+//! as barcode or QR code images and decodes the codes found in images, so that actors need not bundle
+//! imaging libraries
+
+use crate::Sample;
+
+/// Guest sends a GenerateCodeRequest to the capability provider, receives a GeneratedCode back
+pub const OP_GENERATE_CODE: &str = "GenerateCode";
+/// Guest sends a DecodeImageRequest to the capability provider, receives a DecodedContent back
+pub const OP_DECODE_IMAGE: &str = "DecodeImage";
+
+/// The type of code to generate or recognize
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Symbology {
+    Qr,
+    DataMatrix,
+    Aztec,
+    Pdf417,
+    Code128,
+    Code39,
+    Ean13,
+    Ean8,
+    UpcA,
+}
+
+/// The amount of damage a two-dimensional code can sustain and still be read. Higher levels produce
+/// denser codes. Ignored for linear barcodes
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCorrection {
+    /// Recovers roughly 7% of the code
+    Low,
+    /// Recovers roughly 15% of the code
+    #[default]
+    Medium,
+    /// Recovers roughly 25% of the code
+    Quartile,
+    /// Recovers roughly 30% of the code
+    High,
+}
+
+/// The format of a generated image
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// The MIME type of images in this format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// A request to render content as a code image
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateCodeRequest {
+    /// The text to encode. Symbologies with restricted character sets (e.g. EAN-13) reject content
+    /// they cannot represent
+    pub content: String,
+    pub symbology: Symbology,
+    /// The width of the image in pixels. The height follows from the symbology's aspect ratio
+    pub size: u32,
+    #[serde(default)]
+    pub error_correction: ErrorCorrection,
+    #[serde(default)]
+    pub format: ImageFormat,
+    /// The width of the blank margin around the code, in modules. None - the symbology's recommended
+    /// quiet zone
+    #[serde(default)]
+    pub margin: Option<u32>,
+}
+
+impl Sample for GenerateCodeRequest {
+    fn sample() -> Self {
+        GenerateCodeRequest {
+            content: "https://wascc.dev".to_string(),
+            symbology: Symbology::Qr,
+            size: 256,
+            error_correction: ErrorCorrection::Quartile,
+            format: ImageFormat::Png,
+            margin: None,
+        }
+    }
+}
+
+/// A generated code image
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedCode {
+    pub format: ImageFormat,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub image: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A request to find and decode the codes contained in an image
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeImageRequest {
+    /// The raw bytes of the image, in any format the provider supports (e.g. PNG or JPEG)
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub image: Vec<u8>,
+    /// Only codes of these symbologies are reported. Empty - all symbologies the provider recognizes
+    #[serde(default)]
+    pub symbologies: Vec<Symbology>,
+}
+
+/// A single code found in an image
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCode {
+    pub symbology: Symbology,
+    pub content: String,
+    /// The corners of the code within the image, as (x, y) pixel coordinates, clockwise from top left
+    #[serde(default)]
+    pub corners: Vec<(u32, u32)>,
+}
+
+/// The codes found in an image, in no particular order. Empty if the image contains no readable codes
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedContent {
+    #[serde(default)]
+    pub codes: Vec<DecodedCode>,
+}
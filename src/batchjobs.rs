@@ -0,0 +1,203 @@
+//! # Batch Jobs
+//!
+//! This module contains data types for the `wascc:batch` capability provider, which runs finite batch
+//! and ETL workloads on a compute backend such as Kubernetes Jobs. Actors submit jobs, poll or are
+//! notified of their status, and cancel them
+
+use crate::blobstore::BlobReference;
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a SubmitJob to the capability provider, receives a JobHandle back
+pub const OP_SUBMIT_JOB: &str = "SubmitJob";
+/// Guest sends a JobStatusQuery to the capability provider, receives a JobStatus back
+pub const OP_QUERY_JOB_STATUS: &str = "QueryJobStatus";
+/// Guest sends a CancelJob to the capability provider, lack of error indicates success
+pub const OP_CANCEL_JOB: &str = "CancelJob";
+/// Provider sends a JobStatus to the guest when a job submitted with `notify` set finishes
+pub const OP_DELIVER_JOB_STATUS: &str = "DeliverJobStatus";
+
+/// The code a job runs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Workload {
+    /// A container image, e.g. `registry.example.com/etl/nightly:1.4`
+    Image { image: String },
+    /// A WebAssembly module, identified by its public key or a registry reference
+    Module { module: String },
+}
+
+/// The compute resources a job requests. Zero - the backend's default
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceRequests {
+    /// Thousandths of a CPU core
+    #[serde(default)]
+    pub cpu_millis: u32,
+    #[serde(default)]
+    pub memory_bytes: u64,
+    #[serde(default)]
+    pub gpus: u32,
+}
+
+/// A request to run a job
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitJob {
+    /// A human-readable name for the job, used as a prefix of the job ID by most backends
+    pub name: String,
+    pub workload: Workload,
+    /// Command-line arguments passed to the workload
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Parameters passed to the workload as environment variables
+    #[serde(default)]
+    pub parameters: HashMap<String, String>,
+    #[serde(default)]
+    pub resources: ResourceRequests,
+    /// How long the job may run before it is killed and marked failed. None - no limit
+    #[serde(default)]
+    pub timeout: Option<DurationMs>,
+    /// The number of times a failed job is retried before it is marked failed
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Deliver the job's final status to the guest with `OP_DELIVER_JOB_STATUS`
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl Sample for SubmitJob {
+    fn sample() -> Self {
+        let mut parameters = HashMap::new();
+        parameters.insert("SOURCE_BUCKET".to_string(), "raw-events".to_string());
+        SubmitJob {
+            name: "nightly-rollup".to_string(),
+            workload: Workload::Image {
+                image: "registry.example.com/etl/rollup:1.4".to_string(),
+            },
+            args: vec!["--date".to_string(), "2020-05-01".to_string()],
+            parameters,
+            resources: ResourceRequests {
+                cpu_millis: 2000,
+                memory_bytes: 4 * 1024 * 1024 * 1024,
+                gpus: 0,
+            },
+            timeout: Some(DurationMs::from_secs(3600)),
+            max_retries: 2,
+            notify: true,
+        }
+    }
+}
+
+impl SubmitJob {
+    pub fn new(name: &str, workload: Workload) -> SubmitJob {
+        SubmitJob {
+            name: name.to_string(),
+            workload,
+            args: Vec::new(),
+            parameters: HashMap::new(),
+            resources: ResourceRequests::default(),
+            timeout: None,
+            max_retries: 0,
+            notify: false,
+        }
+    }
+
+    pub fn with_arg(mut self, arg: &str) -> SubmitJob {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    pub fn with_parameter(mut self, name: &str, value: &str) -> SubmitJob {
+        self.parameters.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_resources(mut self, resources: ResourceRequests) -> SubmitJob {
+        self.resources = resources;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: DurationMs) -> SubmitJob {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Identifies a job submitted with `OP_SUBMIT_JOB`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobHandle {
+    pub job_id: String,
+}
+
+/// A request for the status of a job
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatusQuery {
+    pub job_id: String,
+}
+
+/// The lifecycle state of a job
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// The job is waiting to be scheduled
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl JobState {
+    /// Indicates whether the job has finished and its state will no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobState::Succeeded | JobState::Failed | JobState::Canceled
+        )
+    }
+}
+
+/// How the last attempt of a job exited
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitInfo {
+    pub code: i32,
+    /// The backend's explanation of the exit, e.g. `OOMKilled` or `DeadlineExceeded`
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// The status of a job
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    pub job_id: String,
+    pub state: JobState,
+    pub submitted_at: Timestamp,
+    #[serde(default)]
+    pub started_at: Option<Timestamp>,
+    #[serde(default)]
+    pub finished_at: Option<Timestamp>,
+    /// The number of times the job has been started, including retries
+    #[serde(default)]
+    pub attempts: u32,
+    /// Present once an attempt has exited
+    #[serde(default)]
+    pub exit: Option<ExitInfo>,
+    /// The blob the job's output is collected in, if the provider collects logs
+    #[serde(default)]
+    pub logs: Option<BlobReference>,
+}
+
+/// A request to stop a job. Canceling a job that has already finished has no effect
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelJob {
+    pub job_id: String,
+    #[serde(default)]
+    pub reason: String,
+}
@@ -4,7 +4,10 @@
 //! how the blob store capability works within the constraints of a WebAssembly host runtime, check out
 //! the documentation on [waSCC.dev](https://wascc.dev)
 
+use crate::core::Timestamp;
+use crate::preview::BytesPreview;
 use crate::Sample;
+use std::fmt;
 
 /// Guest sends a Container to the capability provider, receives a Container back
 pub const OP_CREATE_CONTAINER: &str = "CreateContainer";
@@ -17,18 +20,30 @@ pub const OP_LIST_OBJECTS: &str = "ListObjects";
 /// Guest sends a FileChunk to capability provider for storing as part of a Blob, lack of error indicates success
 pub const OP_UPLOAD_CHUNK: &str = "UploadChunk";
 /// Guest sends a StreamRequest to the capability provider, immediate termination w/success. Guest will then
-/// start receiving OP_RECEIVE_CHUNK operations from the provider as chunks are streamed to the guest
+/// start receiving OP_RECEIVE_CHUNK operations from the provider as chunks are streamed to the guest. Providers
+/// that support resuming downloads return a Transfer describing the download
 pub const OP_START_DOWNLOAD: &str = "StartDownload";
+/// Guest sends a ResumeDownloadRequest to the capability provider, immediate termination w/success. Guest will
+/// then receive OP_RECEIVE_CHUNK operations starting at the requested sequence number
+pub const OP_RESUME_DOWNLOAD: &str = "ResumeDownload";
+/// Guest sends a TransferStatusRequest to the capability provider, receives a TransferStatus back
+pub const OP_GET_TRANSFER_STATUS: &str = "GetTransferStatus";
 /// Guest sends a metadata-carrying FileChunk to initiate an upload, lack of error is success
 pub const OP_START_UPLOAD: &str = "StartUpload";
 /// Guest will receive a FileChunk for each piece of a file requested to download
 pub const OP_RECEIVE_CHUNK: &str = "ReceiveChunk";
-/// Query information on a single blob. Guest sends an incomplete blob struct and gets a complete one in return
+/// Query information on a single blob. Guest sends an incomplete blob struct (or an ObjectInfoRequest) and gets
+/// a complete one in return, or a NotModified if the request's conditions indicate the blob is unchanged
 pub const OP_GET_OBJECT_INFO: &str = "GetObjectInfo";
+/// Guest sends a DeleteObjectsRequest to the capability provider, receives a DeleteResult back
+pub const OP_DELETE_OBJECTS: &str = "DeleteObjects";
+/// Guest sends a ContainerStatsRequest to the capability provider, receives ContainerStats back
+pub const OP_GET_CONTAINER_STATS: &str = "GetContainerStats";
 
 /// Represents a single chunk of a segmented file stream
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct FileChunk {
     /// A sequence number that can be used for retry and ordering logic
     pub sequence_no: u64,
@@ -46,6 +61,19 @@ pub struct FileChunk {
     pub chunk_bytes: Vec<u8>,
 }
 
+impl fmt::Debug for FileChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileChunk")
+            .field("sequence_no", &self.sequence_no)
+            .field("container", &self.container)
+            .field("id", &self.id)
+            .field("total_bytes", &self.total_bytes)
+            .field("chunk_size", &self.chunk_size)
+            .field("chunk_bytes", &BytesPreview(&self.chunk_bytes))
+            .finish()
+    }
+}
+
 impl Sample for FileChunk {
     fn sample() -> Self {
         FileChunk {
@@ -59,16 +87,52 @@ impl Sample for FileChunk {
     }
 }
 
+impl FileChunk {
+    /// Creates a new file chunk
+    pub fn new(
+        sequence_no: u64,
+        container: &str,
+        id: &str,
+        total_bytes: u64,
+        chunk_size: u64,
+        chunk_bytes: Vec<u8>,
+    ) -> FileChunk {
+        FileChunk {
+            sequence_no,
+            container: container.to_string(),
+            id: id.to_string(),
+            total_bytes,
+            chunk_size,
+            chunk_bytes,
+        }
+    }
+
+    /// Indicates whether this is the final chunk of the blob, based on its sequence number and the
+    /// chunk size
+    pub fn is_last(&self) -> bool {
+        (self.sequence_no + 1).saturating_mul(self.chunk_size) >= self.total_bytes
+    }
+}
+
 /// Represents a container within a blob store
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Container {
     pub id: String,
 }
 
+impl Container {
+    /// Creates a new container
+    pub fn new(id: &str) -> Container {
+        Container { id: id.to_string() }
+    }
+}
+
 /// Used to hold a list of containers
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ContainerList {
     #[serde(default)]
     pub containers: Vec<Container>,
@@ -84,9 +148,17 @@ impl Sample for ContainerList {
     }
 }
 
+impl ContainerList {
+    /// Creates a new container list
+    pub fn new(containers: Vec<Container>) -> ContainerList {
+        ContainerList { containers }
+    }
+}
+
 /// Metadata about a blob, not the raw bytes
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Blob {
     /// Unique ID of the blob
     pub id: String,
@@ -94,19 +166,81 @@ pub struct Blob {
     pub container: String,
     /// Total number of bytes of the blob (file size)
     pub byte_size: u64,
+    /// An opaque identifier for the current version of the blob's contents, if supported by the provider
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// The time at which the blob was last modified, if supported by the provider
+    #[serde(default)]
+    pub last_modified: Option<Timestamp>,
+}
+
+impl Blob {
+    /// Creates a new blob
+    pub fn new(id: &str, container: &str, byte_size: u64) -> Blob {
+        Blob {
+            id: id.to_string(),
+            container: container.to_string(),
+            byte_size,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Sets the identifier of the current version of the blob's contents
+    pub fn with_etag(mut self, etag: &str) -> Blob {
+        self.etag = Some(etag.to_string());
+        self
+    }
+
+    /// Sets the time at which the blob was last modified
+    pub fn with_last_modified(mut self, last_modified: Timestamp) -> Blob {
+        self.last_modified = Some(last_modified);
+        self
+    }
+}
+
+/// Identifies a blob stored in a blob store. Used by other capabilities to refer to large
+/// content (attachments, documents, media) rather than carrying the bytes inline
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BlobReference {
+    /// Container in which the blob resides
+    pub container: String,
+    /// Unique ID of the blob
+    pub id: String,
+}
+
+impl BlobReference {
+    /// Creates a new blob reference
+    pub fn new(container: &str, id: &str) -> BlobReference {
+        BlobReference {
+            container: container.to_string(),
+            id: id.to_string(),
+        }
+    }
 }
 
 /// A wrapper for a list of blobs
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BlobList {
     #[serde(default)]
     pub blobs: Vec<Blob>,
 }
 
+impl BlobList {
+    /// Creates a new blob list
+    pub fn new(blobs: Vec<Blob>) -> BlobList {
+        BlobList { blobs }
+    }
+}
+
 /// A request to begin downloading a stream for a blob
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct StreamRequest {
     /// The unique ID of the requested blob
     pub id: String,
@@ -114,12 +248,151 @@ pub struct StreamRequest {
     pub container: String,
     /// The preferred size of chunks to be delivered. Consumers must not assume this is the size of the chunks they will get
     pub chunk_size: u64,
+    /// Only start the download if the blob's current etag matches this value, otherwise fail
+    #[serde(default)]
+    pub if_match: Option<String>,
+    /// Respond with NotModified instead of starting the download if the blob's current etag matches this value
+    #[serde(default)]
+    pub if_none_match: Option<String>,
+    /// Respond with NotModified instead of starting the download if the blob has not changed since this time
+    #[serde(default)]
+    pub if_modified_since: Option<Timestamp>,
+}
+
+impl StreamRequest {
+    /// Creates a new stream request
+    pub fn new(id: &str, container: &str, chunk_size: u64) -> StreamRequest {
+        StreamRequest {
+            id: id.to_string(),
+            container: container.to_string(),
+            chunk_size,
+            if_match: None,
+            if_none_match: None,
+            if_modified_since: None,
+        }
+    }
+
+    /// Only starts the download if the blob's current etag matches the given value
+    pub fn with_if_match(mut self, if_match: &str) -> StreamRequest {
+        self.if_match = Some(if_match.to_string());
+        self
+    }
+
+    /// Responds with NotModified if the blob's current etag matches the given value
+    pub fn with_if_none_match(mut self, if_none_match: &str) -> StreamRequest {
+        self.if_none_match = Some(if_none_match.to_string());
+        self
+    }
+
+    /// Responds with NotModified if the blob has not changed since the given time
+    pub fn with_if_modified_since(mut self, if_modified_since: Timestamp) -> StreamRequest {
+        self.if_modified_since = Some(if_modified_since);
+        self
+    }
+}
+
+/// A conditional request for information on a single blob. This is wire-compatible with the incomplete `Blob`
+/// previously sent with `OP_GET_OBJECT_INFO`, so providers can decode either as this type
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ObjectInfoRequest {
+    /// The unique ID of the requested blob
+    pub id: String,
+    /// The container of the requested blob
+    pub container: String,
+    /// Only return the blob's information if its current etag matches this value, otherwise fail
+    #[serde(default)]
+    pub if_match: Option<String>,
+    /// Respond with NotModified if the blob's current etag matches this value
+    #[serde(default)]
+    pub if_none_match: Option<String>,
+    /// Respond with NotModified if the blob has not changed since this time
+    #[serde(default)]
+    pub if_modified_since: Option<Timestamp>,
+}
+
+impl ObjectInfoRequest {
+    /// Creates a new object info request
+    pub fn new(id: &str, container: &str) -> ObjectInfoRequest {
+        ObjectInfoRequest {
+            id: id.to_string(),
+            container: container.to_string(),
+            if_match: None,
+            if_none_match: None,
+            if_modified_since: None,
+        }
+    }
+
+    /// Only returns the blob's information if its current etag matches the given value
+    pub fn with_if_match(mut self, if_match: &str) -> ObjectInfoRequest {
+        self.if_match = Some(if_match.to_string());
+        self
+    }
+
+    /// Responds with NotModified if the blob's current etag matches the given value
+    pub fn with_if_none_match(mut self, if_none_match: &str) -> ObjectInfoRequest {
+        self.if_none_match = Some(if_none_match.to_string());
+        self
+    }
+
+    /// Responds with NotModified if the blob has not changed since the given time
+    pub fn with_if_modified_since(mut self, if_modified_since: Timestamp) -> ObjectInfoRequest {
+        self.if_modified_since = Some(if_modified_since);
+        self
+    }
+}
+
+/// Returned by the provider in place of a `Blob` (for `OP_GET_OBJECT_INFO`) or the empty or `Transfer`
+/// response (for `OP_START_DOWNLOAD`) when a request's conditions indicate the blob is unchanged. Failed
+/// `if_match` conditions are reported as errors rather than with this marker
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct NotModified {
+    /// The unique ID of the blob
+    pub id: String,
+    /// The container of the blob
+    pub container: String,
+    /// The blob's current etag, if supported by the provider
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// Always true. This field is required, which allows guests to distinguish this marker from a `Blob`
+    pub not_modified: bool,
+}
+
+impl NotModified {
+    /// Creates a new not-modified marker for the given blob
+    pub fn new(id: &str, container: &str, etag: Option<String>) -> NotModified {
+        NotModified {
+            id: id.to_string(),
+            container: container.to_string(),
+            etag,
+            not_modified: true,
+        }
+    }
+
+    /// Attempts to interpret a provider response as a not-modified marker. Returns `None` if the
+    /// response is empty or contains some other payload, such as a `Blob`
+    pub fn from_response(payload: &[u8]) -> Option<NotModified> {
+        if payload.is_empty() {
+            return None;
+        }
+        crate::deserialize::<NotModified>(payload)
+            .ok()
+            .filter(|nm| nm.not_modified)
+    }
 }
 
 /// Metadata about an in-progress file transfer
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Transfer {
+    /// Unique ID of the transfer, used to resume the transfer or query its status. Empty if the provider
+    /// does not support resuming transfers
+    #[serde(default)]
+    pub transfer_id: String,
     /// Unique ID of the blob
     pub blob_id: String,
     /// ID of the container
@@ -131,3 +404,271 @@ pub struct Transfer {
     /// Total number of chunks being transferred
     pub total_chunks: u64,
 }
+
+impl Transfer {
+    /// Creates a new transfer
+    pub fn new(
+        transfer_id: &str,
+        blob_id: &str,
+        container: &str,
+        chunk_size: u64,
+        total_size: u64,
+        total_chunks: u64,
+    ) -> Transfer {
+        Transfer {
+            transfer_id: transfer_id.to_string(),
+            blob_id: blob_id.to_string(),
+            container: container.to_string(),
+            chunk_size,
+            total_size,
+            total_chunks,
+        }
+    }
+}
+
+/// A request to resume an interrupted download, re-delivering chunks starting at a given sequence number
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ResumeDownloadRequest {
+    /// The ID of the transfer returned when the download was started
+    pub transfer_id: String,
+    /// The sequence number of the first chunk to deliver, typically one past the last chunk received
+    pub from_sequence_no: u64,
+}
+
+impl ResumeDownloadRequest {
+    /// Creates a new resume download request
+    pub fn new(transfer_id: &str, from_sequence_no: u64) -> ResumeDownloadRequest {
+        ResumeDownloadRequest {
+            transfer_id: transfer_id.to_string(),
+            from_sequence_no,
+        }
+    }
+}
+
+/// A request for the status of a transfer
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TransferStatusRequest {
+    pub transfer_id: String,
+}
+
+impl TransferStatusRequest {
+    /// Creates a new transfer status request
+    pub fn new(transfer_id: &str) -> TransferStatusRequest {
+        TransferStatusRequest {
+            transfer_id: transfer_id.to_string(),
+        }
+    }
+}
+
+/// The state of a transfer
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferState {
+    /// Chunks are still being delivered
+    InProgress,
+    /// All chunks have been delivered
+    Completed,
+    /// The transfer stopped due to an error and may be resumed
+    Interrupted,
+    /// The provider has discarded the transfer, it can no longer be resumed
+    Expired,
+}
+
+/// The status of a transfer
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TransferStatus {
+    pub transfer: Transfer,
+    pub state: TransferState,
+    /// The number of chunks successfully delivered so far
+    pub chunks_delivered: u64,
+    /// A description of the error that interrupted the transfer, if any
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl TransferStatus {
+    /// Creates a new transfer status
+    pub fn new(transfer: Transfer, state: TransferState, chunks_delivered: u64) -> TransferStatus {
+        TransferStatus {
+            transfer,
+            state,
+            chunks_delivered,
+            error: None,
+        }
+    }
+
+    /// Records the error that interrupted the transfer
+    pub fn with_error(mut self, error: &str) -> TransferStatus {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// A request to delete many objects from a container with a single operation
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeleteObjectsRequest {
+    pub container: String,
+    pub selection: DeleteSelection,
+}
+
+impl Sample for DeleteObjectsRequest {
+    fn sample() -> Self {
+        DeleteObjectsRequest {
+            container: "container".to_string(),
+            selection: DeleteSelection::Ids(vec!["blob1".to_string(), "blob2".to_string()]),
+        }
+    }
+}
+
+impl DeleteObjectsRequest {
+    /// Creates a new delete objects request
+    pub fn new(container: &str, selection: DeleteSelection) -> DeleteObjectsRequest {
+        DeleteObjectsRequest {
+            container: container.to_string(),
+            selection,
+        }
+    }
+}
+
+/// The objects to be deleted by a `DeleteObjectsRequest`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteSelection {
+    /// Delete the objects with exactly these IDs
+    Ids(Vec<String>),
+    /// Delete every object whose ID begins with this prefix, e.g. `reports/2020/`
+    Prefix(String),
+}
+
+/// The outcome of deleting a single object
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeleteOutcome {
+    pub id: String,
+    /// Indicates whether the object was deleted. False if the object did not exist or could not be deleted
+    pub deleted: bool,
+    /// A description of the error that prevented deletion, if any
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl DeleteOutcome {
+    /// Creates a new delete outcome
+    pub fn new(id: &str, deleted: bool) -> DeleteOutcome {
+        DeleteOutcome {
+            id: id.to_string(),
+            deleted,
+            error: None,
+        }
+    }
+
+    /// Records the error that prevented deletion
+    pub fn with_error(mut self, error: &str) -> DeleteOutcome {
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// The result of a bulk delete, with an outcome for every object selected
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeleteResult {
+    #[serde(default)]
+    pub outcomes: Vec<DeleteOutcome>,
+}
+
+impl DeleteResult {
+    /// Creates a new delete result
+    pub fn new(outcomes: Vec<DeleteOutcome>) -> DeleteResult {
+        DeleteResult { outcomes }
+    }
+
+    /// The number of objects that were deleted
+    pub fn deleted_count(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.deleted).count()
+    }
+
+    /// The outcomes of objects that could not be deleted due to an error
+    pub fn failures(&self) -> impl Iterator<Item = &DeleteOutcome> {
+        self.outcomes.iter().filter(|o| o.error.is_some())
+    }
+}
+
+/// A request for usage statistics of a container
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ContainerStatsRequest {
+    pub container: String,
+}
+
+impl ContainerStatsRequest {
+    /// Creates a new container stats request
+    pub fn new(container: &str) -> ContainerStatsRequest {
+        ContainerStatsRequest {
+            container: container.to_string(),
+        }
+    }
+}
+
+/// Usage statistics of a container
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ContainerStats {
+    pub container: String,
+    /// The number of objects in the container
+    pub object_count: u64,
+    /// The sum of the sizes of all objects in the container
+    pub total_bytes: u64,
+}
+
+impl ContainerStats {
+    /// Creates a new container stats
+    pub fn new(container: &str, object_count: u64, total_bytes: u64) -> ContainerStats {
+        ContainerStats {
+            container: container.to_string(),
+            object_count,
+            total_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Blob, NotModified, ObjectInfoRequest};
+    use crate::{deserialize, serialize};
+
+    #[test]
+    fn not_modified_distinguishable_from_blob() {
+        let blob = Blob {
+            id: "blob".to_string(),
+            container: "container".to_string(),
+            byte_size: 100,
+            etag: Some("abc".to_string()),
+            last_modified: None,
+        };
+        let blob_bytes = serialize(&blob).unwrap();
+        assert_eq!(NotModified::from_response(&blob_bytes), None);
+        assert_eq!(NotModified::from_response(&[]), None);
+
+        let nm = NotModified::new("blob", "container", Some("abc".to_string()));
+        let nm_bytes = serialize(&nm).unwrap();
+        assert_eq!(NotModified::from_response(&nm_bytes), Some(nm));
+        assert!(deserialize::<Blob>(&nm_bytes).is_err());
+
+        let req: ObjectInfoRequest = deserialize(&blob_bytes).unwrap();
+        assert_eq!(req.id, "blob");
+        assert_eq!(req.if_none_match, None);
+    }
+}
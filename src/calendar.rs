@@ -0,0 +1,153 @@
+//! # Calendar and Scheduling
+//!
+//! This module contains data types for the `wascc:calendar` capability provider, allowing actors
+//! to manage calendar events and query availability against CalDAV or hosted calendar services
+
+use crate::core::Timestamp;
+use crate::Sample;
+
+/// Guest sends a CalendarEvent (without an ID) to the provider, receives the created CalendarEvent back
+pub const OP_CREATE_EVENT: &str = "CreateEvent";
+/// Guest sends a complete CalendarEvent to the provider, lack of error indicates success
+pub const OP_UPDATE_EVENT: &str = "UpdateEvent";
+/// Guest sends a DeleteEventRequest to the provider, lack of error indicates success
+pub const OP_DELETE_EVENT: &str = "DeleteEvent";
+/// Guest sends an EventQuery to the provider, receives an EventList back
+pub const OP_LIST_EVENTS: &str = "ListEvents";
+/// Guest sends an AvailabilityQuery to the provider, receives a FreeBusyResponse back
+pub const OP_QUERY_AVAILABILITY: &str = "QueryAvailability";
+
+/// A single (possibly recurring) event on a calendar
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    /// The provider-assigned unique ID of the event. Empty when creating a new event
+    pub id: String,
+    /// The calendar on which the event resides
+    pub calendar_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub location: String,
+    /// Start of the (first occurrence of the) event
+    pub start: Timestamp,
+    /// End of the (first occurrence of the) event
+    pub end: Timestamp,
+    /// Indicates that only the date portions of start and end are significant
+    pub all_day: bool,
+    /// An RFC 5545 recurrence rule, e.g. `FREQ=WEEKLY;BYDAY=MO,WE`, for recurring events
+    #[serde(default)]
+    pub recurrence_rule: Option<String>,
+    #[serde(default)]
+    pub attendees: Vec<Attendee>,
+}
+
+impl Sample for CalendarEvent {
+    fn sample() -> Self {
+        CalendarEvent {
+            id: "evt-1".to_string(),
+            calendar_id: "team".to_string(),
+            title: "Standup".to_string(),
+            description: "Daily standup".to_string(),
+            location: "Room 1".to_string(),
+            start: Timestamp::from_secs(1_594_036_800),
+            end: Timestamp::from_secs(1_594_037_700),
+            all_day: false,
+            recurrence_rule: Some("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR".to_string()),
+            attendees: vec![Attendee {
+                email: "alice@example.com".to_string(),
+                name: "Alice".to_string(),
+                status: AttendeeStatus::Accepted,
+                optional: false,
+            }],
+        }
+    }
+}
+
+/// A participant in a calendar event
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attendee {
+    pub email: String,
+    #[serde(default)]
+    pub name: String,
+    pub status: AttendeeStatus,
+    /// Indicates the attendee's presence is not required
+    pub optional: bool,
+}
+
+/// An attendee's response to an event invitation
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendeeStatus {
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+/// A request to delete an event. Deleting a recurring event deletes all occurrences
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteEventRequest {
+    pub calendar_id: String,
+    pub event_id: String,
+}
+
+/// A query for the events on a calendar that overlap a time window. Recurring events are
+/// expanded into individual occurrences by the provider
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventQuery {
+    pub calendar_id: String,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// Maximum number of events to return, 0 - provider default
+    pub max_results: u32,
+}
+
+/// The events returned from an event query
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventList {
+    #[serde(default)]
+    pub events: Vec<CalendarEvent>,
+}
+
+/// A request for the busy periods of one or more calendars within a time window
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityQuery {
+    /// The calendars (or attendee email addresses, if supported by the provider) to check
+    #[serde(default)]
+    pub calendars: Vec<String>,
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+/// A span of time during which a calendar is busy
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BusyPeriod {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+/// The busy periods of a single calendar
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFreeBusy {
+    pub calendar: String,
+    #[serde(default)]
+    pub busy: Vec<BusyPeriod>,
+}
+
+/// The results of an availability query. Any time within the query window not covered by a
+/// busy period is free
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeBusyResponse {
+    #[serde(default)]
+    pub calendars: Vec<CalendarFreeBusy>,
+}
@@ -16,16 +16,197 @@
 
 use std::error::Error;
 
+use crate::core::{invocation_digest, DurationMs, ResponseFreshness, Timestamp};
+use crate::errors::WasccCodecError;
+use crate::intern::{ActorRef, OpName};
 use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::fmt;
+use std::str::FromStr;
 
 /// All capability providers must respond to this operation, which will be requested by
 /// the host (the `system` actor)
 pub const OP_GET_CAPABILITY_DESCRIPTOR: &str = "GetCapabilityDescriptor";
+/// Host sends a DrainStart to a provider that is about to be replaced, receives a DrainProgress back. The
+/// provider stops accepting new invocations and finishes those in flight
+pub const OP_DRAIN_START: &str = "DrainStart";
+/// Provider sends a DrainProgress to the host (the `system` actor) as in-flight invocations finish
+pub const OP_DRAIN_PROGRESS: &str = "DrainProgress";
+/// Provider sends a DrainComplete to the host (the `system` actor) once it has drained, after which the
+/// host may unload it
+pub const OP_DRAIN_COMPLETE: &str = "DrainComplete";
 
 /// The dispatcher is used by a native capability provider to send commands to an actor module, expecting
 /// a result containing a byte array in return
 pub trait Dispatcher: Any + Send + Sync {
     fn dispatch(&self, actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>>;
+
+    /// Dispatches using interned identifiers. Hosts that key their routing tables by `ActorRef` and
+    /// `OpName` can override this to route without allocating; by default it delegates to `dispatch`
+    fn dispatch_interned(
+        &self,
+        actor: &ActorRef,
+        op: &OpName,
+        msg: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        self.dispatch(actor, op, msg)
+    }
+}
+
+/// A capability ID such as `wascc:messaging`, in the form `namespace:name`. Serialized as a plain string.
+/// Conversions from strings do not validate the ID; use `CapabilityId::parse` where validation is needed
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CapabilityId(Cow<'static, str>);
+
+impl CapabilityId {
+    /// Creates a capability ID from a static string without validating it
+    pub const fn from_static(id: &'static str) -> CapabilityId {
+        CapabilityId(Cow::Borrowed(id))
+    }
+
+    /// Parses and validates a capability ID. Both the namespace and the name must be non-empty and may
+    /// only contain ASCII letters, digits, `_`, `-`, and `.`
+    pub fn parse(id: &str) -> Result<CapabilityId, WasccCodecError> {
+        let invalid = |reason: &str| WasccCodecError::InvalidCapabilityId {
+            id: id.to_string(),
+            reason: reason.to_string(),
+        };
+        let (namespace, name) = id
+            .split_once(':')
+            .ok_or_else(|| invalid("expected the form namespace:name"))?;
+        let valid_part = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        };
+        if !valid_part(namespace) {
+            Err(invalid("invalid namespace"))
+        } else if !valid_part(name) {
+            Err(invalid("invalid name"))
+        } else {
+            Ok(CapabilityId(Cow::Owned(id.to_string())))
+        }
+    }
+
+    /// Indicates whether this ID would be accepted by `CapabilityId::parse`
+    pub fn is_valid(&self) -> bool {
+        CapabilityId::parse(&self.0).is_ok()
+    }
+
+    /// The namespace portion of the ID, e.g. `wascc` for `wascc:messaging`. Empty if the ID has no namespace
+    pub fn namespace(&self) -> &str {
+        self.0.split_once(':').map(|(ns, _)| ns).unwrap_or("")
+    }
+
+    /// The name portion of the ID, e.g. `messaging` for `wascc:messaging`
+    pub fn name(&self) -> &str {
+        self.0.split_once(':').map(|(_, n)| n).unwrap_or(&self.0)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CapabilityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for CapabilityId {
+    type Err = WasccCodecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CapabilityId::parse(s)
+    }
+}
+
+impl AsRef<str> for CapabilityId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for CapabilityId {
+    fn from(id: &str) -> CapabilityId {
+        CapabilityId(Cow::Owned(id.to_string()))
+    }
+}
+
+impl From<String> for CapabilityId {
+    fn from(id: String) -> CapabilityId {
+        CapabilityId(Cow::Owned(id))
+    }
+}
+
+impl From<CapabilityId> for String {
+    fn from(id: CapabilityId) -> String {
+        id.0.into_owned()
+    }
+}
+
+impl PartialEq<str> for CapabilityId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for CapabilityId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for CapabilityId {
+    fn eq(&self, other: &String) -> bool {
+        self.0 == other.as_str()
+    }
+}
+
+/// Capability IDs of the capabilities defined by this crate
+pub mod well_known {
+    use super::CapabilityId;
+
+    pub const MESSAGING: CapabilityId = CapabilityId::from_static("wascc:messaging");
+    pub const HTTP_SERVER: CapabilityId = CapabilityId::from_static("wascc:http_server");
+    pub const HTTP_CLIENT: CapabilityId = CapabilityId::from_static("wascc:http_client");
+    pub const KEY_VALUE: CapabilityId = CapabilityId::from_static("wascc:keyvalue");
+    pub const BLOB_STORE: CapabilityId = CapabilityId::from_static("wascc:blobstore");
+    pub const EVENT_STREAMS: CapabilityId = CapabilityId::from_static("wascc:eventstreams");
+    pub const LOGGING: CapabilityId = CapabilityId::from_static("wascc:logging");
+    pub const EXTRAS: CapabilityId = CapabilityId::from_static("wascc:extras");
+    pub const METERING: CapabilityId = CapabilityId::from_static("wascc:metering");
+    pub const DEVICE: CapabilityId = CapabilityId::from_static("wascc:device");
+    pub const CALENDAR: CapabilityId = CapabilityId::from_static("wascc:calendar");
+    pub const EMAIL_PARSE: CapabilityId = CapabilityId::from_static("wascc:emailparse");
+    pub const DOC_EXTRACT: CapabilityId = CapabilityId::from_static("wascc:docextract");
+    pub const GRAPHQL: CapabilityId = CapabilityId::from_static("wascc:graphql");
+    pub const SERVICE_DISCOVERY: CapabilityId = CapabilityId::from_static("wascc:servicediscovery");
+    pub const OUTBOX: CapabilityId = CapabilityId::from_static("wascc:outbox");
+    pub const PUSH_WEBHOOK: CapabilityId = CapabilityId::from_static("wascc:push-webhook");
+    pub const TEXT_GEN: CapabilityId = CapabilityId::from_static("wascc:textgen");
+    pub const COORDINATION: CapabilityId = CapabilityId::from_static("wascc:coordination");
+    pub const STATEMACHINE: CapabilityId = CapabilityId::from_static("wascc:statemachine");
+    pub const SOCKET: CapabilityId = CapabilityId::from_static("wascc:socket");
+    pub const SESSION: CapabilityId = CapabilityId::from_static("wascc:session");
+    pub const ATTESTATION: CapabilityId = CapabilityId::from_static("wascc:attestation");
+    pub const HEALTH_PROBE: CapabilityId = CapabilityId::from_static("wascc:healthprobe");
+    pub const DOC_GEN: CapabilityId = CapabilityId::from_static("wascc:docgen");
+    pub const VECTOR_STORE: CapabilityId = CapabilityId::from_static("wascc:vectorstore");
+    pub const LEADERBOARD: CapabilityId = CapabilityId::from_static("wascc:leaderboard");
+    pub const BARCODES: CapabilityId = CapabilityId::from_static("wascc:barcodes");
+    pub const DATA_FEED: CapabilityId = CapabilityId::from_static("wascc:datafeed");
+    pub const TELEPHONY: CapabilityId = CapabilityId::from_static("wascc:telephony");
+    pub const CRDT: CapabilityId = CapabilityId::from_static("wascc:crdt");
+    pub const BATCH: CapabilityId = CapabilityId::from_static("wascc:batch");
+    pub const ANOMALY: CapabilityId = CapabilityId::from_static("wascc:anomaly");
+    pub const SQL_DB: CapabilityId = CapabilityId::from_static("wascc:sqldb");
+    pub const TERMINAL: CapabilityId = CapabilityId::from_static("wascc:terminal");
 }
 
 /// Metadata describing the capability provider and the operations it supports
@@ -33,7 +214,7 @@ pub trait Dispatcher: Any + Send + Sync {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct CapabilityDescriptor {
     /// The capability ID of the provider, e.g. `wascc:messaging` or `thirdparty:someprovider`
-    pub id: String,
+    pub id: CapabilityId,
     /// The human-friendly name of the provider, displayed in short messages and log entries
     pub name: String,
     /// A semver string representing the version of the provider module
@@ -44,12 +225,81 @@ pub struct CapabilityDescriptor {
     pub long_description: String,
     /// A list of all of the operations supported by this provider
     pub supported_operations: Vec<OperationDescriptor>,
+    /// Capability-specific metadata, keyed by name, e.g. the key limits of a key-value provider under
+    /// `keyvalue::KeyValidation::EXTENSION`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extensions: HashMap<String, serde_json::Value>,
+    /// Other capabilities this provider requires the host to have loaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<CapabilityDependency>,
+    /// Resources the provider needs when the host runs it in a sandbox or separate process
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceRequirements>,
+    /// Declares that the provider partitions its state across instances, so that hosts route each
+    /// invocation to the instance owning its shard (see `core::ShardHint`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sharding: Option<ShardingSupport>,
+}
+
+/// How a sharded provider partitions its state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShardingSupport {
+    /// The number of shards. Each instance of the provider owns one, assigned in the initialize
+    /// handshake
+    pub shard_count: u32,
 }
 
 impl CapabilityDescriptor {
     pub fn builder() -> CapabilityDescriptorBuilder {
         CapabilityDescriptorBuilder::new()
     }
+
+    /// Returns the required dependencies of this provider that are not satisfied by the given set of
+    /// loaded providers, either because the capability is absent or its revision is too old. Optional
+    /// dependencies are never reported
+    pub fn missing_dependencies<'a>(
+        &'a self,
+        loaded: &[CapabilityDescriptor],
+    ) -> Vec<&'a CapabilityDependency> {
+        self.dependencies
+            .iter()
+            .filter(|dep| !dep.optional)
+            .filter(|dep| {
+                !loaded
+                    .iter()
+                    .any(|d| d.id == dep.capability_id && d.revision >= dep.min_revision)
+            })
+            .collect()
+    }
+}
+
+/// Declares that a provider depends on another capability being present in the host
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CapabilityDependency {
+    /// The capability ID of the required provider, e.g. `wascc:keyvalue`
+    pub capability_id: CapabilityId,
+    /// The minimum revision of the required provider
+    pub min_revision: u32,
+    /// Indicates that the provider can function (perhaps with reduced features) without this dependency
+    pub optional: bool,
+}
+
+/// Declares the resources a native capability provider needs, allowing hosts that sandbox providers (or run
+/// them as separate processes) to schedule and constrain them without out-of-band configuration
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ResourceRequirements {
+    /// The minimum memory, in bytes, the provider needs to function
+    pub min_memory_bytes: u64,
+    /// The maximum memory, in bytes, the provider is expected to use, 0 - no limit
+    pub max_memory_bytes: u64,
+    /// A hint of the CPU the provider expects to use, in thousandths of a core, 0 - no hint
+    pub cpu_millicores: u32,
+    /// The maximum number of open file descriptors (including sockets) the provider needs, 0 - no limit
+    pub max_file_descriptors: u32,
+    /// Indicates the provider needs to make outbound network connections
+    pub network_egress: bool,
 }
 
 /// A fluent syntax builder for creating a capability descriptor
@@ -68,7 +318,7 @@ impl CapabilityDescriptorBuilder {
     pub fn id(self, id: &str) -> Self {
         CapabilityDescriptorBuilder {
             descriptor: CapabilityDescriptor {
-                id: id.to_string(),
+                id: CapabilityId::from(id),
                 ..self.descriptor
             },
         }
@@ -126,6 +376,73 @@ impl CapabilityDescriptorBuilder {
         }
     }
 
+    /// Adds an operation descriptor, along with the limits the provider enforces on it, to the
+    /// provider descriptor
+    pub fn with_limited_operation(
+        self,
+        name: &str,
+        direction: OperationDirection,
+        doctext: &str,
+        limits: OperationLimits,
+    ) -> Self {
+        let mut newops = self.descriptor.supported_operations;
+        newops.push(OperationDescriptor::new(name, direction, doctext).with_limits(limits));
+        CapabilityDescriptorBuilder {
+            descriptor: CapabilityDescriptor {
+                supported_operations: newops,
+                ..self.descriptor
+            },
+        }
+    }
+
+    /// Adds a capability-specific metadata entry to the provider descriptor
+    pub fn with_extension(self, name: &str, value: serde_json::Value) -> Self {
+        let mut extensions = self.descriptor.extensions;
+        extensions.insert(name.to_string(), value);
+        CapabilityDescriptorBuilder {
+            descriptor: CapabilityDescriptor {
+                extensions,
+                ..self.descriptor
+            },
+        }
+    }
+
+    /// Adds a dependency on another capability to the provider descriptor
+    pub fn with_dependency(self, capability_id: &str, min_revision: u32, optional: bool) -> Self {
+        let mut deps = self.descriptor.dependencies;
+        deps.push(CapabilityDependency {
+            capability_id: CapabilityId::from(capability_id),
+            min_revision,
+            optional,
+        });
+        CapabilityDescriptorBuilder {
+            descriptor: CapabilityDescriptor {
+                dependencies: deps,
+                ..self.descriptor
+            },
+        }
+    }
+
+    /// Sets the resources the provider needs when run in a sandbox
+    pub fn resources(self, resources: ResourceRequirements) -> Self {
+        CapabilityDescriptorBuilder {
+            descriptor: CapabilityDescriptor {
+                resources: Some(resources),
+                ..self.descriptor
+            },
+        }
+    }
+
+    /// Declares the provider sharded into the given number of shards
+    pub fn sharded(self, shard_count: u32) -> Self {
+        CapabilityDescriptorBuilder {
+            descriptor: CapabilityDescriptor {
+                sharding: Some(ShardingSupport { shard_count }),
+                ..self.descriptor
+            },
+        }
+    }
+
     /// Produces a new capability descriptor from the builder's configuration
     pub fn build(self) -> CapabilityDescriptor {
         self.descriptor
@@ -142,6 +459,12 @@ pub struct OperationDescriptor {
     pub direction: OperationDirection,
     /// Documentation-suitable text for this operation
     pub doctext: String,
+    /// Limits the provider enforces on invocations of this operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<OperationLimits>,
+    /// Whether, and for how long, hosts may cache the results of this operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheControl>,
 }
 
 impl OperationDescriptor {
@@ -151,8 +474,111 @@ impl OperationDescriptor {
             name: name.to_string(),
             direction,
             doctext: doctext.to_string(),
+            limits: None,
+            cache: None,
         }
     }
+
+    /// Declares the limits the provider enforces on invocations of this operation
+    pub fn with_limits(self, limits: OperationLimits) -> OperationDescriptor {
+        OperationDescriptor {
+            limits: Some(limits),
+            ..self
+        }
+    }
+
+    /// Declares the results of this operation cacheable
+    pub fn with_cache_control(self, cache: CacheControl) -> OperationDescriptor {
+        OperationDescriptor {
+            cache: Some(cache),
+            ..self
+        }
+    }
+}
+
+/// Caching rules for an idempotent operation, advertised so that hosts can answer repeated invocations
+/// from a cache instead of invoking the provider. Operations without cache control are never cached
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CacheControl {
+    /// How long a result stays fresh after the provider produced it
+    pub ttl: DurationMs,
+    /// The top-level payload fields that identify a result. Invocations that agree on these fields
+    /// share a cached result. Empty - the entire payload
+    #[serde(default)]
+    pub vary_by: Vec<String>,
+    /// Results may be shared between actors. Otherwise each actor has its own cache entries, which is
+    /// required whenever the result depends on the invoking actor, e.g. through its binding
+    #[serde(default)]
+    pub shared: bool,
+}
+
+impl CacheControl {
+    /// Declares results cacheable for the given time, separately for each actor
+    pub fn new(ttl: DurationMs) -> CacheControl {
+        CacheControl {
+            ttl,
+            vary_by: Vec::new(),
+            shared: false,
+        }
+    }
+
+    pub fn vary_by(mut self, field: &str) -> CacheControl {
+        self.vary_by.push(field.to_string());
+        self
+    }
+
+    pub fn shared(self) -> CacheControl {
+        CacheControl {
+            shared: true,
+            ..self
+        }
+    }
+
+    /// Computes the key under which the result of an invocation is cached. The key is the
+    /// `invocation_digest` of the invocation with its payload reduced to the `vary_by` fields, and with
+    /// an empty origin if results are shared. Fails if the payload cannot be decoded
+    pub fn cache_key(
+        &self,
+        origin: &str,
+        target: &str,
+        operation: &str,
+        payload: &[u8],
+    ) -> Result<String, Box<dyn Error>> {
+        let origin = if self.shared { "" } else { origin };
+        if self.vary_by.is_empty() {
+            return Ok(invocation_digest(origin, target, operation, payload));
+        }
+        let value = crate::deserialize_to_value(payload)?;
+        let fields: Vec<serde_json::Value> = self
+            .vary_by
+            .iter()
+            .map(|f| value.get(f).cloned().unwrap_or(serde_json::Value::Null))
+            .collect();
+        let material = serde_json::to_vec(&fields)?;
+        Ok(invocation_digest(origin, target, operation, &material))
+    }
+
+    /// The freshness of a result the provider produced at the given time
+    pub fn freshness(&self, produced_at: Timestamp) -> ResponseFreshness {
+        ResponseFreshness {
+            produced_at,
+            ttl: self.ttl,
+            from_cache: false,
+        }
+    }
+}
+
+/// Limits a provider enforces on invocations of a single operation, advertised so that actors and
+/// hosts can pace their calls instead of discovering the limits through errors
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct OperationLimits {
+    /// The sustained number of invocations per second the provider accepts, 0 - no limit
+    pub rate_per_second: u32,
+    /// The number of invocations that may exceed the sustained rate in a short burst
+    pub burst: u32,
+    /// The largest request payload, in bytes, the provider accepts, 0 - no limit
+    pub max_payload_bytes: u64,
 }
 
 /// Represents the direction of an operation invocation
@@ -165,6 +591,120 @@ pub enum OperationDirection {
     Both,
 }
 
+impl OperationDirection {
+    /// Indicates whether an operation declared with this direction may be invoked in the
+    /// attempted direction. Bi-directional operations permit invocation in either direction
+    pub fn permits(&self, attempted: &OperationDirection) -> bool {
+        *self == OperationDirection::Both || self == attempted
+    }
+}
+
+/// Validates invocations against the operations declared in a provider's capability descriptor
+pub struct DirectionGuard<'a> {
+    descriptor: &'a CapabilityDescriptor,
+}
+
+impl<'a> DirectionGuard<'a> {
+    /// Creates a new direction guard for the given descriptor
+    pub fn new(descriptor: &'a CapabilityDescriptor) -> DirectionGuard<'a> {
+        DirectionGuard { descriptor }
+    }
+
+    /// Verifies that the operation is declared by the provider and that it may be invoked in the
+    /// given direction
+    pub fn check(&self, op: &str, direction: OperationDirection) -> Result<(), WasccCodecError> {
+        let declared = self
+            .descriptor
+            .supported_operations
+            .iter()
+            .find(|o| o.name == op)
+            .ok_or_else(|| WasccCodecError::UnknownOperation {
+                capability: self.descriptor.id.to_string(),
+                operation: op.to_string(),
+            })?;
+        if declared.direction.permits(&direction) {
+            Ok(())
+        } else {
+            Err(WasccCodecError::InvalidDirection {
+                capability: self.descriptor.id.to_string(),
+                operation: op.to_string(),
+                declared: declared.direction.clone(),
+                attempted: direction,
+            })
+        }
+    }
+}
+
+/// Used by hosts to fail fast on invocations that are misrouted, i.e. operations the provider doesn't
+/// declare or that are being sent in a direction the provider's descriptor does not permit
+pub fn validate_invocation(
+    descriptor: &CapabilityDescriptor,
+    op: &str,
+    direction: OperationDirection,
+) -> Result<(), WasccCodecError> {
+    DirectionGuard::new(descriptor).check(op, direction)
+}
+
+/// Asks a provider to drain ahead of being replaced, e.g. by a newer revision
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DrainStart {
+    /// The time by which the provider must have drained. Invocations still in flight at the deadline
+    /// are abandoned
+    pub deadline: Timestamp,
+    /// The revision of the provider that will replace this one, if any
+    #[serde(default)]
+    pub replacement_revision: Option<u32>,
+}
+
+impl DrainStart {
+    pub fn new(deadline: Timestamp) -> DrainStart {
+        DrainStart {
+            deadline,
+            replacement_revision: None,
+        }
+    }
+
+    pub fn with_replacement_revision(self, revision: u32) -> DrainStart {
+        DrainStart {
+            replacement_revision: Some(revision),
+            ..self
+        }
+    }
+
+    /// The time left to drain, which is zero once the deadline has passed
+    pub fn remaining(&self, now: Timestamp) -> DurationMs {
+        self.deadline.duration_since(now).unwrap_or_default()
+    }
+}
+
+/// Reports how far a provider has drained
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DrainProgress {
+    /// The number of invocations still in flight
+    pub pending: u64,
+    /// The number of invocations that have finished since draining started
+    pub completed: u64,
+}
+
+/// Signals that a provider has finished draining and may be unloaded
+#[repr(C)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DrainComplete {
+    /// The number of invocations that finished while draining
+    pub completed: u64,
+    /// The number of invocations abandoned because the deadline passed before they finished
+    pub abandoned: u64,
+}
+
+impl DrainComplete {
+    /// Indicates whether the provider drained without abandoning any invocations
+    pub fn is_clean(&self) -> bool {
+        self.abandoned == 0
+    }
+}
+
 /// The NullDispatcher is as its name implies--a dispatcher that does nothing. This is convenient for
 /// initializing a capability provider with a null dispatcher, and then swapping it for a real dispatcher
 /// when the host runtime provides one configured with the appropriate channels
@@ -185,7 +725,7 @@ impl Dispatcher for NullDispatcher {
 
 /// Every native capability provider must implement this trait. Both portable and native capability providers
 /// must respond to the following operations: `OP_BIND_ACTOR`, `OP_REMOVE_ACTOR`, `OP_GET_CAPABILITY_DESCRIPTOR`
-pub trait CapabilityProvider: Any + Send + Sync {
+pub trait CapabilityProvider: AsAny + Send + Sync {
     /// This function will be called on the provider when the host runtime is ready and has configured a dispatcher. This function is only ever
     /// called _once_ for a capability provider, regardless of the number of actors being managed in the host
     fn configure_dispatch(&self, dispatcher: Box<dyn Dispatcher>) -> Result<(), Box<dyn Error>>;
@@ -193,30 +733,418 @@ pub trait CapabilityProvider: Any + Send + Sync {
     fn handle_call(&self, actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>>;
 }
 
-/// Wraps a constructor inside an FFI function to allow the `CapabilityProvider` trait implementation
-/// to be instantiated and used by the host runtime
+/// Exposes a value as `Any` so that trait objects can be downcast to their concrete type. Implemented
+/// for every `'static` type, so providers never need to implement it themselves
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Exposes a capability provider trait object as `Any`, e.g. so hosts can downcast a provider to a
+/// concrete type they know about
+pub fn provider_as_any(provider: &dyn CapabilityProvider) -> &dyn Any {
+    provider.as_any()
+}
+
+/// Downcasts a capability provider trait object to its concrete type, if it is of that type
+pub fn downcast_provider<T: CapabilityProvider>(provider: &dyn CapabilityProvider) -> Option<&T> {
+    provider.as_any().downcast_ref::<T>()
+}
+
+/// The version of the `ProviderPlugin` layout. Incremented whenever the layout changes, so hosts can
+/// refuse plugins built against an incompatible codec rather than misreading them
+pub const PROVIDER_PLUGIN_ABI_VERSION: u32 = 2;
+
+/// The name of the symbol, exported by the `capability_provider!` macro, that creates a `ProviderPlugin`.
+/// This is the symbol hosts should resolve
+pub const PROVIDER_PLUGIN_SYMBOL: &[u8] = b"__capability_provider_plugin";
+
+const PLUGIN_CALL_OK: i32 = 0;
+const PLUGIN_CALL_FAILED: i32 = 1;
+
+/// Bytes borrowed across the plugin boundary for the duration of a call
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct PluginSlice {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl PluginSlice {
+    fn new(bytes: &[u8]) -> PluginSlice {
+        PluginSlice {
+            ptr: bytes.as_ptr(),
+            len: bytes.len(),
+        }
+    }
+
+    unsafe fn as_bytes<'a>(self) -> &'a [u8] {
+        if self.len == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.ptr, self.len)
+        }
+    }
+
+    unsafe fn as_str<'a>(self) -> Result<&'a str, Box<dyn Error>> {
+        Ok(std::str::from_utf8(self.as_bytes())?)
+    }
+}
+
+/// Bytes allocated on one side of the plugin boundary and handed to the other, which copies them and
+/// returns the buffer to its owner's `free_buffer` function, so it is released with the allocator that
+/// created it
+#[repr(C)]
+struct PluginBuffer {
+    ptr: *mut u8,
+    len: usize,
+    cap: usize,
+}
+
+impl PluginBuffer {
+    fn empty() -> PluginBuffer {
+        PluginBuffer {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+            cap: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> PluginBuffer {
+        let mut bytes = std::mem::ManuallyDrop::new(bytes);
+        PluginBuffer {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            cap: bytes.capacity(),
+        }
+    }
+
+    unsafe fn to_vec(&self) -> Vec<u8> {
+        PluginSlice {
+            ptr: self.ptr,
+            len: self.len,
+        }
+        .as_bytes()
+        .to_vec()
+    }
+}
+
+unsafe extern "C" fn free_buffer(buffer: PluginBuffer) {
+    if !buffer.ptr.is_null() {
+        drop(Vec::from_raw_parts(buffer.ptr, buffer.len, buffer.cap));
+    }
+}
+
+// Runs a call on the side of the boundary that implements it, writing the response, or the error
+// message, to `out`. Panics are caught, since they must not unwind across the boundary
+fn export_result(
+    out: *mut PluginBuffer,
+    call: impl FnOnce() -> Result<Vec<u8>, Box<dyn Error>>,
+) -> i32 {
+    let (status, bytes) = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(call)) {
+        Ok(Ok(bytes)) => (PLUGIN_CALL_OK, bytes),
+        Ok(Err(e)) => (PLUGIN_CALL_FAILED, e.to_string().into_bytes()),
+        Err(_) => (PLUGIN_CALL_FAILED, b"call panicked".to_vec()),
+    };
+    unsafe { out.write(PluginBuffer::from_vec(bytes)) };
+    status
+}
+
+// Reads the result of a call made across the boundary, returning the buffer to its owner
+unsafe fn import_result(
+    status: i32,
+    out: PluginBuffer,
+    free: unsafe extern "C" fn(PluginBuffer),
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = out.to_vec();
+    free(out);
+    if status == PLUGIN_CALL_OK {
+        Ok(bytes)
+    } else {
+        Err(String::from_utf8_lossy(&bytes).into_owned().into())
+    }
+}
+
+type PluginCallFn = unsafe extern "C" fn(
+    *const c_void,
+    PluginSlice,
+    PluginSlice,
+    PluginSlice,
+    *mut PluginBuffer,
+) -> i32;
+
+/// A host's dispatcher, handed to a plugin with `configure_dispatch`. Like `ProviderPlugin`, it only
+/// exposes `extern "C"` functions, so the plugin never calls through the host's trait object layout
+#[repr(C)]
+struct PluginDispatcher {
+    context: *mut c_void,
+    dispatch: PluginCallFn,
+    free_buffer: unsafe extern "C" fn(PluginBuffer),
+    destroy: unsafe extern "C" fn(*mut c_void),
+}
+
+impl PluginDispatcher {
+    fn new(dispatcher: Box<dyn Dispatcher>) -> PluginDispatcher {
+        PluginDispatcher {
+            context: Box::into_raw(Box::new(dispatcher)) as *mut c_void,
+            dispatch: host_dispatch,
+            free_buffer,
+            destroy: destroy_dispatcher,
+        }
+    }
+}
+
+unsafe extern "C" fn host_dispatch(
+    context: *const c_void,
+    actor: PluginSlice,
+    op: PluginSlice,
+    msg: PluginSlice,
+    out: *mut PluginBuffer,
+) -> i32 {
+    let dispatcher = &*(context as *const Box<dyn Dispatcher>);
+    export_result(out, || {
+        dispatcher.dispatch(actor.as_str()?, op.as_str()?, msg.as_bytes())
+    })
+}
+
+unsafe extern "C" fn destroy_dispatcher(context: *mut c_void) {
+    drop(Box::from_raw(context as *mut Box<dyn Dispatcher>));
+}
+
+// The host's dispatcher as seen by the provider inside a plugin
+struct ForeignDispatcher(PluginDispatcher);
+
+// The host's dispatcher is Send + Sync, and this is the only owner of its context
+unsafe impl Send for ForeignDispatcher {}
+unsafe impl Sync for ForeignDispatcher {}
+
+impl Dispatcher for ForeignDispatcher {
+    fn dispatch(&self, actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut out = PluginBuffer::empty();
+        unsafe {
+            let status = (self.0.dispatch)(
+                self.0.context,
+                PluginSlice::new(actor.as_bytes()),
+                PluginSlice::new(op.as_bytes()),
+                PluginSlice::new(msg),
+                &mut out,
+            );
+            import_result(status, out, self.0.free_buffer)
+        }
+    }
+}
+
+impl Drop for ForeignDispatcher {
+    fn drop(&mut self) {
+        unsafe { (self.0.destroy)(self.0.context) }
+    }
+}
+
+/// The versioned wrapper in which a provider crosses the plugin boundary. The ABI version is always the
+/// first field, so a host can check it before relying on the rest of the layout. The provider itself is
+/// opaque to the host: every call goes through the `extern "C"` functions in this struct, which were
+/// compiled into the plugin, so host and plugin need not agree on Rust's trait object layout. Buffers
+/// are always released with the allocator that created them
+#[repr(C)]
+pub struct ProviderPlugin {
+    abi_version: u32,
+    codec_version: *const u8,
+    codec_version_len: usize,
+    provider: *mut c_void,
+    configure_dispatch:
+        unsafe extern "C" fn(*const c_void, PluginDispatcher, *mut PluginBuffer) -> i32,
+    handle_call: PluginCallFn,
+    free_buffer: unsafe extern "C" fn(PluginBuffer),
+    destroy: unsafe extern "C" fn(*mut ProviderPlugin),
+}
+
+impl ProviderPlugin {
+    /// Wraps a provider for export from a plugin. Called by the `capability_provider!` macro
+    pub fn into_raw(provider: Box<dyn CapabilityProvider>) -> *mut ProviderPlugin {
+        Box::into_raw(Box::new(ProviderPlugin {
+            abi_version: PROVIDER_PLUGIN_ABI_VERSION,
+            codec_version: crate::VERSION.as_ptr(),
+            codec_version_len: crate::VERSION.len(),
+            provider: Box::into_raw(Box::new(provider)) as *mut c_void,
+            configure_dispatch: plugin_configure_dispatch,
+            handle_call: plugin_handle_call,
+            free_buffer,
+            destroy: destroy_plugin,
+        }))
+    }
+
+    /// Takes ownership of a plugin created by `__capability_provider_plugin`, after checking that it
+    /// is not null and was built with a compatible layout. A plugin with an incompatible layout is
+    /// leaked rather than destroyed, since its destroy function cannot be located safely
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be null or have been returned by a plugin's `__capability_provider_plugin`
+    /// function, must not have been passed to this function before, and the plugin library must remain
+    /// loaded for as long as the returned handle exists
+    pub unsafe fn from_raw(plugin: *mut ProviderPlugin) -> Result<PluginHandle, WasccCodecError> {
+        if plugin.is_null() {
+            return Err(WasccCodecError::NullProviderPlugin);
+        }
+        let found = std::ptr::read(plugin as *const u32);
+        if found == PROVIDER_PLUGIN_ABI_VERSION {
+            Ok(PluginHandle { plugin })
+        } else {
+            Err(WasccCodecError::IncompatibleProviderAbi {
+                expected: PROVIDER_PLUGIN_ABI_VERSION,
+                found,
+            })
+        }
+    }
+}
+
+unsafe fn plugin_provider<'a>(provider: *const c_void) -> &'a dyn CapabilityProvider {
+    &**(provider as *const Box<dyn CapabilityProvider>)
+}
+
+unsafe extern "C" fn plugin_configure_dispatch(
+    provider: *const c_void,
+    dispatcher: PluginDispatcher,
+    out: *mut PluginBuffer,
+) -> i32 {
+    let provider = plugin_provider(provider);
+    let dispatcher = ForeignDispatcher(dispatcher);
+    export_result(out, || {
+        provider.configure_dispatch(Box::new(dispatcher))?;
+        Ok(Vec::new())
+    })
+}
+
+unsafe extern "C" fn plugin_handle_call(
+    provider: *const c_void,
+    actor: PluginSlice,
+    op: PluginSlice,
+    msg: PluginSlice,
+    out: *mut PluginBuffer,
+) -> i32 {
+    let provider = plugin_provider(provider);
+    export_result(out, || {
+        provider.handle_call(actor.as_str()?, op.as_str()?, msg.as_bytes())
+    })
+}
+
+unsafe extern "C" fn destroy_plugin(plugin: *mut ProviderPlugin) {
+    let plugin = Box::from_raw(plugin);
+    drop(Box::from_raw(
+        plugin.provider as *mut Box<dyn CapabilityProvider>,
+    ));
+}
+
+/// A host's handle to a provider loaded from a plugin, through which the host calls the provider.
+/// Dropping the handle destroys the provider
+pub struct PluginHandle {
+    plugin: *mut ProviderPlugin,
+}
+
+// The provider itself is Send + Sync, and the handle has exclusive ownership of the plugin
+unsafe impl Send for PluginHandle {}
+unsafe impl Sync for PluginHandle {}
+
+impl PluginHandle {
+    /// The version of the codec the plugin was built with
+    pub fn codec_version(&self) -> &str {
+        unsafe {
+            let plugin = &*self.plugin;
+            let bytes = std::slice::from_raw_parts(plugin.codec_version, plugin.codec_version_len);
+            std::str::from_utf8(bytes).unwrap_or("")
+        }
+    }
+}
+
+impl CapabilityProvider for PluginHandle {
+    fn configure_dispatch(&self, dispatcher: Box<dyn Dispatcher>) -> Result<(), Box<dyn Error>> {
+        let mut out = PluginBuffer::empty();
+        unsafe {
+            let plugin = &*self.plugin;
+            let status = (plugin.configure_dispatch)(
+                plugin.provider,
+                PluginDispatcher::new(dispatcher),
+                &mut out,
+            );
+            import_result(status, out, plugin.free_buffer).map(|_| ())
+        }
+    }
+
+    fn handle_call(&self, actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut out = PluginBuffer::empty();
+        unsafe {
+            let plugin = &*self.plugin;
+            let status = (plugin.handle_call)(
+                plugin.provider,
+                PluginSlice::new(actor.as_bytes()),
+                PluginSlice::new(op.as_bytes()),
+                PluginSlice::new(msg),
+                &mut out,
+            );
+            import_result(status, out, plugin.free_buffer)
+        }
+    }
+}
+
+impl Drop for PluginHandle {
+    fn drop(&mut self) {
+        unsafe { ((*self.plugin).destroy)(self.plugin) }
+    }
+}
+
+/// Wraps a constructor inside FFI functions to allow the `CapabilityProvider` trait implementation
+/// to be instantiated and used by the host runtime. Hosts should resolve `__capability_provider_plugin`
+/// (`PROVIDER_PLUGIN_SYMBOL`), which returns the provider in a versioned `ProviderPlugin`, and load it
+/// with `ProviderPlugin::from_raw`.
+///
+/// `__capability_provider_create` is deprecated and retained only for hosts that have not moved to the
+/// plugin symbol yet. It returns a raw trait object pointer, which is only safe to use when the host and
+/// the plugin were built with the same compiler and codec version
 #[macro_export]
 macro_rules! capability_provider {
     ($provider_type:ty, $constructor:path) => {
         #[no_mangle]
+        #[allow(improper_ctypes_definitions)]
         pub extern "C" fn __capability_provider_create(
-        ) -> *mut $crate::capabilities::CapabilityProvider {
+        ) -> *mut dyn $crate::capabilities::CapabilityProvider {
             let constructor: fn() -> $provider_type = $constructor;
             let object = constructor();
-            let boxed: Box<$crate::capabilities::CapabilityProvider> = Box::new(object);
+            let boxed: Box<dyn $crate::capabilities::CapabilityProvider> = Box::new(object);
             Box::into_raw(boxed)
         }
+
+        #[no_mangle]
+        pub extern "C" fn __capability_provider_plugin(
+        ) -> *mut $crate::capabilities::ProviderPlugin {
+            let constructor: fn() -> $provider_type = $constructor;
+            $crate::capabilities::ProviderPlugin::into_raw(Box::new(constructor()))
+        }
     };
 }
 
 #[cfg(test)]
 mod test {
-    use super::{CapabilityDescriptor, OperationDescriptor, OperationDirection};
+    use super::{
+        downcast_provider, provider_as_any, validate_invocation, well_known, CacheControl,
+        CapabilityDescriptor, CapabilityId, CapabilityProvider, Dispatcher, OperationDescriptor,
+        OperationDirection, OperationLimits, ProviderPlugin, PROVIDER_PLUGIN_ABI_VERSION,
+    };
+    use crate::core::{DurationMs, InvocationResponse, Timestamp};
+    use crate::errors::WasccCodecError;
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::sync::RwLock;
+
     #[test]
     fn descriptor_certify_desired_json_format() {
         let d = CapabilityDescriptor {
             name: "test".to_string(),
-            id: "wascc:testing".to_string(),
+            id: "wascc:testing".into(),
             version: "0.0.1".to_string(),
             revision: 1,
             long_description: "this is a test".to_string(),
@@ -224,9 +1152,223 @@ mod test {
                 direction: OperationDirection::ToActor,
                 doctext: "this is a test".to_string(),
                 name: "OperationDumboDrop".to_string(),
+                limits: None,
+                cache: None,
             }],
+            extensions: HashMap::new(),
+            dependencies: vec![],
+            resources: None,
+            sharding: None,
         };
         let s = serde_json::to_string(&d).unwrap();
         assert_eq!(s, "{\"id\":\"wascc:testing\",\"name\":\"test\",\"version\":\"0.0.1\",\"revision\":1,\"long_description\":\"this is a test\",\"supported_operations\":[{\"name\":\"OperationDumboDrop\",\"direction\":\"to_actor\",\"doctext\":\"this is a test\"}]}".to_string());
     }
+
+    #[test]
+    fn operation_limits_round_trip_through_descriptor() {
+        let limits = OperationLimits {
+            rate_per_second: 10,
+            burst: 20,
+            max_payload_bytes: 4 * 1024 * 1024,
+        };
+        let d = CapabilityDescriptor::builder()
+            .id("wascc:blobstore")
+            .with_limited_operation(
+                "UploadChunk",
+                OperationDirection::ToProvider,
+                "",
+                limits.clone(),
+            )
+            .with_operation("ListObjects", OperationDirection::ToProvider, "")
+            .build();
+        let s = serde_json::to_string(&d).unwrap();
+        assert!(s.contains("\"rate_per_second\":10"));
+        let d2: CapabilityDescriptor = serde_json::from_str(&s).unwrap();
+        assert_eq!(d2.supported_operations[0].limits, Some(limits));
+        assert_eq!(d2.supported_operations[1].limits, None);
+    }
+
+    #[test]
+    fn cache_keys_follow_vary_by_fields() {
+        let cache = CacheControl::new(DurationMs::from_secs(30))
+            .vary_by("container")
+            .vary_by("id");
+        let op = OperationDescriptor::new("GetObjectInfo", OperationDirection::ToProvider, "")
+            .with_cache_control(cache.clone());
+        let s = serde_json::to_string(&op).unwrap();
+        let op2: OperationDescriptor = serde_json::from_str(&s).unwrap();
+        assert_eq!(op2.cache, Some(cache.clone()));
+
+        #[derive(Serialize)]
+        struct ObjectInfo {
+            container: &'static str,
+            id: &'static str,
+            byte_size: u64,
+        }
+        let blob = ObjectInfo {
+            container: "docs",
+            id: "a.txt",
+            byte_size: 0,
+        };
+        let resized = ObjectInfo {
+            byte_size: 42,
+            ..blob
+        };
+        let key = |actor: &str, cache: &CacheControl, b: &ObjectInfo| {
+            let payload = crate::serialize(b).unwrap();
+            cache
+                .cache_key(actor, "wascc:blobstore", "GetObjectInfo", &payload)
+                .unwrap()
+        };
+        assert_eq!(key("Mxxx", &cache, &blob), key("Mxxx", &cache, &resized));
+        assert_ne!(key("Mxxx", &cache, &blob), key("Myyy", &cache, &blob));
+        let shared = cache.clone().shared();
+        assert_eq!(key("Mxxx", &shared, &blob), key("Myyy", &shared, &blob));
+
+        let resp = InvocationResponse::success(vec![1])
+            .with_freshness(cache.freshness(Timestamp::from_secs(100)));
+        assert!(resp.is_cacheable_at(Timestamp::from_secs(129)));
+        assert!(!resp.is_cacheable_at(Timestamp::from_secs(130)));
+        assert!(!InvocationResponse::error("boom").is_cacheable_at(Timestamp::from_secs(100)));
+    }
+
+    #[test]
+    fn validate_invocation_enforces_direction() {
+        let d = CapabilityDescriptor::builder()
+            .id("wascc:testing")
+            .with_operation("Deliver", OperationDirection::ToActor, "")
+            .with_operation("Echo", OperationDirection::Both, "")
+            .build();
+        assert!(validate_invocation(&d, "Deliver", OperationDirection::ToActor).is_ok());
+        assert!(validate_invocation(&d, "Echo", OperationDirection::ToProvider).is_ok());
+        assert_eq!(
+            validate_invocation(&d, "Deliver", OperationDirection::ToProvider),
+            Err(WasccCodecError::InvalidDirection {
+                capability: "wascc:testing".to_string(),
+                operation: "Deliver".to_string(),
+                declared: OperationDirection::ToActor,
+                attempted: OperationDirection::ToProvider,
+            })
+        );
+        assert!(matches!(
+            validate_invocation(&d, "Missing", OperationDirection::ToActor),
+            Err(WasccCodecError::UnknownOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn capability_id_parsing() {
+        let id = CapabilityId::parse("wascc:http_server").unwrap();
+        assert_eq!(id, well_known::HTTP_SERVER);
+        assert_eq!(id.namespace(), "wascc");
+        assert_eq!(id.name(), "http_server");
+        assert!(CapabilityId::parse("messaging").is_err());
+        assert!(CapabilityId::parse("wascc:").is_err());
+        assert!(CapabilityId::parse("wascc:key value").is_err());
+        assert!(!CapabilityId::from("bad id").is_valid());
+    }
+
+    #[derive(Default)]
+    struct EchoProvider {
+        dispatcher: RwLock<Option<Box<dyn Dispatcher>>>,
+    }
+
+    impl EchoProvider {
+        fn new() -> EchoProvider {
+            EchoProvider::default()
+        }
+    }
+
+    impl CapabilityProvider for EchoProvider {
+        fn configure_dispatch(
+            &self,
+            dispatcher: Box<dyn Dispatcher>,
+        ) -> Result<(), Box<dyn Error>> {
+            *self.dispatcher.write().unwrap() = Some(dispatcher);
+            Ok(())
+        }
+
+        fn handle_call(
+            &self,
+            actor: &str,
+            op: &str,
+            msg: &[u8],
+        ) -> Result<Vec<u8>, Box<dyn Error>> {
+            match op {
+                "Echo" => Ok(msg.to_vec()),
+                "Relay" => match self.dispatcher.read().unwrap().as_ref() {
+                    Some(d) => d.dispatch(actor, "HandleMessage", msg),
+                    None => Err("no dispatcher".into()),
+                },
+                _ => Err(format!("unknown operation {}", op).into()),
+            }
+        }
+    }
+
+    struct ShoutingDispatcher;
+
+    impl Dispatcher for ShoutingDispatcher {
+        fn dispatch(&self, actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+            if actor == "missing" {
+                return Err(format!("no actor to receive {}", op).into());
+            }
+            Ok(msg.to_ascii_uppercase())
+        }
+    }
+
+    crate::capability_provider!(EchoProvider, EchoProvider::new);
+
+    #[test]
+    fn plugins_cross_the_boundary_with_a_versioned_layout() {
+        let handle = unsafe { ProviderPlugin::from_raw(__capability_provider_plugin()) }.unwrap();
+        assert_eq!(handle.codec_version(), crate::VERSION);
+        let provider: &dyn CapabilityProvider = &handle;
+        assert_eq!(
+            provider.handle_call("actor", "Echo", b"hi").unwrap(),
+            b"hi".to_vec()
+        );
+        assert_eq!(
+            provider
+                .handle_call("actor", "Nope", b"")
+                .unwrap_err()
+                .to_string(),
+            "unknown operation Nope"
+        );
+        assert!(provider.handle_call("actor", "Relay", b"hi").is_err());
+
+        provider
+            .configure_dispatch(Box::new(ShoutingDispatcher))
+            .unwrap();
+        assert_eq!(
+            provider.handle_call("actor", "Relay", b"hi").unwrap(),
+            b"HI".to_vec()
+        );
+        assert_eq!(
+            provider
+                .handle_call("missing", "Relay", b"hi")
+                .unwrap_err()
+                .to_string(),
+            "no actor to receive HandleMessage"
+        );
+
+        let local: Box<dyn CapabilityProvider> = Box::new(EchoProvider::new());
+        assert!(downcast_provider::<EchoProvider>(local.as_ref()).is_some());
+        assert!(provider_as_any(local.as_ref()).is::<EchoProvider>());
+
+        let mut stale = [0u32; 32]; // ABI version 0
+        assert_eq!(
+            unsafe { ProviderPlugin::from_raw(stale.as_mut_ptr() as *mut ProviderPlugin) }.err(),
+            Some(WasccCodecError::IncompatibleProviderAbi {
+                expected: PROVIDER_PLUGIN_ABI_VERSION,
+                found: 0
+            })
+        );
+        assert_eq!(
+            unsafe { ProviderPlugin::from_raw(std::ptr::null_mut()) }.err(),
+            Some(WasccCodecError::NullProviderPlugin)
+        );
+
+        let raw = __capability_provider_create();
+        drop(unsafe { Box::from_raw(raw) });
+    }
 }
@@ -0,0 +1,394 @@
+//! # Schema Compatibility
+//!
+//! This module captures the wire schema of payload types as fingerprints and compares fingerprints
+//! from different releases to find changes that would break communication between actors and hosts
+//! built against those releases. A fingerprint is derived from the type's `Sample`: every field the
+//! sample serializes is recorded with its message pack kind and whether the type can be deserialized
+//! without it. Fingerprints serialize to JSON, so the fingerprints of a release can be checked in and
+//! compared against the current types in a test:
+//!
+//! ```
+//! use wascc_codec::compat::{check_compatibility, SchemaFingerprint};
+//! use wascc_codec::messaging::RequestMessage;
+//!
+//! let previous = SchemaFingerprint::of::<RequestMessage>().unwrap(); // normally loaded from a file
+//! let current = SchemaFingerprint::of::<RequestMessage>().unwrap();
+//! let report = check_compatibility(&previous, &current);
+//! assert!(report.is_compatible(), "{:?}", report.breaking_changes());
+//! ```
+//!
+//! Because fingerprints are derived from samples, fields the sample leaves empty (a `None` or an
+//! empty collection) are recorded with the `nil` kind or, if they are skipped during serialization,
+//! not at all. Samples should populate every field whose compatibility matters
+
+use crate::fnv;
+use crate::value::Value;
+use crate::Sample;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// The message pack kind of a serialized field
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    /// The sample held no value, so the field's kind is unknown
+    Nil,
+    Bool,
+    Integer,
+    Float,
+    String,
+    Bytes,
+    Array,
+    Map,
+}
+
+/// The schema of a single field
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSchema {
+    pub kind: FieldKind,
+    /// Indicates whether payloads must contain the field to be deserialized
+    pub required: bool,
+}
+
+/// The wire schema of a payload type. Fields are keyed by their path, in which nested fields are
+/// separated by `.` and `[]` denotes the elements of an array
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaFingerprint {
+    pub type_name: String,
+    /// The codec version that produced the fingerprint
+    pub version: String,
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldSchema>,
+}
+
+impl SchemaFingerprint {
+    /// Captures the schema of a payload type from its sample
+    pub fn of<T>() -> Result<SchemaFingerprint, Box<dyn Error>>
+    where
+        T: Sample + Serialize + DeserializeOwned,
+    {
+        let value: Value = crate::deserialize(&crate::serialize(T::sample())?)?;
+        let mut kinds = Vec::new();
+        collect(&value, "", &mut kinds);
+
+        let mut fields = BTreeMap::new();
+        for (path, kind) in kinds {
+            let mut without = value.clone();
+            let required = if without.remove(&segments(&path)) {
+                crate::deserialize::<T>(&crate::serialize(&without)?).is_err()
+            } else {
+                false
+            };
+            fields.insert(path, FieldSchema { kind, required });
+        }
+        Ok(SchemaFingerprint {
+            type_name: std::any::type_name::<T>().to_string(),
+            version: crate::VERSION.to_string(),
+            fields,
+        })
+    }
+
+    /// A short digest of the field schemas, which changes whenever any field is added, removed or
+    /// changes kind or requiredness
+    pub fn digest(&self) -> String {
+        let fields: Vec<(&String, String)> = self
+            .fields
+            .iter()
+            .map(|(path, field)| (path, format!("{:?}:{}", field.kind, field.required)))
+            .collect();
+        let parts = fields
+            .iter()
+            .flat_map(|(path, schema)| vec![path.as_bytes(), schema.as_bytes()]);
+        fnv::fingerprint(parts, 0xff)
+    }
+}
+
+/// Whether a schema change preserves wire compatibility
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    /// Payloads remain readable in both directions
+    Compatible,
+    /// Payloads written by one side can no longer be read by the other
+    Breaking,
+}
+
+/// A difference between two fingerprints of the same type
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaChange {
+    /// A field was added. Breaking if required, as payloads from the old release lack it
+    FieldAdded { path: String, required: bool },
+    /// A field was removed. Breaking if the old release required it, as payloads from the new release
+    /// lack it
+    FieldRemoved { path: String, required: bool },
+    /// A field's kind changed. Always breaking
+    KindChanged {
+        path: String,
+        old: FieldKind,
+        new: FieldKind,
+    },
+    /// A field that was optional became required. Breaking
+    BecameRequired { path: String },
+    /// A field that was required became optional. Compatible
+    BecameOptional { path: String },
+}
+
+impl SchemaChange {
+    pub fn severity(&self) -> Severity {
+        match self {
+            SchemaChange::FieldAdded { required, .. }
+            | SchemaChange::FieldRemoved { required, .. } => {
+                if *required {
+                    Severity::Breaking
+                } else {
+                    Severity::Compatible
+                }
+            }
+            SchemaChange::KindChanged { .. } | SchemaChange::BecameRequired { .. } => {
+                Severity::Breaking
+            }
+            SchemaChange::BecameOptional { .. } => Severity::Compatible,
+        }
+    }
+
+    /// The path of the field that changed
+    pub fn path(&self) -> &str {
+        match self {
+            SchemaChange::FieldAdded { path, .. }
+            | SchemaChange::FieldRemoved { path, .. }
+            | SchemaChange::KindChanged { path, .. }
+            | SchemaChange::BecameRequired { path }
+            | SchemaChange::BecameOptional { path } => path,
+        }
+    }
+}
+
+/// The result of comparing two fingerprints
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilityReport {
+    pub type_name: String,
+    #[serde(default)]
+    pub changes: Vec<SchemaChange>,
+}
+
+impl CompatibilityReport {
+    /// Indicates whether none of the changes are breaking
+    pub fn is_compatible(&self) -> bool {
+        self.breaking_changes().is_empty()
+    }
+
+    pub fn breaking_changes(&self) -> Vec<&SchemaChange> {
+        self.changes
+            .iter()
+            .filter(|c| c.severity() == Severity::Breaking)
+            .collect()
+    }
+}
+
+/// Compares the fingerprint of a type from a previous release with its current fingerprint. A change in
+/// kind to or from `nil` is not reported, since one of the samples held no value for the field
+pub fn check_compatibility(
+    old: &SchemaFingerprint,
+    new: &SchemaFingerprint,
+) -> CompatibilityReport {
+    let mut changes = Vec::new();
+    for (path, before) in &old.fields {
+        let path = path.to_string();
+        match new.fields.get(&path) {
+            None => changes.push(SchemaChange::FieldRemoved {
+                path,
+                required: before.required,
+            }),
+            Some(after) => {
+                if before.kind != after.kind
+                    && before.kind != FieldKind::Nil
+                    && after.kind != FieldKind::Nil
+                {
+                    changes.push(SchemaChange::KindChanged {
+                        path: path.to_string(),
+                        old: before.kind,
+                        new: after.kind,
+                    });
+                }
+                if !before.required && after.required {
+                    changes.push(SchemaChange::BecameRequired { path });
+                } else if before.required && !after.required {
+                    changes.push(SchemaChange::BecameOptional { path });
+                }
+            }
+        }
+    }
+    for (path, after) in &new.fields {
+        if !old.fields.contains_key(path) {
+            changes.push(SchemaChange::FieldAdded {
+                path: path.to_string(),
+                required: after.required,
+            });
+        }
+    }
+    CompatibilityReport {
+        type_name: new.type_name.to_string(),
+        changes,
+    }
+}
+
+// Records the kind of every map entry within the value, depth first
+fn collect(value: &Value, path: &str, fields: &mut Vec<(String, FieldKind)>) {
+    match value {
+        Value::Map(entries) => {
+            for (k, v) in entries {
+                let key = match k {
+                    Value::Str(s) => s.to_string(),
+                    Value::Int(i) => i.to_string(),
+                    Value::UInt(u) => u.to_string(),
+                    other => format!("{:?}", other),
+                };
+                let field = if path.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                fields.push((field.to_string(), v.kind()));
+                collect(v, &field, fields);
+            }
+        }
+        Value::Seq(items) => {
+            if let Some(first) = items.first() {
+                collect(first, &format!("{}[]", path), fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Splits a field path into map keys, each followed by the number of array levels beneath it
+fn segments(path: &str) -> Vec<(&str, usize)> {
+    path.split('.')
+        .map(|s| {
+            let key = s.trim_end_matches("[]");
+            (key, (s.len() - key.len()) / 2)
+        })
+        .collect()
+}
+
+// Schema inspection of sample payloads
+impl Value {
+    fn kind(&self) -> FieldKind {
+        match self {
+            Value::Nil => FieldKind::Nil,
+            Value::Bool(_) => FieldKind::Bool,
+            Value::Int(_) | Value::UInt(_) => FieldKind::Integer,
+            Value::Float(_) => FieldKind::Float,
+            Value::Str(_) => FieldKind::String,
+            Value::Bytes(_) => FieldKind::Bytes,
+            Value::Seq(_) => FieldKind::Array,
+            Value::Map(_) => FieldKind::Map,
+        }
+    }
+
+    /// Removes the entry at the given path (following the first element of arrays), returning whether
+    /// an entry was removed
+    fn remove(&mut self, path: &[(&str, usize)]) -> bool {
+        let ((key, depth), rest) = match path.split_first() {
+            Some(first) => first,
+            None => return false,
+        };
+        let entries = match self {
+            Value::Map(entries) => entries,
+            _ => return false,
+        };
+        if rest.is_empty() {
+            let before = entries.len();
+            entries.retain(|(k, _)| k.as_str() != Some(key));
+            return entries.len() != before;
+        }
+        let mut target = match entries.iter_mut().find(|(k, _)| k.as_str() == Some(*key)) {
+            Some((_, v)) => v,
+            None => return false,
+        };
+        for _ in 0..*depth {
+            target = match target {
+                Value::Seq(items) if !items.is_empty() => &mut items[0],
+                _ => return false,
+            };
+        }
+        target.remove(rest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_compatibility, SchemaChange, SchemaFingerprint, Severity};
+    use crate::Sample;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct OrderV1 {
+        order_id: String,
+        quantity: u32,
+        #[serde(default)]
+        note: Option<String>,
+    }
+
+    impl Sample for OrderV1 {
+        fn sample() -> Self {
+            OrderV1 {
+                order_id: "o-1".to_string(),
+                quantity: 2,
+                note: Some("gift".to_string()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct OrderV2 {
+        order_id: String,
+        quantity: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        warehouse: String,
+    }
+
+    impl Sample for OrderV2 {
+        fn sample() -> Self {
+            OrderV2 {
+                order_id: "o-1".to_string(),
+                quantity: "2".to_string(),
+                tags: vec!["priority".to_string()],
+                warehouse: "eu-1".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn classifies_schema_changes() {
+        let v1 = SchemaFingerprint::of::<OrderV1>().unwrap();
+        let v2 = SchemaFingerprint::of::<OrderV2>().unwrap();
+        assert!(v1.fields["orderId"].required);
+        assert!(!v1.fields["note"].required);
+        assert_ne!(v1.digest(), v2.digest());
+        assert!(check_compatibility(&v1, &v1).changes.is_empty());
+
+        let report = check_compatibility(&v1, &v2);
+        let severity = |path: &str| {
+            report
+                .changes
+                .iter()
+                .find(|c| c.path() == path)
+                .map(SchemaChange::severity)
+        };
+        assert_eq!(severity("quantity"), Some(Severity::Breaking));
+        assert_eq!(severity("note"), Some(Severity::Compatible));
+        assert_eq!(severity("tags"), Some(Severity::Compatible));
+        assert_eq!(severity("warehouse"), Some(Severity::Breaking));
+        assert_eq!(severity("orderId"), None);
+        assert!(!report.is_compatible());
+        assert_eq!(report.breaking_changes().len(), 2);
+    }
+}
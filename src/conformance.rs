@@ -0,0 +1,718 @@
+//! # Conformance Samples
+//!
+//! This module exposes the codec's sample payloads so that SDKs in other languages can verify that they
+//! produce and consume the same wire format. Samples are exported as JSON lines, one record per payload
+//! type, each containing the base64-encoded message pack bytes of the type's `Sample` value. Validation
+//! decodes those bytes back into the Rust types and reports any differences in a machine-readable form
+//!
+//! Third-party capability crates can give their own payload types the same coverage with the
+//! `conformance!` macro, which generates tests that round-trip each sample and compare it against a
+//! golden file checked into the crate:
+//!
+//! ```
+//! # #[macro_use] extern crate serde_derive;
+//! # use wascc_codec::Sample;
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! pub struct FrobRequest {
+//!     pub target: String,
+//! }
+//!
+//! impl Sample for FrobRequest {
+//!     fn sample() -> Self {
+//!         FrobRequest { target: "widget".to_string() }
+//!     }
+//! }
+//!
+//! wascc_codec::conformance! {
+//!     golden_dir = "tests/golden";
+//!     "frobnicator", "frobrequest" => FrobRequest,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! A missing golden file is written the first time the tests run. When a sample changes on purpose,
+//! run the tests with `WASCC_CONFORMANCE_BLESS` set to rewrite the golden files. Golden files are
+//! never rewritten over a breaking schema change; delete the file to accept one
+
+use crate::compat::{check_compatibility, SchemaFingerprint};
+use crate::Sample;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// When this environment variable is set, `check_golden` rewrites golden files that no longer match
+/// their samples
+pub const BLESS_ENV: &str = "WASCC_CONFORMANCE_BLESS";
+
+type EncodeFn = fn() -> Result<Vec<u8>, Box<dyn Error>>;
+type DecodeFn = fn(&[u8]) -> Result<(), Box<dyn Error>>;
+type CompareFn = fn(&[u8]) -> Result<Vec<FieldDiff>, Box<dyn Error>>;
+type JsonFn = fn() -> Result<Value, Box<dyn Error>>;
+type FingerprintFn = fn() -> Result<SchemaFingerprint, Box<dyn Error>>;
+
+/// A payload type with a sample value that is part of the conformance suite
+pub struct SampleType {
+    /// The module (e.g. `httpserver`) under which the sample is grouped
+    pub module: &'static str,
+    /// The name of the sample within its module (e.g. `request`)
+    pub name: &'static str,
+    encode: EncodeFn,
+    decode: DecodeFn,
+    compare: CompareFn,
+    json: JsonFn,
+    fingerprint: FingerprintFn,
+}
+
+impl SampleType {
+    /// Registers a payload type's sample under the given module and name
+    pub fn new<T>(module: &'static str, name: &'static str) -> SampleType
+    where
+        T: Sample + Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        SampleType {
+            module,
+            name,
+            encode: || crate::serialize(T::sample()),
+            decode: |bytes| crate::deserialize::<T>(bytes).map(|_| ()),
+            compare: |bytes| {
+                let actual: T = crate::deserialize(bytes)?;
+                let expected = T::sample();
+                if actual == expected {
+                    Ok(vec![])
+                } else {
+                    let mut diffs = Vec::new();
+                    diff_values(
+                        "",
+                        &serde_json::to_value(&expected)?,
+                        &serde_json::to_value(&actual)?,
+                        &mut diffs,
+                    );
+                    Ok(diffs)
+                }
+            },
+            json: || Ok(serde_json::to_value(T::sample())?),
+            fingerprint: SchemaFingerprint::of::<T>,
+        }
+    }
+
+    /// Produces the serialized bytes of this type's sample value
+    pub fn encode(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        (self.encode)()
+    }
+
+    /// Decodes the given bytes into this sample's type, discarding the result
+    pub fn decode(&self, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+        (self.decode)(bytes)
+    }
+
+    /// Decodes the given bytes and compares the result against this type's sample value
+    pub fn validate(&self, bytes: &[u8]) -> SampleResult {
+        let (status, diffs, message) = match (self.compare)(bytes) {
+            Ok(diffs) if diffs.is_empty() => (SampleStatus::Passed, diffs, None),
+            Ok(diffs) => (SampleStatus::Mismatch, diffs, None),
+            Err(e) => (SampleStatus::DecodeFailed, vec![], Some(e.to_string())),
+        };
+        SampleResult {
+            module: self.module.to_string(),
+            name: self.name.to_string(),
+            status,
+            diffs,
+            message,
+        }
+    }
+
+    /// Encodes this type's sample value and validates the result against the sample
+    pub fn round_trip(&self) -> SampleResult {
+        match self.encode() {
+            Ok(bytes) => self.validate(&bytes),
+            Err(e) => SampleResult {
+                module: self.module.to_string(),
+                name: self.name.to_string(),
+                status: SampleStatus::DecodeFailed,
+                diffs: vec![],
+                message: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Produces the golden record of this type's sample value
+    pub fn golden(&self) -> Result<GoldenSample, Box<dyn Error>> {
+        Ok(GoldenSample {
+            version: crate::VERSION.to_string(),
+            module: self.module.to_string(),
+            name: self.name.to_string(),
+            json: (self.json)()?,
+            payload: base64::encode(self.encode()?),
+            schema: (self.fingerprint)()?,
+        })
+    }
+}
+
+/// Returns all of the sample types built into the codec
+pub fn samples() -> Vec<SampleType> {
+    vec![
+        SampleType::new::<crate::http::Request>("httpserver", "request"),
+        SampleType::new::<crate::http::Response>("httpserver", "response"),
+        SampleType::new::<crate::keyvalue::GetRequest>("keyvalue", "getrequest"),
+        SampleType::new::<crate::keyvalue::GetResponse>("keyvalue", "getresponse"),
+        SampleType::new::<crate::keyvalue::SetRequest>("keyvalue", "setrequest"),
+        SampleType::new::<crate::keyvalue::SetResponse>("keyvalue", "setresponse"),
+        SampleType::new::<crate::keyvalue::DelRequest>("keyvalue", "delrequest"),
+        SampleType::new::<crate::keyvalue::DelResponse>("keyvalue", "delresponse"),
+        SampleType::new::<crate::keyvalue::AddRequest>("keyvalue", "addrequest"),
+        SampleType::new::<crate::keyvalue::AddResponse>("keyvalue", "addresponse"),
+        SampleType::new::<crate::keyvalue::ListPushRequest>("keyvalue", "listpushrequest"),
+        SampleType::new::<crate::keyvalue::ListDelItemRequest>("keyvalue", "listdelitemrequest"),
+        SampleType::new::<crate::keyvalue::ListResponse>("keyvalue", "listresponse"),
+        SampleType::new::<crate::keyvalue::ListClearRequest>("keyvalue", "listclearrequest"),
+        SampleType::new::<crate::keyvalue::ListRangeRequest>("keyvalue", "listrangerequest"),
+        SampleType::new::<crate::keyvalue::ListRangeResponse>("keyvalue", "listrangeresponse"),
+        SampleType::new::<crate::keyvalue::SetAddRequest>("keyvalue", "setaddrequest"),
+        SampleType::new::<crate::keyvalue::SetRemoveRequest>("keyvalue", "setremoverequest"),
+        SampleType::new::<crate::keyvalue::SetOperationResponse>(
+            "keyvalue",
+            "setoperationresponse",
+        ),
+        SampleType::new::<crate::keyvalue::SetQueryRequest>("keyvalue", "setqueryrequest"),
+        SampleType::new::<crate::keyvalue::SetQueryResponse>("keyvalue", "setqueryresponse"),
+        SampleType::new::<crate::keyvalue::SetIntersectionRequest>(
+            "keyvalue",
+            "setintersectionrequest",
+        ),
+        SampleType::new::<crate::keyvalue::SetUnionRequest>("keyvalue", "setunionrequest"),
+        SampleType::new::<crate::keyvalue::KeyExistsQuery>("keyvalue", "keyexistsquery"),
+        SampleType::new::<crate::keyvalue::SetWithExpirationRequest>(
+            "keyvalue",
+            "setwithexpirationrequest",
+        ),
+        SampleType::new::<crate::keyvalue::AppendRequest>("keyvalue", "appendrequest"),
+        SampleType::new::<crate::keyvalue::ExportRequest>("keyvalue", "exportrequest"),
+        SampleType::new::<crate::keyvalue::PfAddRequest>("keyvalue", "pfaddrequest"),
+        SampleType::new::<crate::keyvalue::SubscribeKeyspace>("keyvalue", "subscribekeyspace"),
+        SampleType::new::<crate::blobstore::FileChunk>("blobstore", "filechunk"),
+        SampleType::new::<crate::blobstore::ContainerList>("blobstore", "containerlist"),
+        SampleType::new::<crate::blobstore::DeleteObjectsRequest>(
+            "blobstore",
+            "deleteobjectsrequest",
+        ),
+        SampleType::new::<crate::messaging::RequestMessage>("messaging", "requestmessage"),
+        SampleType::new::<crate::messaging::SubscribeRequest>("messaging", "subscriberequest"),
+        SampleType::new::<crate::messaging::DeclareTopologyRequest>(
+            "messaging",
+            "declaretopologyrequest",
+        ),
+        SampleType::new::<crate::extras::GeneratorResult>("extras", "result_guid"),
+        SampleType::new::<crate::extras::VrfRequest>("extras", "vrfrequest"),
+        SampleType::new::<crate::logging::WriteLogRequest>("logging", "writelogrequest"),
+        SampleType::new::<crate::logging::LogQueryRequest>("logging", "logqueryrequest"),
+        SampleType::new::<crate::eventstreams::StreamQuery>("eventstreams", "streamquery"),
+        SampleType::new::<crate::eventstreams::MultiStreamSubscribe>(
+            "eventstreams",
+            "multistreamsubscribe",
+        ),
+        SampleType::new::<crate::eventstreams::CompactStreamRequest>(
+            "eventstreams",
+            "compactstreamrequest",
+        ),
+        SampleType::new::<crate::metering::UsageRecord>("metering", "usagerecord"),
+        SampleType::new::<crate::metering::AggregateUsageRequest>(
+            "metering",
+            "aggregateusagerequest",
+        ),
+        SampleType::new::<crate::device::I2cTransfer>("device", "i2ctransfer"),
+        SampleType::new::<crate::calendar::CalendarEvent>("calendar", "calendarevent"),
+        SampleType::new::<crate::mime::ParsedMessage>("mime", "parsedmessage"),
+        SampleType::new::<crate::docextract::ExtractTextRequest>(
+            "docextract",
+            "extracttextrequest",
+        ),
+        SampleType::new::<crate::graphql::GraphQLRequest>("graphql", "graphqlrequest"),
+        SampleType::new::<crate::discovery::RegisterService>("discovery", "registerservice"),
+        SampleType::new::<crate::outbox::EnqueueOutbox>("outbox", "enqueueoutbox"),
+        SampleType::new::<crate::access::PolicyDocument>("access", "policydocument"),
+        SampleType::new::<crate::webhooks::WebhookRegistration>("webhooks", "webhookregistration"),
+        SampleType::new::<crate::textgen::CompletionRequest>("textgen", "completionrequest"),
+        SampleType::new::<crate::coordination::CampaignRequest>("coordination", "campaignrequest"),
+        SampleType::new::<crate::state::SaveSnapshot>("state", "savesnapshot"),
+        SampleType::new::<crate::net::OpenConnection>("net", "openconnection"),
+        SampleType::new::<crate::session::CreateSession>("session", "createsession"),
+        SampleType::new::<crate::plugin::HostPluginDescriptor>("plugin", "hostplugindescriptor"),
+        SampleType::new::<crate::attestation::RemoteAttestationRequest>(
+            "attestation",
+            "remoteattestationrequest",
+        ),
+        SampleType::new::<crate::probes::ProbeSpec>("probes", "probespec"),
+        SampleType::new::<crate::envelope::KeyAgreementRequest>("envelope", "keyagreementrequest"),
+        SampleType::new::<crate::docgen::GenerateDocumentRequest>(
+            "docgen",
+            "generatedocumentrequest",
+        ),
+        SampleType::new::<crate::core::InitializePayload>("core", "initializepayload"),
+        SampleType::new::<crate::vectorstore::CollectionSpec>("vectorstore", "collectionspec"),
+        SampleType::new::<crate::vectorstore::UpsertVectors>("vectorstore", "upsertvectors"),
+        SampleType::new::<crate::vectorstore::SimilarityQuery>("vectorstore", "similarityquery"),
+        SampleType::new::<crate::leaderboard::SubmitScore>("leaderboard", "submitscore"),
+        SampleType::new::<crate::leaderboard::TopNQuery>("leaderboard", "topnquery"),
+        SampleType::new::<crate::barcode::GenerateCodeRequest>("barcode", "generatecoderequest"),
+        SampleType::new::<crate::datafeed::FeedQuery>("datafeed", "feedquery"),
+        SampleType::new::<crate::datafeed::FeedSubscription>("datafeed", "feedsubscription"),
+        SampleType::new::<crate::telephony::InitiateCall>("telephony", "initiatecall"),
+        SampleType::new::<crate::crdt::GCounterUpdate>("crdt", "gcounterupdate"),
+        SampleType::new::<crate::crdt::PnCounterUpdate>("crdt", "pncounterupdate"),
+        SampleType::new::<crate::crdt::OrSetOp>("crdt", "orsetop"),
+        SampleType::new::<crate::batchjobs::SubmitJob>("batchjobs", "submitjob"),
+        SampleType::new::<crate::analytics::IngestDataPoint>("analytics", "ingestdatapoint"),
+        SampleType::new::<crate::analytics::AnomalyQuery>("analytics", "anomalyquery"),
+        SampleType::new::<crate::analytics::ForecastRequest>("analytics", "forecastrequest"),
+        SampleType::new::<crate::sqldb::QueryRequest>("sqldb", "queryrequest"),
+        SampleType::new::<crate::exec::OpenSession>("exec", "opensession"),
+    ]
+}
+
+/// A single line of a JSON lines conformance export
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct SampleRecord {
+    /// The codec version that produced the record
+    pub version: String,
+    pub module: String,
+    pub name: String,
+    /// The base64-encoded message pack bytes of the sample value
+    pub payload: String,
+}
+
+/// The checked-in reference for a sample, stored as JSON by `check_golden`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct GoldenSample {
+    /// The codec version that wrote the golden file
+    pub version: String,
+    pub module: String,
+    pub name: String,
+    /// The sample value, for readable comparisons
+    pub json: Value,
+    /// The base64-encoded message pack bytes of the sample value
+    pub payload: String,
+    pub schema: SchemaFingerprint,
+}
+
+/// The outcome of validating a single sample
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleStatus {
+    /// The payload decoded to a value equal to the sample
+    Passed,
+    /// The payload decoded, but to a value that differs from the sample
+    Mismatch,
+    /// The payload could not be decoded into the sample's type
+    DecodeFailed,
+    /// The input contained a sample that is not part of the suite
+    Unknown,
+    /// A sample in the suite (for the selected modules) was absent from the input
+    Missing,
+}
+
+/// A single field that differs between the expected sample and the decoded payload
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct FieldDiff {
+    /// The path of the differing field, e.g. `header.accept` or `body[3]`
+    pub path: String,
+    pub expected: Option<Value>,
+    pub actual: Option<Value>,
+}
+
+/// The result of validating a single sample
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct SampleResult {
+    pub module: String,
+    pub name: String,
+    pub status: SampleStatus,
+    #[serde(default)]
+    pub diffs: Vec<FieldDiff>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// The results of validating a conformance export
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+pub struct ValidationReport {
+    pub results: Vec<SampleResult>,
+}
+
+impl ValidationReport {
+    /// Indicates whether every sample in the report passed
+    pub fn passed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|r| r.status == SampleStatus::Passed)
+    }
+}
+
+/// The serialized size of a single sample
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct SampleSize {
+    pub module: String,
+    pub name: String,
+    pub bytes: usize,
+}
+
+/// A sample whose serialized size grew beyond the allowed tolerance
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct SizeRegression {
+    pub module: String,
+    pub name: String,
+    pub baseline_bytes: usize,
+    pub bytes: usize,
+}
+
+/// The serialized sizes of all of the codec's samples. Store a report as a baseline (it serializes as
+/// JSON) and compare later reports against it in CI to catch accidental payload growth
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+pub struct SizeReport {
+    pub version: String,
+    pub sizes: Vec<SampleSize>,
+}
+
+impl SizeReport {
+    /// Returns the samples whose size grew by more than `tolerance_bytes` relative to the baseline.
+    /// Samples absent from the baseline are not reported
+    pub fn regressions(
+        &self,
+        baseline: &SizeReport,
+        tolerance_bytes: usize,
+    ) -> Vec<SizeRegression> {
+        self.sizes
+            .iter()
+            .filter_map(|s| {
+                baseline
+                    .sizes
+                    .iter()
+                    .find(|b| b.module == s.module && b.name == s.name)
+                    .filter(|b| s.bytes > b.bytes + tolerance_bytes)
+                    .map(|b| SizeRegression {
+                        module: s.module.to_string(),
+                        name: s.name.to_string(),
+                        baseline_bytes: b.bytes,
+                        bytes: s.bytes,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Produces a report of the serialized sizes of all of the codec's samples
+pub fn size_report() -> Result<SizeReport, Box<dyn Error>> {
+    let sizes = samples()
+        .iter()
+        .map(|s| {
+            Ok(SampleSize {
+                module: s.module.to_string(),
+                name: s.name.to_string(),
+                bytes: s.encode()?.len(),
+            })
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    Ok(SizeReport {
+        version: crate::VERSION.to_string(),
+        sizes,
+    })
+}
+
+fn selected(modules: &[&str], module: &str) -> bool {
+    modules.is_empty() || modules.contains(&module)
+}
+
+/// Writes one JSON line per sample to the writer. If `modules` is non-empty, only
+/// samples in those modules are exported
+pub fn export_jsonl<W: Write>(writer: &mut W, modules: &[&str]) -> Result<(), Box<dyn Error>> {
+    for sample in samples().iter().filter(|s| selected(modules, s.module)) {
+        let record = SampleRecord {
+            version: crate::VERSION.to_string(),
+            module: sample.module.to_string(),
+            name: sample.name.to_string(),
+            payload: base64::encode(sample.encode()?),
+        };
+        serde_json::to_writer(&mut *writer, &record)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Validates a JSON lines export against the codec's samples. If `modules` is non-empty, records
+/// from other modules are skipped and only samples in those modules are reported missing
+pub fn validate_jsonl<R: BufRead>(
+    reader: R,
+    modules: &[&str],
+) -> Result<ValidationReport, Box<dyn Error>> {
+    let suite = samples();
+    let mut seen = HashSet::new();
+    let mut report = ValidationReport::default();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: SampleRecord = serde_json::from_str(&line)?;
+        if !selected(modules, &record.module) {
+            continue;
+        }
+        seen.insert((record.module.clone(), record.name.clone()));
+        let result = match suite
+            .iter()
+            .find(|s| s.module == record.module && s.name == record.name)
+        {
+            Some(sample) => match base64::decode(&record.payload) {
+                Ok(bytes) => sample.validate(&bytes),
+                Err(e) => SampleResult {
+                    module: record.module,
+                    name: record.name,
+                    status: SampleStatus::DecodeFailed,
+                    diffs: vec![],
+                    message: Some(e.to_string()),
+                },
+            },
+            None => SampleResult {
+                module: record.module,
+                name: record.name,
+                status: SampleStatus::Unknown,
+                diffs: vec![],
+                message: None,
+            },
+        };
+        report.results.push(result);
+    }
+    for sample in suite.iter().filter(|s| selected(modules, s.module)) {
+        if !seen.contains(&(sample.module.to_string(), sample.name.to_string())) {
+            report.results.push(SampleResult {
+                module: sample.module.to_string(),
+                name: sample.name.to_string(),
+                status: SampleStatus::Missing,
+                diffs: vec![],
+                message: None,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Round-trips each sample through the codec, returning a description of each sample that did not
+/// decode to a value equal to itself
+pub fn check_round_trips(samples: &[SampleType]) -> Vec<String> {
+    samples
+        .iter()
+        .map(SampleType::round_trip)
+        .filter(|r| r.status != SampleStatus::Passed)
+        .map(|r| {
+            format!(
+                "{}/{}: {:?} {}",
+                r.module,
+                r.name,
+                r.status,
+                r.message.clone().unwrap_or_else(|| diff_paths(&r.diffs))
+            )
+        })
+        .collect()
+}
+
+/// Compares each sample against its golden file, `<dir>/<module>/<name>.json`, returning a description
+/// of each failure. Missing golden files are written. A golden file fails if its payload no longer
+/// decodes, if the type's schema changed incompatibly since it was written, or if the sample's value
+/// changed. With `bless`, golden files that fail only because the value changed are rewritten instead
+pub fn check_golden<P: AsRef<Path>>(
+    samples: &[SampleType],
+    dir: P,
+    bless: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut failures = Vec::new();
+    for sample in samples {
+        let current = sample.golden()?;
+        let path = dir
+            .as_ref()
+            .join(sample.module)
+            .join(format!("{}.json", sample.name));
+        if !path.exists() {
+            write_golden(&path, &current)?;
+            continue;
+        }
+        let golden: GoldenSample = serde_json::from_slice(&fs::read(&path)?)?;
+        let id = format!("{}/{}", sample.module, sample.name);
+
+        let mut breaking = false;
+        if let Err(e) = sample.decode(&base64::decode(&golden.payload)?) {
+            failures.push(format!("{}: golden payload no longer decodes: {}", id, e));
+            breaking = true;
+        }
+        for change in check_compatibility(&golden.schema, &current.schema).breaking_changes() {
+            failures.push(format!("{}: breaking schema change {:?}", id, change));
+            breaking = true;
+        }
+
+        let mut diffs = Vec::new();
+        diff_values("", &golden.json, &current.json, &mut diffs);
+        if !diffs.is_empty() {
+            if bless && !breaking {
+                write_golden(&path, &current)?;
+            } else {
+                failures.push(format!(
+                    "{}: sample differs from golden file at {} (set {} to update it)",
+                    id,
+                    diff_paths(&diffs),
+                    BLESS_ENV
+                ));
+            }
+        }
+    }
+    Ok(failures)
+}
+
+fn write_golden(path: &Path, golden: &GoldenSample) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut json = serde_json::to_vec_pretty(golden)?;
+    json.push(b'\n');
+    fs::write(path, json)?;
+    Ok(())
+}
+
+fn diff_paths(diffs: &[FieldDiff]) -> String {
+    diffs
+        .iter()
+        .map(|d| d.path.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generates conformance tests for a list of payload types implementing `Sample`. Each entry names the
+/// module and sample name under which the type's golden file is stored, followed by the type. The
+/// generated tests round-trip every sample and compare it against its golden file with `check_golden`,
+/// rewriting golden files when the `WASCC_CONFORMANCE_BLESS` environment variable is set
+#[macro_export]
+macro_rules! conformance {
+    (golden_dir = $dir:expr; $($module:literal, $name:literal => $t:ty),* $(,)?) => {
+        #[cfg(test)]
+        mod conformance_suite {
+            #[allow(unused_imports)]
+            use super::*;
+
+            fn samples() -> Vec<$crate::conformance::SampleType> {
+                vec![$($crate::conformance::SampleType::new::<$t>($module, $name)),*]
+            }
+
+            #[test]
+            fn samples_round_trip() {
+                let failures = $crate::conformance::check_round_trips(&samples());
+                assert!(failures.is_empty(), "{}", failures.join("\n"));
+            }
+
+            #[test]
+            fn samples_match_golden_files() {
+                let bless = std::env::var_os($crate::conformance::BLESS_ENV).is_some();
+                let failures =
+                    $crate::conformance::check_golden(&samples(), $dir, bless).unwrap();
+                assert!(failures.is_empty(), "{}", failures.join("\n"));
+            }
+        }
+    };
+}
+
+fn diff_values(path: &str, expected: &Value, actual: &Value, diffs: &mut Vec<FieldDiff>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            let mut keys: Vec<&String> = e.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for k in keys {
+                let child = if path.is_empty() {
+                    k.to_string()
+                } else {
+                    format!("{}.{}", path, k)
+                };
+                match (e.get(k), a.get(k)) {
+                    (Some(ev), Some(av)) => diff_values(&child, ev, av, diffs),
+                    (ev, av) => diffs.push(FieldDiff {
+                        path: child,
+                        expected: ev.cloned(),
+                        actual: av.cloned(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (i, (ev, av)) in e.iter().zip(a.iter()).enumerate() {
+                diff_values(&format!("{}[{}]", path, i), ev, av, diffs);
+            }
+        }
+        (e, a) if e != a => diffs.push(FieldDiff {
+            path: path.to_string(),
+            expected: Some(e.clone()),
+            actual: Some(a.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check_golden, export_jsonl, validate_jsonl, SampleRecord, SampleStatus, SampleType,
+    };
+    use crate::http::{Method, Request};
+    use crate::keyvalue::SetRequest;
+    use crate::Sample;
+    use std::fs;
+
+    conformance! {
+        golden_dir = "tests/golden";
+        "httpserver", "request" => Request,
+        "keyvalue", "setrequest" => SetRequest,
+    }
+
+    #[test]
+    fn golden_files_are_written_compared_and_blessed() {
+        let dir = std::env::temp_dir().join(format!("wascc-codec-golden-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let samples = vec![SampleType::new::<SetRequest>("keyvalue", "setrequest")];
+        assert!(check_golden(&samples, &dir, false).unwrap().is_empty());
+
+        let path = dir.join("keyvalue").join("setrequest.json");
+        let mut golden: serde_json::Value =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        golden["json"]["value"] = "stale".into();
+        fs::write(&path, serde_json::to_vec(&golden).unwrap()).unwrap();
+        let failures = check_golden(&samples, &dir, false).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].contains("value"));
+
+        assert!(check_golden(&samples, &dir, true).unwrap().is_empty());
+        assert!(check_golden(&samples, &dir, false).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_validates_and_reports_diffs() {
+        let mut buf = Vec::new();
+        export_jsonl(&mut buf, &[]).unwrap();
+        let report = validate_jsonl(&buf[..], &[]).unwrap();
+        assert!(report.passed());
+
+        let mut req = Request::sample();
+        req.method = Method::Post;
+        let record = SampleRecord {
+            version: crate::VERSION.to_string(),
+            module: "httpserver".to_string(),
+            name: "request".to_string(),
+            payload: base64::encode(crate::serialize(req).unwrap()),
+        };
+        let line = serde_json::to_string(&record).unwrap();
+        let report = validate_jsonl(line.as_bytes(), &["httpserver"]).unwrap();
+        assert_eq!(report.results[0].status, SampleStatus::Mismatch);
+        assert_eq!(report.results[0].diffs[0].path, "method");
+        assert_eq!(report.results[1].name, "response");
+        assert_eq!(report.results[1].status, SampleStatus::Missing);
+    }
+}
@@ -0,0 +1,189 @@
+//! # Capability contract verification
+//!
+//! This module lets a host compare the operations an actor requires of a capability against the
+//! operations a provider declares in its `CapabilityDescriptor`, so incompatible bindings can be
+//! refused with an actionable report instead of failing on the first misrouted invocation
+
+use crate::capabilities::{CapabilityDescriptor, CapabilityId, OperationDirection};
+use crate::errors::WasccCodecError;
+use std::fmt;
+
+/// The operations an actor requires of a single capability
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractRequirement {
+    /// The capability the actor is binding to
+    pub capability_id: CapabilityId,
+    /// The minimum provider revision the actor was built against, 0 - any revision
+    #[serde(default)]
+    pub min_revision: u32,
+    #[serde(default)]
+    pub operations: Vec<RequiredOperation>,
+}
+
+/// A single operation required by an actor, and the direction in which it will be invoked
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredOperation {
+    pub name: String,
+    pub direction: OperationDirection,
+}
+
+/// An operation the provider declares, but in a direction other than the one the actor requires
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectionMismatch {
+    pub operation: String,
+    pub required: OperationDirection,
+    pub declared: OperationDirection,
+}
+
+/// The result of verifying an actor's requirements against a provider's descriptor
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractReport {
+    /// The capability ID the actor requires
+    pub capability_id: CapabilityId,
+    /// The capability ID the provider declares, if it differs from the required one
+    #[serde(default)]
+    pub provider_capability_id: Option<CapabilityId>,
+    /// Required operations the provider does not declare
+    #[serde(default)]
+    pub missing_operations: Vec<String>,
+    #[serde(default)]
+    pub direction_mismatches: Vec<DirectionMismatch>,
+    /// The required and actual revisions, if the provider's revision is older than required
+    #[serde(default)]
+    pub revision_gap: Option<(u32, u32)>,
+}
+
+impl ContractReport {
+    /// Indicates whether the provider satisfies all of the actor's requirements
+    pub fn is_compatible(&self) -> bool {
+        self.provider_capability_id.is_none()
+            && self.missing_operations.is_empty()
+            && self.direction_mismatches.is_empty()
+            && self.revision_gap.is_none()
+    }
+
+    /// Converts the report into an error if the provider is incompatible
+    pub fn into_result(self) -> Result<(), WasccCodecError> {
+        if self.is_compatible() {
+            Ok(())
+        } else {
+            Err(WasccCodecError::IncompatibleContract {
+                capability: self.capability_id.to_string(),
+                details: self.to_string(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_compatible() {
+            return write!(f, "provider satisfies all requirements");
+        }
+        let mut problems = Vec::new();
+        if let Some(ref id) = self.provider_capability_id {
+            problems.push(format!("provider implements '{}'", id));
+        }
+        if !self.missing_operations.is_empty() {
+            problems.push(format!(
+                "missing operations: {}",
+                self.missing_operations.join(", ")
+            ));
+        }
+        for m in &self.direction_mismatches {
+            problems.push(format!(
+                "operation '{}' is declared {:?} but required {:?}",
+                m.operation, m.declared, m.required
+            ));
+        }
+        if let Some((required, actual)) = self.revision_gap {
+            problems.push(format!(
+                "provider revision {} is older than required revision {}",
+                actual, required
+            ));
+        }
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+/// Verifies an actor's requirements against a provider's capability descriptor
+pub fn verify_contract(
+    requirement: &ContractRequirement,
+    provider: &CapabilityDescriptor,
+) -> ContractReport {
+    let mut report = ContractReport {
+        capability_id: requirement.capability_id.clone(),
+        provider_capability_id: None,
+        missing_operations: vec![],
+        direction_mismatches: vec![],
+        revision_gap: None,
+    };
+    if provider.id != requirement.capability_id {
+        report.provider_capability_id = Some(provider.id.clone());
+    }
+    if provider.revision < requirement.min_revision {
+        report.revision_gap = Some((requirement.min_revision, provider.revision));
+    }
+    for required in &requirement.operations {
+        match provider
+            .supported_operations
+            .iter()
+            .find(|o| o.name == required.name)
+        {
+            None => report.missing_operations.push(required.name.to_string()),
+            Some(declared) if !declared.direction.permits(&required.direction) => {
+                report.direction_mismatches.push(DirectionMismatch {
+                    operation: required.name.to_string(),
+                    required: required.direction.clone(),
+                    declared: declared.direction.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::{verify_contract, ContractRequirement, RequiredOperation};
+    use crate::capabilities::{CapabilityDescriptor, OperationDirection};
+
+    #[test]
+    fn report_lists_all_incompatibilities() {
+        let provider = CapabilityDescriptor::builder()
+            .id("wascc:messaging")
+            .revision(2)
+            .with_operation("Publish", OperationDirection::ToProvider, "")
+            .with_operation("DeliverMessage", OperationDirection::ToActor, "")
+            .build();
+        let requirement = ContractRequirement {
+            capability_id: "wascc:messaging".into(),
+            min_revision: 3,
+            operations: vec![
+                RequiredOperation {
+                    name: "Publish".to_string(),
+                    direction: OperationDirection::ToProvider,
+                },
+                RequiredOperation {
+                    name: "DeliverMessage".to_string(),
+                    direction: OperationDirection::ToProvider,
+                },
+                RequiredOperation {
+                    name: "Request".to_string(),
+                    direction: OperationDirection::ToProvider,
+                },
+            ],
+        };
+        let report = verify_contract(&requirement, &provider);
+        assert!(!report.is_compatible());
+        assert_eq!(report.missing_operations, vec!["Request".to_string()]);
+        assert_eq!(report.direction_mismatches[0].operation, "DeliverMessage");
+        assert_eq!(report.revision_gap, Some((3, 2)));
+        assert!(report.into_result().is_err());
+    }
+}
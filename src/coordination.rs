@@ -0,0 +1,115 @@
+//! # Coordination
+//!
+//! This module contains data types for the `wascc:coordination` capability provider. Providers backed
+//! by consensus stores such as etcd or ZooKeeper offer leader election to groups of actors: each member
+//! campaigns in a named election, and the provider notifies the winner when it gains leadership and
+//! again if it loses it
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+
+/// Guest sends a CampaignRequest to the capability provider, lack of error indicates the candidate has
+/// entered the election. The guest will receive OP_LEADERSHIP_GRANTED if and when it becomes leader
+pub const OP_CAMPAIGN: &str = "Campaign";
+/// Provider sends LeadershipGranted to a guest when its candidate becomes leader
+pub const OP_LEADERSHIP_GRANTED: &str = "LeadershipGranted";
+/// Provider sends LeadershipRevoked to a guest when its candidate is no longer leader
+pub const OP_LEADERSHIP_REVOKED: &str = "LeadershipRevoked";
+/// Guest sends a ResignRequest to the capability provider to give up leadership or leave the election,
+/// lack of error indicates success
+pub const OP_RESIGN: &str = "Resign";
+/// Guest sends a LeaderQuery to the capability provider, receives a LeaderInfo back
+pub const OP_QUERY_LEADER: &str = "QueryLeader";
+
+/// Enters a candidate into an election
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignRequest {
+    /// The name of the election, shared by all candidates
+    pub election: String,
+    /// Uniquely identifies the candidate within the election
+    pub candidate_id: String,
+    /// The leadership lease. If the candidate's host stops renewing its session for this long, the
+    /// candidate loses leadership (or leaves the election) and another candidate may be elected
+    pub ttl: DurationMs,
+    /// Optional data published while the candidate is leader, e.g. its address
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl Sample for CampaignRequest {
+    fn sample() -> Self {
+        CampaignRequest {
+            election: "billing-scheduler".to_string(),
+            candidate_id: "Mxxx-host-1".to_string(),
+            ttl: DurationMs(10_000),
+            value: Some("10.0.0.12:9000".to_string()),
+        }
+    }
+}
+
+/// Notifies a candidate that it has been elected leader
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadershipGranted {
+    pub election: String,
+    pub candidate_id: String,
+    /// Increases each time leadership changes hands. Leaders should include the term in writes to shared
+    /// resources so that writes from a deposed leader can be fenced off
+    pub term: u64,
+}
+
+/// The reason a leader lost leadership
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationReason {
+    /// The leader resigned
+    Resigned,
+    /// The leader's lease expired before it was renewed
+    LeaseExpired,
+    /// The provider lost its connection to the consensus store
+    SessionLost,
+}
+
+/// Notifies a leader that it is no longer leader. The actor must stop acting as leader immediately
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeadershipRevoked {
+    pub election: String,
+    pub candidate_id: String,
+    /// The term that has ended
+    pub term: u64,
+    pub reason: RevocationReason,
+}
+
+/// Gives up leadership, or withdraws a candidate that has not yet been elected
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResignRequest {
+    pub election: String,
+    pub candidate_id: String,
+}
+
+/// A query for the current leader of an election
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderQuery {
+    pub election: String,
+}
+
+/// The current leader of an election
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderInfo {
+    pub election: String,
+    /// The leader's candidate ID, or `None` if the election currently has no leader
+    #[serde(default)]
+    pub leader: Option<String>,
+    /// The value published by the leader
+    #[serde(default)]
+    pub value: Option<String>,
+    pub term: u64,
+    /// When the leader's current lease expires unless renewed
+    #[serde(default)]
+    pub lease_expires_at: Option<Timestamp>,
+}
@@ -3,14 +3,31 @@
 //! This module contains data types used for wascc actor module and host runtime communications
 //! that is not specific to any given capability provider
 
+use crate::capabilities::CapabilityId;
+use crate::errors::WasccCodecError;
+use crate::fnv;
+use crate::sha256::{HmacSha256, Sha256};
+use crate::Sample;
 use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub const OP_PERFORM_LIVE_UPDATE: &str = "PerformLiveUpdate";
 pub const OP_IDENTIFY_CAPABILITY: &str = "IdentifyCapability";
 pub const OP_HEALTH_REQUEST: &str = "HealthRequest";
+/// Host sends an InitializePayload to a capability provider once at startup, before any other
+/// operation, receives an InitializeAck back
 pub const OP_INITIALIZE: &str = "Initialize";
 pub const OP_BIND_ACTOR: &str = "BindActor";
 pub const OP_REMOVE_ACTOR: &str = "RemoveActor";
+pub const OP_UPDATE_CONFIGURATION: &str = "UpdateConfiguration";
+pub const OP_CLAIMS_EXPIRING: &str = "ClaimsExpiring";
+pub const OP_CLAIMS_RENEWED: &str = "ClaimsRenewed";
+/// Host sends a ready check to a capability provider, receives a ReadinessReport back. Unlike a health
+/// check, a provider that is alive but still initializing reports that it is not ready
+pub const OP_READY_CHECK: &str = "ReadyCheck";
+/// Host-management actor sends a QueryBindings to the host, receives a BindingsSnapshot back
+pub const OP_QUERY_BINDINGS: &str = "QueryBindings";
 
 // Keys used for providing actor claim data to a capability provider during binding
 
@@ -19,6 +36,13 @@ pub const CONFIG_WASCC_CLAIMS_CAPABILITIES: &str = "__wascc_capabilities";
 pub const CONFIG_WASCC_CLAIMS_NAME: &str = "__wascc_name";
 pub const CONFIG_WASCC_CLAIMS_EXPIRES: &str = "__wascc_expires";
 pub const CONFIG_WASCC_CLAIMS_TAGS: &str = "__wascc_tags";
+/// Configuration key listing, comma-separated, the keys whose values are secrets. Secret values are
+/// redacted from the configuration's `Debug` output and should be read with `CapabilityConfiguration::secret`
+pub const CONFIG_WASCC_SECRET_KEYS: &str = "__wascc_secret_keys";
+
+/// The version of the `CapabilityConfiguration` wire format written by this codec. Payloads without a
+/// `schema_version` field predate it and are version 1
+pub const CAPABILITY_CONFIGURATION_SCHEMA_VERSION: u32 = 1;
 
 /// LiveUpdate is used when a module is being replaced. The bytes contained in this message will, if valid,
 /// replace the existing actor. This message is sent to an actor from the "system" origin
@@ -41,11 +65,1421 @@ pub struct HealthRequest {
 /// Capability providers must be able to accept configuration values on a per-actor basis. The module
 /// field will be the public key of the actor (the `sub` field of its embedded JWT), though providers
 /// should treat this string as opaque data to be used as a key
-#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
 pub struct CapabilityConfiguration {
     /// The key to be used to distinguish actor configuration, this is the subject's public key
     pub module: String,
     /// Raw configuration values
     #[serde(default)]
     pub values: HashMap<String, String>,
+    /// The version of the wire format the configuration was written in. Receivers call `migrate` (or
+    /// decode with `from_compat_bytes`) to bring older configurations up to date
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+}
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+impl CapabilityConfiguration {
+    /// Creates an empty configuration for the given actor in the current wire format version
+    pub fn new(module: &str) -> CapabilityConfiguration {
+        CapabilityConfiguration {
+            module: module.to_string(),
+            values: HashMap::new(),
+            schema_version: CAPABILITY_CONFIGURATION_SCHEMA_VERSION,
+        }
+    }
+
+    /// Decodes a configuration written in any supported version of the wire format, including
+    /// payloads that predate the `schema_version` field, and migrates it to the current version
+    pub fn from_compat_bytes(
+        buf: &[u8],
+    ) -> Result<CapabilityConfiguration, Box<dyn std::error::Error>> {
+        let config: CapabilityConfiguration = crate::deserialize(buf)?;
+        Ok(config.migrate()?)
+    }
+
+    /// Upgrades a configuration to the current wire format version. Versions newer than this codec
+    /// supports are rejected rather than guessed at
+    pub fn migrate(self) -> Result<CapabilityConfiguration, WasccCodecError> {
+        match self.schema_version {
+            // Version 1 is current. Future versions add a step here for each version they supersede,
+            // upgrading one version at a time
+            1 => Ok(self),
+            found => Err(WasccCodecError::UnsupportedSchemaVersion {
+                type_name: "CapabilityConfiguration".to_string(),
+                found,
+                supported: CAPABILITY_CONFIGURATION_SCHEMA_VERSION,
+            }),
+        }
+    }
+
+    /// Applies a configuration delta to this configuration, overwriting changed values
+    /// and dropping removed keys
+    pub fn apply_delta(&mut self, delta: &ConfigurationDelta) {
+        for key in &delta.removed {
+            self.values.remove(key);
+        }
+        for (k, v) in &delta.changed {
+            self.values.insert(k.to_string(), v.to_string());
+        }
+    }
+
+    /// Computes a stable fingerprint of the configuration values, suitable for detecting configuration
+    /// drift between hosts. The fingerprint is independent of key order but is not a cryptographic hash
+    pub fn config_hash(&self) -> String {
+        let mut keys: Vec<&String> = self.values.keys().collect();
+        keys.sort();
+        let fields = keys
+            .into_iter()
+            .flat_map(|k| vec![k.as_bytes(), self.values[k].as_bytes()]);
+        fnv::fingerprint(fields, 0)
+    }
+
+    /// Indicates whether the value of the given key is marked as a secret
+    pub fn is_secret(&self, key: &str) -> bool {
+        self.values
+            .get(CONFIG_WASCC_SECRET_KEYS)
+            .is_some_and(|keys| keys.split(',').any(|k| k.trim() == key))
+    }
+
+    /// Marks the value of the given key as a secret. Keys containing a comma are rejected, as the list
+    /// of secret keys is stored comma-separated
+    pub fn mark_secret(&mut self, key: &str) -> Result<(), WasccCodecError> {
+        if key.contains(',') {
+            return Err(WasccCodecError::InvalidKeyCharacter {
+                key: key.to_string(),
+                character: ',',
+            });
+        }
+        if self.is_secret(key) {
+            return Ok(());
+        }
+        let keys = self
+            .values
+            .entry(CONFIG_WASCC_SECRET_KEYS.to_string())
+            .or_default();
+        if !keys.is_empty() {
+            keys.push(',');
+        }
+        keys.push_str(key);
+        Ok(())
+    }
+
+    /// Returns the value of a key marked as a secret. Returns `None` if the key is absent or is not
+    /// marked as a secret, so that secrets are never read through a path that may log them. The
+    /// returned copy is wiped when dropped, but the plaintext held in `values` is not
+    pub fn secret(&self, key: &str) -> Option<SecretString> {
+        if self.is_secret(key) {
+            self.values.get(key).map(|v| SecretString::from(v.as_str()))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for CapabilityConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: HashMap<&str, &str> = self
+            .values
+            .iter()
+            .map(|(k, v)| {
+                if self.is_secret(k) {
+                    (k.as_str(), REDACTED)
+                } else {
+                    (k.as_str(), v.as_str())
+                }
+            })
+            .collect();
+        f.debug_struct("CapabilityConfiguration")
+            .field("module", &self.module)
+            .field("values", &values)
+            .field("schema_version", &self.schema_version)
+            .finish()
+    }
+}
+
+/// Sent to an actor from the "system" origin when the actor's signed claims are nearing expiration. Once the
+/// claims expire, the host will stop the actor, so long-running actors can use this notice to drain work or
+/// request renewal
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimsExpiring {
+    /// The time at which the actor's claims expire
+    pub expires_at: Timestamp,
+    /// Indicates whether the host is able to renew the actor's claims (e.g. from a trusted issuer)
+    pub renewable: bool,
+}
+
+/// Sent to an actor from the "system" origin after the actor's claims have been renewed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimsRenewed {
+    /// The new expiration time of the actor's claims, or `None` if they no longer expire
+    #[serde(default)]
+    pub expires_at: Option<Timestamp>,
+}
+
+/// A capability provider's response to a ready check. Hosts should not dispatch actor invocations to a
+/// provider until it reports that it is ready
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessReport {
+    pub ready: bool,
+    /// Human-readable descriptions of initialization tasks still in progress (e.g. "priming cache")
+    #[serde(default)]
+    pub pending_tasks: Vec<String>,
+    /// The provider's estimate of how long it will be until it is ready, if known
+    #[serde(default)]
+    pub eta: Option<DurationMs>,
+}
+
+impl ReadinessReport {
+    /// Creates a report indicating the provider is ready
+    pub fn ready() -> ReadinessReport {
+        ReadinessReport {
+            ready: true,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a report indicating the provider is still working on the given tasks
+    pub fn pending(tasks: Vec<String>, eta: Option<DurationMs>) -> ReadinessReport {
+        ReadinessReport {
+            ready: false,
+            pending_tasks: tasks,
+            eta,
+        }
+    }
+}
+
+/// Limits a host imposes on a capability provider. `None` means the host imposes no limit
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderLimits {
+    /// The largest invocation payload, in bytes, the host will accept from the provider
+    #[serde(default)]
+    pub max_payload_bytes: Option<u64>,
+    /// The number of invocations the host will dispatch to the provider at once
+    #[serde(default)]
+    pub max_concurrent_invocations: Option<u32>,
+    /// How long the host waits for the provider to handle an invocation
+    #[serde(default)]
+    pub invocation_timeout: Option<DurationMs>,
+    /// The memory available to the provider, in bytes
+    #[serde(default)]
+    pub memory_bytes: Option<u64>,
+}
+
+/// Sent by the host to a capability provider with `OP_INITIALIZE`, describing the environment in which
+/// the provider is running
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializePayload {
+    /// The unique ID (public key) of the host
+    pub host_id: String,
+    pub host_version: String,
+    /// The codec version the host was built with
+    pub codec_version: String,
+    #[serde(default)]
+    pub labels: HostLabels,
+    /// The ID the host assigned to this instance of the provider, unique within the host. Providers
+    /// include it in logs and metrics to tell apart several instances of the same capability
+    pub provider_instance_id: String,
+    #[serde(default)]
+    pub limits: ProviderLimits,
+    /// The shard this instance owns, if the provider declared itself sharded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardAssignment>,
+}
+
+impl InitializePayload {
+    /// Creates a payload for a host built against this version of the codec
+    pub fn new(host_id: &str, host_version: &str, provider_instance_id: &str) -> InitializePayload {
+        InitializePayload {
+            host_id: host_id.to_string(),
+            host_version: host_version.to_string(),
+            codec_version: crate::VERSION.to_string(),
+            labels: HostLabels::default(),
+            provider_instance_id: provider_instance_id.to_string(),
+            limits: ProviderLimits::default(),
+            shard: None,
+        }
+    }
+
+    pub fn with_shard(self, shard: ShardAssignment) -> InitializePayload {
+        InitializePayload {
+            shard: Some(shard),
+            ..self
+        }
+    }
+
+    /// The lattice-wide identity of the provider instance being initialized
+    pub fn instance_id(&self) -> ProviderInstanceId {
+        ProviderInstanceId::new(&self.host_id, &self.provider_instance_id)
+    }
+
+    pub fn with_labels(self, labels: HostLabels) -> InitializePayload {
+        InitializePayload { labels, ..self }
+    }
+
+    pub fn with_limits(self, limits: ProviderLimits) -> InitializePayload {
+        InitializePayload { limits, ..self }
+    }
+
+    /// Produces the acknowledgement of this payload from a provider built against this version of the codec
+    pub fn ack(&self) -> InitializeAck {
+        InitializeAck {
+            provider_instance_id: self.provider_instance_id.to_string(),
+            codec_version: crate::VERSION.to_string(),
+            warnings: vec![],
+            shard: self.shard,
+        }
+    }
+}
+
+impl Sample for InitializePayload {
+    fn sample() -> Self {
+        let mut labels = HostLabels::new();
+        labels.insert(LABEL_ARCH, "x86_64");
+        labels.insert(LABEL_OS, "linux");
+        InitializePayload {
+            codec_version: "0.7.2".to_string(),
+            ..InitializePayload::new(
+                "NBLQ7RIN4KGXR3YDZ6LLZSGEFUCQIERG4PMTMRH3RJPCJEKMQXJKG36X",
+                "0.9.0",
+                "wascc:keyvalue/0",
+            )
+        }
+        .with_labels(labels)
+        .with_limits(ProviderLimits {
+            max_payload_bytes: Some(1024 * 1024),
+            max_concurrent_invocations: Some(64),
+            invocation_timeout: Some(DurationMs(30_000)),
+            memory_bytes: None,
+        })
+    }
+}
+
+/// A capability provider's response to an `InitializePayload`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeAck {
+    /// Echoes the instance ID assigned by the host
+    pub provider_instance_id: String,
+    /// The codec version the provider was built with
+    pub codec_version: String,
+    /// Human-readable descriptions of anything in the payload the provider cannot honor, such as a
+    /// limit it does not support. The host may log these or refuse to use the provider
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// Echoes the shard the instance took ownership of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardAssignment>,
+}
+
+/// Identifies a provider instance across a lattice: the ID of its host and the instance ID the host
+/// assigned, joined by a `/`. Serialized as a plain string
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ProviderInstanceId(String);
+
+impl ProviderInstanceId {
+    pub fn new(host_id: &str, provider_instance_id: &str) -> ProviderInstanceId {
+        ProviderInstanceId(format!("{}/{}", host_id, provider_instance_id))
+    }
+
+    pub fn host_id(&self) -> &str {
+        self.0.split('/').next().unwrap_or_default()
+    }
+
+    /// The instance ID assigned by the host, unique within the host
+    pub fn local_id(&self) -> &str {
+        self.0.split_once('/').map_or("", |(_, local)| local)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ProviderInstanceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The shard of a sharded provider's state owned by one of its instances
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardAssignment {
+    /// The index of the owned shard, from 0 to `count - 1`
+    pub index: u32,
+    pub count: u32,
+}
+
+impl ShardAssignment {
+    /// Indicates whether an invocation carrying the given hint belongs to this shard. Hints naming an
+    /// instance are matched by hosts, not shards, and never belong to one
+    pub fn owns(&self, hint: &ShardHint) -> bool {
+        hint.shard_index(self.count) == Some(self.index)
+    }
+}
+
+/// Tells the host which instance of a sharded provider an invocation is for
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ShardHint {
+    /// Route by a key (e.g. a tenant or entity ID). Invocations with the same key always reach the
+    /// same shard
+    Key(String),
+    /// Route to an explicit shard index
+    Shard(u32),
+    /// Route to a specific instance, e.g. the one holding a session
+    Instance(ProviderInstanceId),
+}
+
+impl ShardHint {
+    /// The shard the hint routes to, out of the given number of shards. Keys are mapped with jump
+    /// consistent hashing over the first 8 bytes of their SHA-256, so every host computes the same
+    /// shard and growing the shard count moves as few keys as possible
+    pub fn shard_index(&self, shard_count: u32) -> Option<u32> {
+        if shard_count == 0 {
+            return None;
+        }
+        match self {
+            ShardHint::Key(key) => {
+                let mut hasher = Sha256::new();
+                hasher.update(key.as_bytes());
+                let digest = hasher.finish();
+                let mut prefix = [0u8; 8];
+                prefix.copy_from_slice(&digest[..8]);
+                Some(jump_consistent_hash(
+                    u64::from_be_bytes(prefix),
+                    shard_count,
+                ))
+            }
+            ShardHint::Shard(index) if *index < shard_count => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+// Lamping and Veach, "A Fast, Minimal Memory, Consistent Hash Algorithm"
+fn jump_consistent_hash(mut key: u64, buckets: u32) -> u32 {
+    let mut b: i64 = -1;
+    let mut j: i64 = 0;
+    while j < i64::from(buckets) {
+        b = j;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as u32
+}
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A string holding a secret, such as a password or API key. Its `Debug` and `Display` output is
+/// redacted, and its memory is overwritten when it is dropped. Serializing it writes the secret itself,
+/// as payloads carrying secrets must deliver them
+#[derive(PartialEq, Eq, Clone, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+
+    /// Returns the secret. Callers must take care not to log the returned value
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> SecretString {
+        SecretString(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString({})", REDACTED)
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        // Volatile writes keep the compiler from eliding the wipe of memory that is about to be freed.
+        // SAFETY: only zero bytes, which are valid UTF-8, are written within the string's length
+        let bytes = unsafe { self.0.as_mut_vec() };
+        for b in bytes.iter_mut() {
+            unsafe { std::ptr::write_volatile(b, 0) };
+        }
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// A filter for the bindings returned by `OP_QUERY_BINDINGS`. Empty filters match all bindings
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryBindings {
+    /// Only return bindings of the actor with this public key
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Only return bindings to this capability
+    #[serde(default)]
+    pub capability_id: Option<CapabilityId>,
+}
+
+/// The state of a single live binding between an actor and a capability provider
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BindingSnapshot {
+    /// The public key of the bound actor
+    pub actor: String,
+    pub capability_id: CapabilityId,
+    /// The name of the binding, `default` unless the actor is bound to several providers of the same capability
+    pub binding_name: String,
+    /// The `CapabilityConfiguration::config_hash` of the binding's configuration
+    pub config_hash: String,
+    pub bound_at: Timestamp,
+    /// The last time the actor invoked the provider (or vice versa) over this binding, if ever
+    #[serde(default)]
+    pub last_invoked_at: Option<Timestamp>,
+}
+
+/// A consistent snapshot of every live binding on a host, taken at a single point in time
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BindingsSnapshot {
+    /// The ID of the host the snapshot was taken from
+    pub host_id: String,
+    pub taken_at: Timestamp,
+    #[serde(default)]
+    pub bindings: Vec<BindingSnapshot>,
+}
+
+/// Sent to a capability provider with the `OP_UPDATE_CONFIGURATION` operation to change the
+/// configuration of a live binding without removing and re-binding the actor
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationDelta {
+    /// The public key of the actor whose binding is being reconfigured
+    pub module: String,
+    /// Keys that have been added or whose values have changed, along with their new values
+    #[serde(default)]
+    pub changed: HashMap<String, String>,
+    /// Keys that have been removed from the configuration
+    #[serde(default)]
+    pub removed: Vec<String>,
+}
+
+/// The provider's response to a configuration update. Keys that the provider could not apply
+/// to the live binding are reported so the host can decide whether to re-bind the actor
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationUpdateResult {
+    /// Keys that were applied to the live binding without requiring a restart
+    #[serde(default)]
+    pub applied: Vec<String>,
+    /// Keys that can only take effect after the actor is removed and re-bound
+    #[serde(default)]
+    pub restart_required: Vec<String>,
+}
+
+/// A point in time, expressed as milliseconds since the Unix epoch (UTC). Serialized as a bare
+/// unsigned integer so it remains compatible with fields that previously used raw `u64` values
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    /// Creates a timestamp from milliseconds since the epoch
+    pub fn from_millis(millis: u64) -> Timestamp {
+        Timestamp(millis)
+    }
+
+    /// Creates a timestamp from seconds since the epoch
+    pub fn from_secs(secs: u64) -> Timestamp {
+        Timestamp(secs.saturating_mul(1000))
+    }
+
+    /// Returns the number of milliseconds since the epoch
+    pub fn as_millis(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of whole seconds since the epoch
+    pub fn as_secs(&self) -> u64 {
+        self.0 / 1000
+    }
+
+    /// The current system time. Note that this is not available to actors running in
+    /// a wasm32-unknown-unknown guest, as there is no system clock in that environment
+    pub fn now() -> Timestamp {
+        SystemTime::now().into()
+    }
+
+    /// Returns the timestamp offset by the given duration, or `None` on overflow
+    pub fn checked_add(&self, duration: DurationMs) -> Option<Timestamp> {
+        self.0.checked_add(duration.0).map(Timestamp)
+    }
+
+    /// Returns the amount of time elapsed from an earlier timestamp to this one, or `None`
+    /// if the earlier timestamp is actually later than this one
+    pub fn duration_since(&self, earlier: Timestamp) -> Option<DurationMs> {
+        self.0.checked_sub(earlier.0).map(DurationMs)
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    /// Converts a system time into a timestamp. Times before the epoch are clamped to zero
+    fn from(t: SystemTime) -> Timestamp {
+        let millis = t
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        Timestamp(millis.min(u64::MAX as u128) as u64)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(t: Timestamp) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(t.0)
+    }
+}
+
+/// A span of time, expressed in milliseconds. Serialized as a bare unsigned integer
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct DurationMs(pub u64);
+
+impl DurationMs {
+    /// Creates a duration from a number of milliseconds
+    pub fn from_millis(millis: u64) -> DurationMs {
+        DurationMs(millis)
+    }
+
+    /// Creates a duration from a number of seconds
+    pub fn from_secs(secs: u64) -> DurationMs {
+        DurationMs(secs.saturating_mul(1000))
+    }
+
+    /// Returns the total number of milliseconds in this duration
+    pub fn as_millis(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of whole seconds in this duration
+    pub fn as_secs(&self) -> u64 {
+        self.0 / 1000
+    }
+}
+
+impl From<Duration> for DurationMs {
+    /// Converts a standard duration, truncating sub-millisecond precision
+    fn from(d: Duration) -> DurationMs {
+        DurationMs(d.as_millis().min(u64::MAX as u128) as u64)
+    }
+}
+
+impl From<DurationMs> for Duration {
+    fn from(d: DurationMs) -> Duration {
+        Duration::from_millis(d.0)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_conversions {
+    use super::{DurationMs, Timestamp};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    impl From<DateTime<Utc>> for Timestamp {
+        /// Converts a UTC date-time into a timestamp. Times before the epoch are clamped to zero
+        fn from(dt: DateTime<Utc>) -> Timestamp {
+            Timestamp(dt.timestamp_millis().max(0) as u64)
+        }
+    }
+
+    impl From<Timestamp> for DateTime<Utc> {
+        /// Converts a timestamp into a UTC date-time. Timestamps beyond the range chrono can
+        /// represent are clamped to the maximum representable date-time
+        fn from(t: Timestamp) -> DateTime<Utc> {
+            let millis = t.0.min(i64::MAX as u64) as i64;
+            Utc.timestamp_millis_opt(millis)
+                .single()
+                .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        }
+    }
+
+    impl From<chrono::Duration> for DurationMs {
+        /// Converts a chrono duration, clamping negative durations to zero
+        fn from(d: chrono::Duration) -> DurationMs {
+            DurationMs(d.num_milliseconds().max(0) as u64)
+        }
+    }
+
+    impl From<DurationMs> for chrono::Duration {
+        fn from(d: DurationMs) -> chrono::Duration {
+            chrono::Duration::milliseconds(d.0.min(i64::MAX as u64) as i64)
+        }
+    }
+}
+
+/// Header used to propagate `DebugFlags` across HTTP requests and broker messages
+pub const HEADER_DEBUG_FLAGS: &str = "wascc-debug";
+/// Header used to propagate an `InvocationContext`'s trace ID across HTTP requests and broker messages
+pub const HEADER_TRACE_ID: &str = "wascc-trace-id";
+
+/// Context that accompanies an invocation and is propagated to the invocations it causes
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InvocationContext {
+    /// Correlates all of the invocations that result from a single inbound request
+    #[serde(default)]
+    pub trace_id: Option<String>,
+    #[serde(default)]
+    pub debug: DebugFlags,
+}
+
+impl InvocationContext {
+    /// Produces the context for an invocation made while handling this one. The trace ID and debug flags
+    /// are carried over unchanged
+    pub fn child(&self) -> InvocationContext {
+        self.clone()
+    }
+
+    /// Writes this context's trace ID and debug flags into a set of outbound headers
+    pub fn inject(&self, headers: &mut HashMap<String, String>) {
+        if let Some(trace_id) = &self.trace_id {
+            headers.insert(HEADER_TRACE_ID.to_string(), trace_id.clone());
+        }
+        self.debug.inject(headers);
+    }
+
+    /// Reads a context from a set of inbound headers, matching header names case-insensitively
+    pub fn extract(headers: &HashMap<String, String>) -> InvocationContext {
+        InvocationContext {
+            trace_id: headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(HEADER_TRACE_ID))
+                .map(|(_, v)| v.clone()),
+            debug: DebugFlags::extract(headers),
+        }
+    }
+}
+
+/// Flags used to single out a small fraction of invocations for detailed handling. Providers should only
+/// emit verbose logs or record payloads for invocations that carry these flags
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugFlags {
+    /// The invocation was selected for sampling
+    #[serde(default)]
+    pub sampled: bool,
+    /// Providers should log at their most detailed level while handling the invocation
+    #[serde(default)]
+    pub verbose: bool,
+    /// Providers may record request and response payloads. Payloads can contain sensitive data, so this
+    /// flag should only be set deliberately
+    #[serde(default)]
+    pub capture_payloads: bool,
+}
+
+impl DebugFlags {
+    const SAMPLED: &'static str = "sampled";
+    const VERBOSE: &'static str = "verbose";
+    const CAPTURE_PAYLOADS: &'static str = "capture-payloads";
+
+    /// Makes a sampling decision for a new invocation. `rate` is the fraction of invocations to sample
+    /// (0.0 - 1.0) and `roll` is a uniformly distributed random number, e.g. from `extras::OP_REQUEST_RANDOM`.
+    /// Sampled invocations are also marked verbose
+    pub fn sample(rate: f64, roll: u32) -> DebugFlags {
+        let sampled = (roll as f64) < rate.clamp(0.0, 1.0) * (u32::MAX as f64 + 1.0);
+        DebugFlags {
+            sampled,
+            verbose: sampled,
+            capture_payloads: false,
+        }
+    }
+
+    /// Indicates whether any flag is set
+    pub fn is_active(&self) -> bool {
+        self.sampled || self.verbose || self.capture_payloads
+    }
+
+    /// Encodes the flags as a header value, e.g. `sampled,verbose`. Returns `None` if no flag is set
+    pub fn to_header_value(&self) -> Option<String> {
+        let flags: Vec<&str> = [
+            (self.sampled, DebugFlags::SAMPLED),
+            (self.verbose, DebugFlags::VERBOSE),
+            (self.capture_payloads, DebugFlags::CAPTURE_PAYLOADS),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, name)| *name)
+        .collect();
+        if flags.is_empty() {
+            None
+        } else {
+            Some(flags.join(","))
+        }
+    }
+
+    /// Decodes flags from a header value, ignoring any unrecognized flags
+    pub fn from_header_value(value: &str) -> DebugFlags {
+        let mut flags = DebugFlags::default();
+        for flag in value.split(',').map(str::trim) {
+            if flag.eq_ignore_ascii_case(DebugFlags::SAMPLED) {
+                flags.sampled = true;
+            } else if flag.eq_ignore_ascii_case(DebugFlags::VERBOSE) {
+                flags.verbose = true;
+            } else if flag.eq_ignore_ascii_case(DebugFlags::CAPTURE_PAYLOADS) {
+                flags.capture_payloads = true;
+            }
+        }
+        flags
+    }
+
+    /// Writes the flags to the `HEADER_DEBUG_FLAGS` header, if any flag is set
+    pub fn inject(&self, headers: &mut HashMap<String, String>) {
+        if let Some(value) = self.to_header_value() {
+            headers.insert(HEADER_DEBUG_FLAGS.to_string(), value);
+        }
+    }
+
+    /// Reads the flags from the `HEADER_DEBUG_FLAGS` header, matching the header name case-insensitively
+    pub fn extract(headers: &HashMap<String, String>) -> DebugFlags {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(HEADER_DEBUG_FLAGS))
+            .map(|(_, v)| DebugFlags::from_header_value(v))
+            .unwrap_or_default()
+    }
+}
+
+/// Well-known host label containing the CPU architecture of the host (e.g. `x86_64`)
+pub const LABEL_ARCH: &str = "hostcore.arch";
+/// Well-known host label containing the operating system of the host (e.g. `linux`)
+pub const LABEL_OS: &str = "hostcore.os";
+/// Well-known host label containing the region or zone in which the host is running
+pub const LABEL_REGION: &str = "hostcore.region";
+
+/// The set of key/value labels that describe a host in a lattice. Schedulers evaluate
+/// `PlacementConstraint`s against these labels to decide where actors and providers may run
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(transparent)]
+pub struct HostLabels(pub HashMap<String, String>);
+
+impl HostLabels {
+    /// Creates an empty set of labels
+    pub fn new() -> HostLabels {
+        HostLabels::default()
+    }
+
+    /// Creates a set of labels pre-populated with the architecture and operating system of the current process
+    pub fn for_current_host() -> HostLabels {
+        let mut labels = HostLabels::new();
+        labels.insert(LABEL_ARCH, std::env::consts::ARCH);
+        labels.insert(LABEL_OS, std::env::consts::OS);
+        labels
+    }
+
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn arch(&self) -> Option<&str> {
+        self.get(LABEL_ARCH)
+    }
+
+    pub fn os(&self) -> Option<&str> {
+        self.get(LABEL_OS)
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.get(LABEL_REGION)
+    }
+
+    /// Indicates whether these labels satisfy every one of the supplied constraints
+    pub fn satisfies(&self, constraints: &[PlacementConstraint]) -> bool {
+        constraints.iter().all(|c| c.matches(self))
+    }
+}
+
+/// An expression evaluated against a host's labels to determine whether an actor or capability
+/// provider may be placed on that host
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum PlacementConstraint {
+    /// The label must be present with exactly the given value
+    Equals { key: String, value: String },
+    /// The label must be absent or have a different value
+    NotEquals { key: String, value: String },
+    /// The label must be present with one of the given values
+    In { key: String, values: Vec<String> },
+    /// The label must be present, with any value
+    Exists { key: String },
+    /// The label must not be present
+    NotExists { key: String },
+    /// Every nested constraint must match
+    All(Vec<PlacementConstraint>),
+    /// At least one nested constraint must match
+    Any(Vec<PlacementConstraint>),
+    /// The nested constraint must not match
+    Not(Box<PlacementConstraint>),
+}
+
+impl PlacementConstraint {
+    /// Shorthand for an `Equals` constraint
+    pub fn equals(key: &str, value: &str) -> PlacementConstraint {
+        PlacementConstraint::Equals {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Evaluates this constraint against a set of host labels
+    pub fn matches(&self, labels: &HostLabels) -> bool {
+        match self {
+            PlacementConstraint::Equals { key, value } => labels.get(key) == Some(value.as_str()),
+            PlacementConstraint::NotEquals { key, value } => {
+                labels.get(key) != Some(value.as_str())
+            }
+            PlacementConstraint::In { key, values } => labels
+                .get(key)
+                .is_some_and(|v| values.iter().any(|candidate| candidate == v)),
+            PlacementConstraint::Exists { key } => labels.get(key).is_some(),
+            PlacementConstraint::NotExists { key } => labels.get(key).is_none(),
+            PlacementConstraint::All(constraints) => constraints.iter().all(|c| c.matches(labels)),
+            PlacementConstraint::Any(constraints) => constraints.iter().any(|c| c.matches(labels)),
+            PlacementConstraint::Not(constraint) => !constraint.matches(labels),
+        }
+    }
+}
+
+/// Describes a single host in a lattice, as reported to schedulers and command-line tools
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HostInventory {
+    /// The unique ID (public key) of the host
+    pub host_id: String,
+    #[serde(default)]
+    pub labels: HostLabels,
+    /// Public keys of the actors currently running on the host
+    #[serde(default)]
+    pub actors: Vec<String>,
+    /// Capability IDs of the providers currently loaded on the host
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+/// A lattice control command requesting that an actor be started on a host whose labels satisfy
+/// all of the supplied constraints
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartActorCommand {
+    /// The OCI reference or public key of the actor to start
+    pub actor: String,
+    #[serde(default)]
+    pub constraints: Vec<PlacementConstraint>,
+}
+
+/// Computes the canonical digest of an invocation: the lowercase hex SHA-256 of the origin, target,
+/// operation and payload, each preceded by its length in bytes as a big-endian `u64`, so that
+/// independent host implementations compute identical digests. The digest is unkeyed, so it identifies
+/// an invocation's contents (e.g. for caching) but anyone can recompute it. Use `invocation_mac` to
+/// protect invocations in transit
+pub fn invocation_digest(origin: &str, target: &str, op: &str, payload: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    for part in invocation_parts(origin, target, op, payload).iter() {
+        hasher.update(&(part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hex(&hasher.finish())
+}
+
+/// Computes the authentication code hosts attach to invocations so that the receiving side can detect
+/// tampering in transit. It is the lowercase hex HMAC-SHA256, keyed with a secret shared by the hosts and
+/// providers of a lattice, of the same length-prefixed fields `invocation_digest` hashes. An invocation
+/// altered by anyone without the secret cannot be given a matching code
+pub fn invocation_mac(
+    secret: &[u8],
+    origin: &str,
+    target: &str,
+    op: &str,
+    payload: &[u8],
+) -> String {
+    let mut mac = HmacSha256::new(secret);
+    for part in invocation_parts(origin, target, op, payload).iter() {
+        mac.update(&(part.len() as u64).to_be_bytes());
+        mac.update(part);
+    }
+    hex(&mac.finish())
+}
+
+fn invocation_parts<'a>(
+    origin: &'a str,
+    target: &'a str,
+    op: &'a str,
+    payload: &'a [u8],
+) -> [&'a [u8]; 4] {
+    [origin.as_bytes(), target.as_bytes(), op.as_bytes(), payload]
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// An invocation as it travels between host and provider, carrying the authentication code computed by
+/// its sender. Receivers call `validate` with the lattice's shared secret before acting on it
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Invocation {
+    /// The actor public key, capability ID or `system` that sent the invocation
+    pub origin: String,
+    /// The actor public key or capability ID the invocation is addressed to
+    pub target: String,
+    pub operation: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub payload: Vec<u8>,
+    /// The `invocation_mac` of the other fields
+    pub digest: String,
+    /// Routes the invocation to an instance of a sharded provider. Routing metadata is not covered by
+    /// the digest, hosts may set or rewrite it in transit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<ShardHint>,
+}
+
+impl Invocation {
+    /// Creates an invocation, computing its authentication code under the given secret
+    pub fn new(
+        secret: &[u8],
+        origin: &str,
+        target: &str,
+        operation: &str,
+        payload: Vec<u8>,
+    ) -> Invocation {
+        Invocation {
+            digest: invocation_mac(secret, origin, target, operation, &payload),
+            origin: origin.to_string(),
+            target: target.to_string(),
+            operation: operation.to_string(),
+            payload,
+            shard: None,
+        }
+    }
+
+    pub fn with_shard_hint(self, shard: ShardHint) -> Invocation {
+        Invocation {
+            shard: Some(shard),
+            ..self
+        }
+    }
+
+    /// Verifies the invocation's authentication code against its contents and the given secret
+    pub fn validate(self, secret: &[u8]) -> Result<ValidatedInvocation, WasccCodecError> {
+        let expected = invocation_mac(
+            secret,
+            &self.origin,
+            &self.target,
+            &self.operation,
+            &self.payload,
+        );
+        // Compare without short-circuiting so the time taken does not reveal how much of a forged digest
+        // was correct
+        let matches = expected.len() == self.digest.len()
+            && expected
+                .bytes()
+                .zip(self.digest.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+        if matches {
+            Ok(ValidatedInvocation(self))
+        } else {
+            Err(WasccCodecError::InvocationDigestMismatch {
+                origin: self.origin,
+                target: self.target,
+                operation: self.operation,
+            })
+        }
+    }
+}
+
+/// The result of an invocation as it travels back to the invoker
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InvocationResponse {
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub payload: Vec<u8>,
+    /// Present if the invocation failed, in which case the payload is empty
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Present if the operation is cacheable (see `CacheControl`), describing how long the result stays
+    /// fresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<ResponseFreshness>,
+}
+
+impl InvocationResponse {
+    pub fn success(payload: Vec<u8>) -> InvocationResponse {
+        InvocationResponse {
+            payload,
+            ..Default::default()
+        }
+    }
+
+    pub fn error(msg: &str) -> InvocationResponse {
+        InvocationResponse {
+            error: Some(msg.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_freshness(self, freshness: ResponseFreshness) -> InvocationResponse {
+        InvocationResponse {
+            freshness: Some(freshness),
+            ..self
+        }
+    }
+
+    /// Indicates whether the response may be served from a cache at the given time. Errors are never
+    /// cached
+    pub fn is_cacheable_at(&self, now: Timestamp) -> bool {
+        self.error.is_none() && self.freshness.is_some_and(|f| f.is_fresh(now))
+    }
+}
+
+/// How long the result of a cacheable invocation stays fresh
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFreshness {
+    /// The time at which the provider produced the result
+    pub produced_at: Timestamp,
+    pub ttl: DurationMs,
+    /// Set by hosts when they answer an invocation from their cache
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+impl ResponseFreshness {
+    pub fn expires_at(&self) -> Timestamp {
+        self.produced_at
+            .checked_add(self.ttl)
+            .unwrap_or(Timestamp(u64::MAX))
+    }
+
+    pub fn is_fresh(&self, now: Timestamp) -> bool {
+        now < self.expires_at()
+    }
+
+    /// How much longer the result stays fresh
+    pub fn remaining(&self, now: Timestamp) -> DurationMs {
+        self.expires_at().duration_since(now).unwrap_or_default()
+    }
+}
+
+/// An invocation whose authentication code has been verified. It can only be obtained from `Invocation::validate`, so
+/// functions that take one need not check the digest again
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidatedInvocation(Invocation);
+
+impl ValidatedInvocation {
+    pub fn origin(&self) -> &str {
+        &self.0.origin
+    }
+
+    pub fn target(&self) -> &str {
+        &self.0.target
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.0.operation
+    }
+
+    pub fn payload(&self) -> &[u8] {
+        &self.0.payload
+    }
+
+    pub fn digest(&self) -> &str {
+        &self.0.digest
+    }
+
+    /// Releases the underlying invocation, e.g. to forward it
+    pub fn into_inner(self) -> Invocation {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        invocation_digest, invocation_mac, CapabilityConfiguration, DebugFlags, HostLabels,
+        InitializePayload, Invocation, InvocationContext, PlacementConstraint, ShardAssignment,
+        ShardHint, CAPABILITY_CONFIGURATION_SCHEMA_VERSION, LABEL_ARCH, LABEL_REGION,
+    };
+    use crate::errors::WasccCodecError;
+    use crate::Sample;
+    use std::collections::HashMap;
+
+    #[test]
+    fn placement_constraints_evaluate_against_labels() {
+        let mut labels = HostLabels::new();
+        labels.insert(LABEL_ARCH, "aarch64");
+        labels.insert(LABEL_REGION, "us-east-1");
+
+        let constraints = vec![
+            PlacementConstraint::Any(vec![
+                PlacementConstraint::equals(LABEL_ARCH, "x86_64"),
+                PlacementConstraint::equals(LABEL_ARCH, "aarch64"),
+            ]),
+            PlacementConstraint::In {
+                key: LABEL_REGION.to_string(),
+                values: vec!["us-east-1".to_string(), "us-west-2".to_string()],
+            },
+            PlacementConstraint::Not(Box::new(PlacementConstraint::Exists {
+                key: "gpu".to_string(),
+            })),
+        ];
+        assert!(labels.satisfies(&constraints));
+
+        labels.insert("gpu", "true");
+        assert!(!labels.satisfies(&constraints));
+
+        let buf = crate::serialize(&constraints).unwrap();
+        let decoded: Vec<PlacementConstraint> = crate::deserialize(&buf).unwrap();
+        assert_eq!(constraints, decoded);
+    }
+
+    #[test]
+    fn debug_flags_propagate_through_headers() {
+        assert!(!DebugFlags::sample(0.0, 0).sampled);
+        assert!(DebugFlags::sample(1.0, u32::MAX).sampled);
+        assert!(DebugFlags::sample(0.5, u32::MAX / 4).sampled);
+        assert!(!DebugFlags::sample(0.5, u32::MAX / 4 * 3).sampled);
+
+        let flags = DebugFlags {
+            capture_payloads: true,
+            ..DebugFlags::sample(1.0, 0)
+        };
+        let mut headers = HashMap::new();
+        flags.inject(&mut headers);
+        assert_eq!(headers["wascc-debug"], "sampled,verbose,capture-payloads");
+        assert_eq!(DebugFlags::extract(&headers), flags);
+
+        let mut headers = HashMap::new();
+        DebugFlags::default().inject(&mut headers);
+        assert!(headers.is_empty());
+        assert!(!DebugFlags::extract(&headers).is_active());
+    }
+
+    #[test]
+    fn invocation_context_propagates_through_headers() {
+        let context = InvocationContext {
+            trace_id: Some("4bf92f3577b34da6".to_string()),
+            debug: DebugFlags::sample(1.0, 0),
+        };
+        let mut headers = HashMap::new();
+        context.child().inject(&mut headers);
+        assert_eq!(headers["wascc-trace-id"], "4bf92f3577b34da6");
+        assert_eq!(InvocationContext::extract(&headers), context);
+
+        let mut headers = HashMap::new();
+        headers.insert("WASCC-Trace-Id".to_string(), "abc".to_string());
+        assert_eq!(
+            InvocationContext::extract(&headers).trace_id.as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            InvocationContext::extract(&HashMap::new()),
+            InvocationContext::default()
+        );
+    }
+
+    #[test]
+    fn invocation_mac_detects_tampering() {
+        assert_eq!(
+            invocation_digest("", "", "", &[]),
+            "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+        );
+        // Length prefixes keep field boundaries from shifting
+        assert_ne!(
+            invocation_digest("ab", "c", "Op", b""),
+            invocation_digest("a", "bc", "Op", b"")
+        );
+
+        let secret = b"lattice secret";
+        let inv = Invocation::new(secret, "MB4OLDIC", "wascc:keyvalue", "Get", b"key".to_vec());
+        assert_eq!(
+            inv.digest,
+            invocation_mac(secret, "MB4OLDIC", "wascc:keyvalue", "Get", b"key")
+        );
+        let buf = crate::serialize(&inv).unwrap();
+        let decoded: Invocation = crate::deserialize(&buf).unwrap();
+        let validated = decoded.clone().validate(secret).unwrap();
+        assert_eq!(validated.operation(), "Get");
+        assert_eq!(validated.payload(), b"key");
+        assert!(decoded.validate(b"other secret").is_err());
+
+        // Recomputing the public digest over altered contents does not forge a valid code
+        let mut forged = inv;
+        forged.payload = b"other".to_vec();
+        forged.digest = invocation_digest(&forged.origin, &forged.target, "Get", &forged.payload);
+        assert_eq!(
+            forged.validate(secret),
+            Err(WasccCodecError::InvocationDigestMismatch {
+                origin: "MB4OLDIC".to_string(),
+                target: "wascc:keyvalue".to_string(),
+                operation: "Get".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn secret_configuration_values_are_redacted() {
+        let mut config = CapabilityConfiguration::new("MB4OLDIC");
+        config
+            .values
+            .insert("url".to_string(), "redis://cache:6379".to_string());
+        config
+            .values
+            .insert("password".to_string(), "hunter2".to_string());
+        assert_eq!(config.secret("password"), None);
+
+        config.mark_secret("password").unwrap();
+        config.mark_secret("token").unwrap();
+        config.mark_secret("password").unwrap();
+        assert_eq!(
+            config.mark_secret("a,b"),
+            Err(WasccCodecError::InvalidKeyCharacter {
+                key: "a,b".to_string(),
+                character: ','
+            })
+        );
+        assert_eq!(
+            config.values[super::CONFIG_WASCC_SECRET_KEYS],
+            "password,token"
+        );
+        let secret = config.secret("password").unwrap();
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(
+            format!("{} {:?}", secret, secret),
+            "[REDACTED] SecretString([REDACTED])"
+        );
+        assert_eq!(config.secret("url"), None);
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("redis://cache:6379"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn configuration_without_schema_version_is_migrated() {
+        #[derive(Serialize)]
+        struct LegacyConfiguration {
+            module: String,
+            values: HashMap<String, String>,
+        }
+        let mut values = HashMap::new();
+        values.insert("url".to_string(), "nats://0.0.0.0:4222".to_string());
+        let buf = crate::serialize(LegacyConfiguration {
+            module: "MB4OLDIC".to_string(),
+            values: values.clone(),
+        })
+        .unwrap();
+        let config = CapabilityConfiguration::from_compat_bytes(&buf).unwrap();
+        assert_eq!(
+            config.schema_version,
+            CAPABILITY_CONFIGURATION_SCHEMA_VERSION
+        );
+        assert_eq!(config.values, values);
+        let strict = crate::deserialize_strict::<CapabilityConfiguration>(&buf).unwrap();
+        assert_eq!(strict.schema_version, 1);
+
+        let mut future = CapabilityConfiguration::new("MB4OLDIC");
+        future.schema_version = CAPABILITY_CONFIGURATION_SCHEMA_VERSION + 1;
+        let buf = crate::serialize(&future).unwrap();
+        assert!(CapabilityConfiguration::from_compat_bytes(&buf).is_err());
+        assert_eq!(
+            future.migrate(),
+            Err(WasccCodecError::UnsupportedSchemaVersion {
+                type_name: "CapabilityConfiguration".to_string(),
+                found: 2,
+                supported: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn shard_hints_route_consistently() {
+        let init = InitializePayload::sample().with_shard(ShardAssignment { index: 2, count: 8 });
+        let id = init.instance_id();
+        assert_eq!(id.host_id(), init.host_id);
+        assert_eq!(id.local_id(), "wascc:keyvalue/0");
+        assert_eq!(init.ack().shard, init.shard);
+
+        let shards: Vec<u32> = (0..1000)
+            .map(|i| {
+                ShardHint::Key(format!("tenant-{}", i))
+                    .shard_index(8)
+                    .unwrap()
+            })
+            .collect();
+        assert!(shards.iter().all(|s| *s < 8));
+        assert!((0..8).all(|s| shards.contains(&s)));
+        let hint = ShardHint::Key("tenant-42".to_string());
+        assert_eq!(hint.shard_index(8), Some(shards[42]));
+        // Growing from 8 to 9 shards only moves keys to the new shard
+        for (i, shard) in shards.iter().enumerate() {
+            let grown = ShardHint::Key(format!("tenant-{}", i)).shard_index(9);
+            assert!(grown == Some(*shard) || grown == Some(8));
+        }
+
+        let owner = ShardAssignment {
+            index: shards[42],
+            count: 8,
+        };
+        assert!(owner.owns(&hint));
+        assert!(!owner.owns(&ShardHint::Shard((shards[42] + 1) % 8)));
+        assert!(!owner.owns(&ShardHint::Instance(id.clone())));
+
+        let inv = Invocation::new(
+            b"secret",
+            "MB4OLDIC",
+            "wascc:keyvalue",
+            "Get",
+            b"key".to_vec(),
+        )
+        .with_shard_hint(ShardHint::Instance(id));
+        let buf = crate::serialize(&inv).unwrap();
+        let decoded: Invocation = crate::deserialize(&buf).unwrap();
+        assert_eq!(decoded, inv);
+        assert!(decoded.validate(b"secret").is_ok());
+    }
 }
@@ -0,0 +1,317 @@
+//! # Conflict-free Replicated Data Types
+//!
+//! This module contains data types for the `wascc:crdt` capability provider, which stores counters and
+//! sets that can be updated on any host of a lattice without coordination. Each host's provider applies
+//! updates locally and replicates its state to its peers, which merge it into their own. Merging is
+//! commutative, associative and idempotent, so all replicas converge regardless of the order in which
+//! states arrive
+
+use crate::errors::WasccCodecError;
+use crate::Sample;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+
+/// Guest sends a GCounterUpdate to the capability provider, receives the counter's CrdtValue back
+pub const OP_UPDATE_GCOUNTER: &str = "UpdateGCounter";
+/// Guest sends a PnCounterUpdate to the capability provider, receives the counter's CrdtValue back
+pub const OP_UPDATE_PNCOUNTER: &str = "UpdatePnCounter";
+/// Guest sends an OrSetOp to the capability provider, receives the set's CrdtValue back
+pub const OP_UPDATE_ORSET: &str = "UpdateOrSet";
+/// Guest sends a GetCrdtState to the capability provider, receives a StateSync back
+pub const OP_GET_STATE: &str = "GetCrdtState";
+/// Guest or peer provider sends a StateSync to the capability provider, receives the merged StateSync back
+pub const OP_MERGE_STATE: &str = "MergeCrdtState";
+
+/// Increments a grow-only counter, creating it if needed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GCounterUpdate {
+    pub key: String,
+    pub increment: u64,
+}
+
+impl Sample for GCounterUpdate {
+    fn sample() -> Self {
+        GCounterUpdate {
+            key: "page-views".to_string(),
+            increment: 1,
+        }
+    }
+}
+
+/// Adds a positive or negative amount to a counter, creating it if needed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PnCounterUpdate {
+    pub key: String,
+    pub delta: i64,
+}
+
+impl Sample for PnCounterUpdate {
+    fn sample() -> Self {
+        PnCounterUpdate {
+            key: "seats-available".to_string(),
+            delta: -2,
+        }
+    }
+}
+
+/// Adds an element to, or removes an element from, an observed-remove set, creating it if needed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrSetOp {
+    pub key: String,
+    pub action: OrSetAction,
+}
+
+impl Sample for OrSetOp {
+    fn sample() -> Self {
+        OrSetOp {
+            key: "online-users".to_string(),
+            action: OrSetAction::Add {
+                element: "alice".to_string(),
+            },
+        }
+    }
+}
+
+/// A change to an observed-remove set. A remove only affects the additions its replica has observed, so
+/// an element added concurrently on another replica survives the remove (add wins)
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum OrSetAction {
+    Add { element: String },
+    Remove { element: String },
+}
+
+/// The value of a CRDT, as seen by the replica that answered
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CrdtValue {
+    Counter(i64),
+    Set(BTreeSet<String>),
+}
+
+/// A request for the full replicated state of a CRDT
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCrdtState {
+    pub key: String,
+}
+
+/// The full state of a CRDT, exchanged between replicas. The receiver merges it into its own state
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSync {
+    pub key: String,
+    /// The ID of the replica (usually the host) the state comes from
+    pub replica_id: String,
+    pub state: CrdtState,
+}
+
+/// The replicated state of a CRDT
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CrdtState {
+    GCounter(GCounterState),
+    PnCounter(PnCounterState),
+    OrSet(OrSetState),
+}
+
+impl CrdtState {
+    /// The value the state represents
+    pub fn value(&self) -> CrdtValue {
+        match self {
+            CrdtState::GCounter(s) => {
+                CrdtValue::Counter(i64::try_from(s.value()).unwrap_or(i64::MAX))
+            }
+            CrdtState::PnCounter(s) => CrdtValue::Counter(s.value()),
+            CrdtState::OrSet(s) => CrdtValue::Set(s.elements()),
+        }
+    }
+
+    /// Merges another replica's state of the same CRDT into this one
+    pub fn merge(&mut self, other: &CrdtState) -> Result<(), WasccCodecError> {
+        match (self, other) {
+            (CrdtState::GCounter(a), CrdtState::GCounter(b)) => a.merge(b),
+            (CrdtState::PnCounter(a), CrdtState::PnCounter(b)) => a.merge(b),
+            (CrdtState::OrSet(a), CrdtState::OrSet(b)) => a.merge(b),
+            (a, b) => {
+                return Err(WasccCodecError::CrdtKindMismatch {
+                    expected: a.kind().to_string(),
+                    found: b.kind().to_string(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            CrdtState::GCounter(_) => "g_counter",
+            CrdtState::PnCounter(_) => "pn_counter",
+            CrdtState::OrSet(_) => "or_set",
+        }
+    }
+}
+
+/// A grow-only counter: the count contributed by each replica
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GCounterState {
+    #[serde(default)]
+    pub counts: HashMap<String, u64>,
+}
+
+impl GCounterState {
+    pub fn increment(&mut self, replica_id: &str, amount: u64) {
+        let count = self.counts.entry(replica_id.to_string()).or_insert(0);
+        *count = count.saturating_add(amount);
+    }
+
+    pub fn value(&self) -> u64 {
+        self.counts
+            .values()
+            .fold(0u64, |sum, c| sum.saturating_add(*c))
+    }
+
+    pub fn merge(&mut self, other: &GCounterState) {
+        for (replica, count) in &other.counts {
+            let mine = self.counts.entry(replica.to_string()).or_insert(0);
+            *mine = (*mine).max(*count);
+        }
+    }
+}
+
+/// A counter that can be incremented and decremented, kept as a pair of grow-only counters
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PnCounterState {
+    #[serde(default)]
+    pub increments: GCounterState,
+    #[serde(default)]
+    pub decrements: GCounterState,
+}
+
+impl PnCounterState {
+    pub fn apply(&mut self, replica_id: &str, delta: i64) {
+        if delta >= 0 {
+            self.increments.increment(replica_id, delta as u64);
+        } else {
+            self.decrements.increment(replica_id, delta.unsigned_abs());
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        (self.increments.value() as i128 - self.decrements.value() as i128)
+            .clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    pub fn merge(&mut self, other: &PnCounterState) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+}
+
+/// An observed-remove set. Every addition of an element is identified by a unique tag (e.g. the replica
+/// ID and a sequence number); removing an element tombstones the tags observed so far
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OrSetState {
+    /// The live addition tags of each element
+    #[serde(default)]
+    pub entries: HashMap<String, BTreeSet<String>>,
+    /// The tags of additions that have been removed
+    #[serde(default)]
+    pub tombstones: BTreeSet<String>,
+}
+
+impl OrSetState {
+    /// Adds an element under a tag that must not have been used before
+    pub fn add(&mut self, element: &str, tag: &str) {
+        if !self.tombstones.contains(tag) {
+            self.entries
+                .entry(element.to_string())
+                .or_default()
+                .insert(tag.to_string());
+        }
+    }
+
+    pub fn remove(&mut self, element: &str) {
+        if let Some(tags) = self.entries.remove(element) {
+            self.tombstones.extend(tags);
+        }
+    }
+
+    pub fn contains(&self, element: &str) -> bool {
+        self.entries.contains_key(element)
+    }
+
+    pub fn elements(&self) -> BTreeSet<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    pub fn merge(&mut self, other: &OrSetState) {
+        self.tombstones.extend(other.tombstones.iter().cloned());
+        for (element, tags) in &other.entries {
+            self.entries
+                .entry(element.to_string())
+                .or_default()
+                .extend(tags.iter().cloned());
+        }
+        let tombstones = &self.tombstones;
+        self.entries.retain(|_, tags| {
+            tags.retain(|t| !tombstones.contains(t));
+            !tags.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CrdtState, CrdtValue, GCounterState, OrSetState, PnCounterState};
+
+    #[test]
+    fn grow_only_counter_values_clamp_to_i64() {
+        let mut counter = GCounterState::default();
+        counter.increment("host-a", u64::MAX);
+        assert_eq!(
+            CrdtState::GCounter(counter).value(),
+            CrdtValue::Counter(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn replicas_converge_regardless_of_merge_order() {
+        let mut a = PnCounterState::default();
+        let mut b = PnCounterState::default();
+        a.apply("host-a", 5);
+        b.apply("host-b", -2);
+        let mut ab = a.clone();
+        ab.merge(&b);
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab, ba);
+        assert_eq!(ab.value(), 3);
+
+        let mut x = OrSetState::default();
+        x.add("alice", "host-a:1");
+        let mut y = x.clone();
+        y.remove("alice");
+        x.add("alice", "host-a:2");
+        let mut xy = CrdtState::OrSet(x.clone());
+        xy.merge(&CrdtState::OrSet(y.clone())).unwrap();
+        let mut yx = CrdtState::OrSet(y);
+        yx.merge(&CrdtState::OrSet(x)).unwrap();
+        assert_eq!(xy, yx);
+        assert_eq!(
+            xy.value(),
+            CrdtValue::Set(vec!["alice".to_string()].into_iter().collect())
+        );
+
+        assert!(xy
+            .merge(&CrdtState::GCounter(GCounterState::default()))
+            .is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! # Data Feeds
+//!
+//! This module contains data types for the `wascc:datafeed` capability provider, a common shape for
+//! providers that front external data APIs, e.g. weather, exchange rates or stock quotes. Actors query a
+//! named feed on demand or subscribe to have the provider poll it and push each new value
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a FeedQuery to the capability provider, receives a FeedResult back
+pub const OP_QUERY_FEED: &str = "QueryFeed";
+/// Guest sends a FeedSubscription to the capability provider, lack of error indicates success. The guest
+/// will then receive OP_DELIVER_FEED_UPDATE operations from the provider
+pub const OP_SUBSCRIBE_FEED: &str = "SubscribeFeed";
+/// Guest sends a FeedUnsubscribe to the capability provider, lack of error indicates success
+pub const OP_UNSUBSCRIBE_FEED: &str = "UnsubscribeFeed";
+/// Provider sends a FeedUpdate to the guest each time it polls a subscribed feed
+pub const OP_DELIVER_FEED_UPDATE: &str = "DeliverFeedUpdate";
+
+/// How fresh a feed value must be. Providers cache values and only call the external API when the
+/// cached value is too old
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Freshness {
+    /// The oldest acceptable value. None - any cached value will do
+    #[serde(default)]
+    pub max_age: Option<DurationMs>,
+    /// Return a value older than `max_age`, flagged as stale, if a fresh one cannot be fetched, rather
+    /// than failing
+    #[serde(default)]
+    pub allow_stale: bool,
+}
+
+/// A request for the current value of a feed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedQuery {
+    /// The name of the feed, as configured in the provider, e.g. `weather.current`
+    pub feed: String,
+    /// Feed-specific parameters, e.g. a location
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    #[serde(default)]
+    pub freshness: Freshness,
+}
+
+impl Sample for FeedQuery {
+    fn sample() -> Self {
+        let mut params = HashMap::new();
+        params.insert("lat".to_string(), "52.52".to_string());
+        params.insert("lon".to_string(), "13.40".to_string());
+        FeedQuery {
+            feed: "weather.current".to_string(),
+            params,
+            freshness: Freshness {
+                max_age: Some(DurationMs::from_secs(600)),
+                allow_stale: true,
+            },
+        }
+    }
+}
+
+impl FeedQuery {
+    pub fn new(feed: &str) -> FeedQuery {
+        FeedQuery {
+            feed: feed.to_string(),
+            params: HashMap::new(),
+            freshness: Freshness::default(),
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: &str) -> FeedQuery {
+        self.params.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_freshness(mut self, freshness: Freshness) -> FeedQuery {
+        self.freshness = freshness;
+        self
+    }
+}
+
+/// A value of a feed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedResult {
+    pub feed: String,
+    /// The encoded value, e.g. JSON text
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub value: Vec<u8>,
+    /// The MIME type of `value`, e.g. `application/json`
+    pub content_type: String,
+    /// The time at which the external source produced (or the provider fetched) the value
+    pub as_of: Timestamp,
+    /// Indicates the value is older than the query's freshness requirement, because a fresh value could
+    /// not be fetched
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl FeedResult {
+    /// The age of the value at the given time
+    pub fn age(&self, now: Timestamp) -> DurationMs {
+        now.duration_since(self.as_of).unwrap_or_default()
+    }
+}
+
+/// A request for the provider to poll a feed and push its values to the guest
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedSubscription {
+    /// An actor-chosen ID that identifies the subscription in updates
+    pub subscription_id: String,
+    pub feed: String,
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+    /// How often the provider polls the feed. Providers may enforce a minimum interval
+    pub interval: DurationMs,
+    /// Deliver an update on every poll, even if the value has not changed since the previous update
+    #[serde(default)]
+    pub deliver_unchanged: bool,
+}
+
+impl Sample for FeedSubscription {
+    fn sample() -> Self {
+        let mut params = HashMap::new();
+        params.insert("pair".to_string(), "EUR/USD".to_string());
+        FeedSubscription {
+            subscription_id: "fx-eurusd".to_string(),
+            feed: "fx.rate".to_string(),
+            params,
+            interval: DurationMs::from_secs(60),
+            deliver_unchanged: false,
+        }
+    }
+}
+
+/// Ends a feed subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedUnsubscribe {
+    pub subscription_id: String,
+}
+
+/// A value of a subscribed feed, pushed to the guest
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedUpdate {
+    pub subscription_id: String,
+    /// A sequence number that can be used for ordering logic, starting at 0
+    pub sequence_no: u64,
+    pub result: FeedResult,
+}
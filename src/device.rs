@@ -0,0 +1,128 @@
+//! # Embedded Device Access
+//!
+//! This module contains data types for the `wascc:device` capability provider, which gives actors
+//! running on edge devices access to GPIO pins and I2C/SPI buses
+
+use crate::Sample;
+
+/// Guest sends a ReadPinRequest to the capability provider, receives a PinState back
+pub const OP_READ_PIN: &str = "ReadPin";
+/// Guest sends a WritePinRequest to the capability provider, lack of error indicates success
+pub const OP_WRITE_PIN: &str = "WritePin";
+/// Guest sends an I2cTransfer to the capability provider, receives a TransferResponse back
+pub const OP_I2C_TRANSFER: &str = "I2cTransfer";
+/// Guest sends a SpiTransfer to the capability provider, receives a TransferResponse back
+pub const OP_SPI_TRANSFER: &str = "SpiTransfer";
+/// Guest sends an empty payload to the capability provider, receives a DeviceList back
+pub const OP_LIST_DEVICES: &str = "ListDevices";
+
+/// A request to read the current level of a GPIO pin
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadPinRequest {
+    /// The pin number, as numbered by the provider's GPIO chip
+    pub pin: u32,
+}
+
+/// The level of a GPIO pin
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinState {
+    pub pin: u32,
+    /// Indicates whether the pin is high (true) or low (false)
+    pub high: bool,
+}
+
+/// A request to drive a GPIO output pin high or low
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritePinRequest {
+    pub pin: u32,
+    /// Drive the pin high (true) or low (false)
+    pub high: bool,
+}
+
+/// A combined write-then-read transaction with a device on an I2C bus
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct I2cTransfer {
+    /// The bus number (e.g. 1 for `/dev/i2c-1`)
+    pub bus: u32,
+    /// The 7-bit address of the target device
+    pub address: u16,
+    /// Bytes to write to the device. May be empty for a read-only transfer
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub write: Vec<u8>,
+    /// The number of bytes to read back after writing. May be 0 for a write-only transfer
+    pub read_len: u32,
+}
+
+impl Sample for I2cTransfer {
+    fn sample() -> Self {
+        I2cTransfer {
+            bus: 1,
+            address: 0x76,
+            write: vec![0xD0],
+            read_len: 1,
+        }
+    }
+}
+
+/// A full-duplex transfer with a device on an SPI bus. The number of bytes read back
+/// is always equal to the number of bytes written
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpiTransfer {
+    /// The bus number (e.g. 0 for `/dev/spidev0.x`)
+    pub bus: u32,
+    /// The chip select line of the target device
+    pub chip_select: u32,
+    /// Clock speed for the transfer in hertz, 0 - provider default
+    pub clock_hz: u32,
+    /// Bytes to clock out to the device
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub write: Vec<u8>,
+}
+
+/// The bytes read back from an I2C or SPI transfer
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferResponse {
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub read: Vec<u8>,
+}
+
+/// The kind of hardware interface exposed by a device
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Gpio,
+    I2c,
+    Spi,
+}
+
+/// Describes a single hardware interface available to actors
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInfo {
+    /// The kind of interface
+    pub kind: DeviceKind,
+    /// The GPIO chip or bus number
+    pub bus: u32,
+    /// A human-friendly description of the device, e.g. `bcm2835 gpio`
+    pub description: String,
+    /// The pins (for GPIO) or chip selects (for SPI) available on this device
+    #[serde(default)]
+    pub lines: Vec<u32>,
+}
+
+/// The hardware interfaces the provider exposes to actors
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceList {
+    #[serde(default)]
+    pub devices: Vec<DeviceInfo>,
+}
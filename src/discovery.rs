@@ -0,0 +1,114 @@
+//! # Service Discovery
+//!
+//! This module contains data types for the `wascc:servicediscovery` capability provider. Providers
+//! backed by registries such as Consul or etcd allow actors to register the services they expose and
+//! to look up the endpoints of sibling services
+
+use crate::core::DurationMs;
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a RegisterService to the capability provider, lack of error indicates success
+pub const OP_REGISTER_SERVICE: &str = "RegisterService";
+/// Guest sends a DeregisterService to the capability provider, lack of error indicates success
+pub const OP_DEREGISTER_SERVICE: &str = "DeregisterService";
+/// Guest sends a QueryService to the capability provider, receives ServiceInstances back
+pub const OP_QUERY_SERVICE: &str = "QueryService";
+
+/// Registers an instance of a service with the registry
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterService {
+    /// The logical name of the service, shared by all of its instances
+    pub name: String,
+    /// Uniquely identifies this instance of the service. Registering an existing instance ID replaces it
+    pub instance_id: String,
+    /// The address at which the instance can be reached, e.g. `10.0.0.12:8080` or a URL
+    pub address: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// The instance is removed from the registry if it is not re-registered within this time. If
+    /// `None`, the instance remains registered until it is explicitly deregistered
+    #[serde(default)]
+    pub ttl: Option<DurationMs>,
+}
+
+impl Sample for RegisterService {
+    fn sample() -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("version".to_string(), "1.4.2".to_string());
+        RegisterService {
+            name: "inventory".to_string(),
+            instance_id: "inventory-7f3a".to_string(),
+            address: "10.0.0.12:8080".to_string(),
+            tags: vec!["primary".to_string(), "us-east-1".to_string()],
+            metadata,
+            ttl: Some(DurationMs(30_000)),
+        }
+    }
+}
+
+/// Removes an instance of a service from the registry
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeregisterService {
+    pub name: String,
+    pub instance_id: String,
+}
+
+/// A query for the instances of a service
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryService {
+    pub name: String,
+    /// Only instances carrying all of these tags are returned
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only return instances whose health status is `Passing`
+    #[serde(default)]
+    pub healthy_only: bool,
+}
+
+/// The health of a registered service instance, as reported by the registry
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Passing,
+    Warning,
+    Critical,
+    /// The registry has no health information for the instance
+    Unknown,
+}
+
+/// A single registered instance of a service
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceInstance {
+    pub instance_id: String,
+    pub address: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    pub health: HealthStatus,
+}
+
+/// The instances of a service matching a query
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceInstances {
+    pub name: String,
+    #[serde(default)]
+    pub instances: Vec<ServiceInstance>,
+}
+
+impl ServiceInstances {
+    /// Returns the instances whose health status is `Passing`
+    pub fn healthy(&self) -> impl Iterator<Item = &ServiceInstance> {
+        self.instances
+            .iter()
+            .filter(|i| i.health == HealthStatus::Passing)
+    }
+}
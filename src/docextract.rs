@@ -0,0 +1,118 @@
+//! # Document Text Extraction
+//!
+//! This module contains data types for the `wascc:docextract` capability provider, which extracts text
+//! and layout from documents and images using OCR engines or document parsers such as Apache Tika
+
+use crate::blobstore::BlobReference;
+use crate::Sample;
+
+/// Guest sends an ExtractTextRequest to the capability provider, receives an ExtractedDocument back
+pub const OP_EXTRACT_TEXT: &str = "ExtractText";
+
+/// A request to extract the text from a document. The document is either supplied inline in `document`,
+/// or, for documents too large to send inline, stored in a blob store and referenced by `blob`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractTextRequest {
+    /// The raw bytes of the document
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub document: Vec<u8>,
+    /// A blob containing the document
+    #[serde(default)]
+    pub blob: Option<BlobReference>,
+    /// The MIME type of the document (e.g. `application/pdf`, `image/png`). Empty - let the provider detect it
+    #[serde(default)]
+    pub format_hint: String,
+    /// ISO 639-1 codes of the languages expected in the document, in order of preference
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Include per-block layout information (bounding boxes) in the result
+    pub include_layout: bool,
+}
+
+impl Sample for ExtractTextRequest {
+    fn sample() -> Self {
+        ExtractTextRequest {
+            document: vec![],
+            blob: Some(BlobReference {
+                container: "scans".to_string(),
+                id: "invoice-42.png".to_string(),
+            }),
+            format_hint: "image/png".to_string(),
+            languages: vec!["en".to_string()],
+            include_layout: true,
+        }
+    }
+}
+
+/// A rectangle on a page, in coordinates normalized to the page's dimensions (0.0 to 1.0), with the origin
+/// at the top left corner of the page
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundingBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// The granularity of a block of extracted text
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Paragraph,
+    Line,
+    Word,
+    TableCell,
+}
+
+/// A block of text recognized on a page
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TextBlock {
+    pub kind: BlockKind,
+    pub text: String,
+    #[serde(default)]
+    pub bounding_box: Option<BoundingBox>,
+    /// The provider's confidence in the recognized text, from 0.0 to 1.0. Text extracted directly from
+    /// a digital document (rather than recognized) has a confidence of 1.0
+    pub confidence: f32,
+}
+
+/// A single page of an extracted document
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Page {
+    /// The page number, starting at 1
+    pub number: u32,
+    /// The full text of the page
+    pub text: String,
+    /// Blocks of text with layout information. Empty unless layout was requested
+    #[serde(default)]
+    pub blocks: Vec<TextBlock>,
+}
+
+/// The text extracted from a document
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedDocument {
+    /// The detected MIME type of the document
+    pub content_type: String,
+    /// The ISO 639-1 code of the document's detected primary language, if known
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub pages: Vec<Page>,
+}
+
+impl ExtractedDocument {
+    /// The full text of the document, with pages separated by form feeds
+    pub fn text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\x0c")
+    }
+}
@@ -0,0 +1,101 @@
+//! # Document Generation
+//!
+//! This module contains data types for the `wascc:docgen` capability provider, which renders
+//! templates with data into PDF, DOCX or HTML documents, e.g. invoices and reports. Small documents
+//! are returned inline, while larger ones can be written directly to a blob store
+
+use crate::blobstore::BlobReference;
+use crate::Sample;
+
+/// Guest sends a GenerateDocumentRequest to the capability provider, receives a GeneratedDocument back
+pub const OP_GENERATE_DOCUMENT: &str = "GenerateDocument";
+
+/// The template a document is rendered from
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateSource {
+    /// A template registered with the provider, by name. If no version is given the latest is used
+    Named {
+        name: String,
+        #[serde(default)]
+        version: Option<String>,
+    },
+    /// A template supplied with the request, in the provider's template language
+    Inline(String),
+    /// A template stored in a blob store
+    Blob(BlobReference),
+}
+
+/// The format of a generated document
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    Pdf,
+    Docx,
+    Html,
+}
+
+impl OutputFormat {
+    /// The MIME type of documents in this format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Pdf => "application/pdf",
+            OutputFormat::Docx => {
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            }
+            OutputFormat::Html => "text/html",
+        }
+    }
+}
+
+/// A request to render a template into a document
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateDocumentRequest {
+    pub template: TemplateSource,
+    /// The data the template is rendered with, as JSON text
+    pub data: String,
+    pub format: OutputFormat,
+    /// A blob to which the document is written instead of being returned inline
+    #[serde(default)]
+    pub destination: Option<BlobReference>,
+    /// The locale used to format dates and numbers in the document (e.g. `en-US`). Empty - use the
+    /// provider's default
+    #[serde(default)]
+    pub locale: String,
+}
+
+impl Sample for GenerateDocumentRequest {
+    fn sample() -> Self {
+        GenerateDocumentRequest {
+            template: TemplateSource::Named {
+                name: "invoice".to_string(),
+                version: Some("3".to_string()),
+            },
+            data: r#"{"invoiceNo":"INV-1042","customer":"ACME Corp","total":"129.95"}"#.to_string(),
+            format: OutputFormat::Pdf,
+            destination: Some(BlobReference::new("invoices", "INV-1042.pdf")),
+            locale: "en-US".to_string(),
+        }
+    }
+}
+
+/// A generated document. The document is either contained inline in `document`, or, if the request
+/// named a destination, stored in a blob store and referenced by `blob`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedDocument {
+    pub format: OutputFormat,
+    /// The raw bytes of the document
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub document: Vec<u8>,
+    /// The blob containing the document
+    #[serde(default)]
+    pub blob: Option<BlobReference>,
+    /// The size of the document in bytes, whether inline or stored
+    pub byte_size: u64,
+    /// The number of pages in the document. Not reported for HTML documents
+    #[serde(default)]
+    pub page_count: Option<u32>,
+}
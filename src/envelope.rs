@@ -0,0 +1,316 @@
+//! # Payload Encryption
+//!
+//! This module contains an envelope for invocation payloads that must remain opaque to the host
+//! dispatching them, such as in multi-tenant hosts. An actor and a provider first agree on a key by
+//! exchanging `KeyAgreementRequest` and `KeyAgreementResponse` (with `OP_AGREE_PAYLOAD_KEY`), each
+//! contributing an X25519 public key. Each side computes the X25519 shared secret from its own private
+//! key and the other side's public key, and passes it to `derive_payload_key`, which applies
+//! HKDF-SHA256 with the agreement's key ID as info. Both sides thereby hold the same symmetric key, and
+//! from then on send `EncryptedPayload`s in place of plaintext payloads over the normal dispatch path.
+//!
+//! Key derivation, and sealing and opening envelopes with ChaCha20-Poly1305, require the `encryption`
+//! feature. The codec never gathers randomness itself, so that it builds for targets without an
+//! entropy source: callers supply either a random number generator or the nonce
+
+use crate::Sample;
+
+/// Actor sends a KeyAgreementRequest to a provider, receives a KeyAgreementResponse back
+pub const OP_AGREE_PAYLOAD_KEY: &str = "AgreePayloadKey";
+
+/// The AEAD algorithm used by `EncryptedPayload::seal`
+pub const ALG_CHACHA20_POLY1305: &str = "chacha20-poly1305";
+/// The key agreement algorithm used to establish payload keys
+pub const ALG_X25519: &str = "x25519";
+
+/// An encrypted invocation payload
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedPayload {
+    /// Identifies the key the payload was sealed with, as established by key agreement
+    pub key_id: String,
+    /// The AEAD algorithm the payload was sealed with
+    pub algorithm: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub nonce: Vec<u8>,
+    /// The sealed payload, including the authentication tag
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub ciphertext: Vec<u8>,
+    /// Additional data authenticated with, but not encrypted in, the payload, e.g. the operation name.
+    /// Carried in the clear
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub aad: Vec<u8>,
+}
+
+/// Proposes a payload key, carrying the initiator's ephemeral public key
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyAgreementRequest {
+    /// An initiator-chosen ID for the resulting key, referenced by each `EncryptedPayload`
+    pub key_id: String,
+    /// The key agreement algorithm, `ALG_X25519`
+    pub algorithm: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub public_key: Vec<u8>,
+    /// The AEAD algorithms the initiator supports, most preferred first
+    #[serde(default)]
+    pub aead_algorithms: Vec<String>,
+}
+
+impl Sample for KeyAgreementRequest {
+    fn sample() -> Self {
+        KeyAgreementRequest {
+            key_id: "k-2020-07-01".to_string(),
+            algorithm: ALG_X25519.to_string(),
+            public_key: vec![0x09; 32],
+            aead_algorithms: vec![ALG_CHACHA20_POLY1305.to_string()],
+        }
+    }
+}
+
+/// Accepts a proposed payload key, carrying the responder's ephemeral public key
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyAgreementResponse {
+    pub key_id: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub public_key: Vec<u8>,
+    /// The AEAD algorithm chosen from the initiator's list
+    pub aead_algorithm: String,
+}
+
+#[cfg(feature = "encryption")]
+mod aead {
+    use super::{EncryptedPayload, ALG_CHACHA20_POLY1305};
+    use crate::errors::WasccCodecError;
+    use crate::sha256::HmacSha256;
+    use chacha20poly1305::aead::rand_core::{CryptoRng, RngCore};
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, Payload};
+    use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    /// Derives the symmetric payload key from the X25519 shared secret of a key agreement, using
+    /// HKDF-SHA256 (RFC 5869) with an empty salt and the agreement's `key_id` as info
+    pub fn derive_payload_key(shared_secret: &[u8; 32], key_id: &str) -> [u8; 32] {
+        hkdf_sha256(&[], shared_secret, key_id.as_bytes())
+    }
+
+    // HKDF extract and expand, limited to a single block of output
+    fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8]) -> [u8; 32] {
+        let mut extract = HmacSha256::new(salt);
+        extract.update(ikm);
+        let prk = extract.finish();
+        let mut expand = HmacSha256::new(&prk);
+        expand.update(info);
+        expand.update(&[1]);
+        expand.finish()
+    }
+
+    impl EncryptedPayload {
+        /// Encrypts a payload with a 256-bit key, using a nonce drawn from the supplied random number
+        /// generator
+        pub fn seal<R: RngCore + CryptoRng>(
+            rng: &mut R,
+            key: &[u8; 32],
+            key_id: &str,
+            plaintext: &[u8],
+            aad: &[u8],
+        ) -> Result<EncryptedPayload, WasccCodecError> {
+            let nonce = ChaCha20Poly1305::generate_nonce(rng);
+            EncryptedPayload::seal_with_nonce(key, key_id, nonce.as_ref(), plaintext, aad)
+        }
+
+        /// Encrypts a payload with a 256-bit key and a caller-chosen nonce. A nonce must never be used
+        /// twice with the same key, e.g. callers may use a per-key message counter
+        pub fn seal_with_nonce(
+            key: &[u8; 32],
+            key_id: &str,
+            nonce: &[u8; 12],
+            plaintext: &[u8],
+            aad: &[u8],
+        ) -> Result<EncryptedPayload, WasccCodecError> {
+            let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+            let ciphertext = cipher
+                .encrypt(
+                    Nonce::from_slice(nonce),
+                    Payload {
+                        msg: plaintext,
+                        aad,
+                    },
+                )
+                .map_err(|_| failed(key_id, "encryption failed"))?;
+            Ok(EncryptedPayload {
+                key_id: key_id.to_string(),
+                algorithm: ALG_CHACHA20_POLY1305.to_string(),
+                nonce: nonce.to_vec(),
+                ciphertext,
+                aad: aad.to_vec(),
+            })
+        }
+
+        /// Decrypts the payload, verifying that neither it nor its additional data were tampered with
+        pub fn open(&self, key: &[u8; 32]) -> Result<Vec<u8>, WasccCodecError> {
+            if self.algorithm != ALG_CHACHA20_POLY1305 {
+                return Err(failed(&self.key_id, "unsupported algorithm"));
+            }
+            if self.nonce.len() != 12 {
+                return Err(failed(&self.key_id, "invalid nonce length"));
+            }
+            ChaCha20Poly1305::new(Key::from_slice(key))
+                .decrypt(
+                    Nonce::from_slice(&self.nonce),
+                    Payload {
+                        msg: &self.ciphertext,
+                        aad: &self.aad,
+                    },
+                )
+                .map_err(|_| failed(&self.key_id, "authentication failed"))
+        }
+
+        /// Serializes a value with the codec's standard serialization and seals it
+        pub fn seal_value<T: Serialize, R: RngCore + CryptoRng>(
+            rng: &mut R,
+            key: &[u8; 32],
+            key_id: &str,
+            value: &T,
+            aad: &[u8],
+        ) -> Result<EncryptedPayload, WasccCodecError> {
+            let plaintext = crate::serialize(value).map_err(|e| failed(key_id, &e.to_string()))?;
+            EncryptedPayload::seal(rng, key, key_id, &plaintext, aad)
+        }
+
+        /// Opens the payload and deserializes the value within it
+        pub fn open_value<T: DeserializeOwned>(
+            &self,
+            key: &[u8; 32],
+        ) -> Result<T, WasccCodecError> {
+            crate::deserialize(&self.open(key)?).map_err(|e| WasccCodecError::MalformedPayload {
+                reason: e.to_string(),
+            })
+        }
+    }
+
+    fn failed(key_id: &str, reason: &str) -> WasccCodecError {
+        WasccCodecError::EncryptionFailed {
+            key_id: key_id.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::{derive_payload_key, hkdf_sha256, EncryptedPayload};
+        use crate::errors::WasccCodecError;
+        use crate::keyvalue::GetRequest;
+        use chacha20poly1305::aead::rand_core::{self, CryptoRng, RngCore};
+
+        // Deterministic stand-in for a system random number generator
+        struct CountingRng(u8);
+
+        impl RngCore for CountingRng {
+            fn next_u32(&mut self) -> u32 {
+                rand_core::impls::next_u32_via_fill(self)
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                rand_core::impls::next_u64_via_fill(self)
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                for b in dest.iter_mut() {
+                    self.0 = self.0.wrapping_add(1);
+                    *b = self.0;
+                }
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl CryptoRng for CountingRng {}
+
+        #[test]
+        fn hkdf_matches_rfc_5869_vectors() {
+            let hex = |bytes: [u8; 32]| -> String {
+                bytes.iter().map(|b| format!("{:02x}", b)).collect()
+            };
+            // Test case 1, truncated to the first 32 bytes of output
+            let salt: Vec<u8> = (0x00..=0x0c).collect();
+            let info: Vec<u8> = (0xf0..=0xf9).collect();
+            assert_eq!(
+                hex(hkdf_sha256(&salt, &[0x0b; 22], &info)),
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+            );
+            // Test case 3, with an empty salt and info
+            assert_eq!(
+                hex(hkdf_sha256(&[], &[0x0b; 22], &[])),
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d"
+            );
+
+            let secret = [9u8; 32];
+            assert_eq!(
+                derive_payload_key(&secret, "k1"),
+                derive_payload_key(&secret, "k1")
+            );
+            assert_ne!(
+                derive_payload_key(&secret, "k1"),
+                derive_payload_key(&secret, "k2")
+            );
+        }
+
+        #[test]
+        fn nonces_come_from_the_callers_generator() {
+            let key = [7u8; 32];
+            let mut rng = CountingRng(0);
+            let first = EncryptedPayload::seal(&mut rng, &key, "k1", b"a", b"").unwrap();
+            let second = EncryptedPayload::seal(&mut rng, &key, "k1", b"a", b"").unwrap();
+            assert_eq!(first.nonce, (1..=12).collect::<Vec<u8>>());
+            assert_ne!(first.nonce, second.nonce);
+
+            let fixed = EncryptedPayload::seal_with_nonce(&key, "k1", &[3; 12], b"a", b"").unwrap();
+            assert_eq!(fixed.nonce, vec![3; 12]);
+            assert_eq!(fixed.open(&key).unwrap(), b"a");
+        }
+
+        #[test]
+        fn sealed_payloads_open_only_with_the_right_key_and_aad() {
+            let key = [7u8; 32];
+            let sealed = EncryptedPayload::seal_value(
+                &mut CountingRng(0),
+                &key,
+                "k1",
+                &GetRequest::new("secret"),
+                b"Get",
+            )
+            .unwrap();
+            assert_ne!(
+                sealed.ciphertext,
+                crate::serialize(GetRequest::new("secret")).unwrap()
+            );
+            let opened: GetRequest = sealed.open_value(&key).unwrap();
+            assert_eq!(opened, GetRequest::new("secret"));
+
+            assert!(matches!(
+                sealed.open(&[8u8; 32]),
+                Err(WasccCodecError::EncryptionFailed { .. })
+            ));
+            let tampered = EncryptedPayload {
+                aad: b"Set".to_vec(),
+                ..sealed
+            };
+            assert!(tampered.open(&key).is_err());
+        }
+    }
+}
+
+#[cfg(feature = "encryption")]
+pub use aead::derive_payload_key;
+#[cfg(feature = "encryption")]
+pub use chacha20poly1305::aead::rand_core::{CryptoRng, RngCore};
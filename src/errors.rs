@@ -0,0 +1,291 @@
+//! # Error types
+//!
+//! This module contains the typed errors produced by the codec's validation helpers. They can be
+//! returned anywhere a `Box<dyn Error>` is expected, such as from `CapabilityProvider::handle_call`
+
+use crate::capabilities::OperationDirection;
+use std::error::Error;
+use std::fmt;
+
+/// An error produced by the codec's validation and helper functions
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasccCodecError {
+    /// The operation is not declared in the capability provider's descriptor
+    UnknownOperation {
+        capability: String,
+        operation: String,
+    },
+    /// The operation was invoked in a direction its descriptor does not permit
+    InvalidDirection {
+        capability: String,
+        operation: String,
+        declared: OperationDirection,
+        attempted: OperationDirection,
+    },
+    /// A key-value store key was empty
+    EmptyKey,
+    /// A key-value store key exceeded the provider's maximum key length
+    KeyTooLong { length: usize, max: usize },
+    /// A key contained a character not permitted, e.g. by a key-value provider or in a configuration's
+    /// list of secret keys
+    InvalidKeyCharacter { key: String, character: char },
+    /// A key-value store value exceeded the provider's maximum value size
+    ValueTooLarge { length: u64, max: u64 },
+    /// A capability ID was not of the form `namespace:name`
+    InvalidCapabilityId { id: String, reason: String },
+    /// A provider does not satisfy the operations or revision an actor requires
+    IncompatibleContract { capability: String, details: String },
+    /// A serialized payload exceeded the size permitted by the host or provider
+    PayloadTooLarge { actual: u64, allowed: u64 },
+    /// A broker message could not be interpreted as a CloudEvent
+    InvalidCloudEvent { reason: String },
+    /// A strictly deserialized payload contained a field not defined by the target type
+    UnknownField { path: String },
+    /// A strictly deserialized payload omitted a field defined by the target type
+    MissingField { path: String },
+    /// A payload could not be decoded into the target type
+    MalformedPayload { reason: String },
+    /// The host could not dispatch an invocation to its target actor or provider
+    BadDispatch { target: String, reason: String },
+    /// A capability provider refused to perform an operation, e.g. because the actor's configuration
+    /// does not permit it
+    ProviderOperationRejected {
+        capability: String,
+        operation: String,
+        reason: String,
+    },
+    /// A capability provider plugin was built against an incompatible plugin layout
+    IncompatibleProviderAbi { expected: u32, found: u32 },
+    /// A capability provider plugin's constructor returned a null pointer
+    NullProviderPlugin,
+    /// The credentials supplied with a request could not be parsed
+    MalformedCredentials { scheme: String, reason: String },
+    /// A percent-encoded value could not be decoded
+    InvalidPercentEncoding { value: String, reason: String },
+    /// An encrypted payload could not be sealed or opened with the given key
+    EncryptionFailed { key_id: String, reason: String },
+    /// A key-value store expiration would expire the key immediately or could not be interpreted
+    InvalidExpiration { reason: String },
+    /// An invocation's digest does not match its contents, i.e. it was altered after the digest was computed
+    InvocationDigestMismatch {
+        origin: String,
+        target: String,
+        operation: String,
+    },
+    /// Two CRDT states of different kinds cannot be merged
+    CrdtKindMismatch { expected: String, found: String },
+    /// A signed message's signature could not be verified
+    InvalidSignature { signer: String, reason: String },
+    /// A payload was written in a version of a type's wire format this codec does not support
+    UnsupportedSchemaVersion {
+        type_name: String,
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for WasccCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WasccCodecError::UnknownOperation {
+                capability,
+                operation,
+            } => write!(
+                f,
+                "Operation '{}' is not supported by capability '{}'",
+                operation, capability
+            ),
+            WasccCodecError::InvalidDirection {
+                capability,
+                operation,
+                declared,
+                attempted,
+            } => write!(
+                f,
+                "Operation '{}' on capability '{}' is declared {:?} but was invoked {:?}",
+                operation, capability, declared, attempted
+            ),
+            WasccCodecError::EmptyKey => write!(f, "Key must not be empty"),
+            WasccCodecError::KeyTooLong { length, max } => write!(
+                f,
+                "Key is {} bytes long, exceeding the maximum of {} bytes",
+                length, max
+            ),
+            WasccCodecError::InvalidKeyCharacter { key, character } => write!(
+                f,
+                "Key '{}' contains the disallowed character {:?}",
+                key, character
+            ),
+            WasccCodecError::ValueTooLarge { length, max } => write!(
+                f,
+                "Value is {} bytes long, exceeding the maximum of {} bytes",
+                length, max
+            ),
+            WasccCodecError::InvalidCapabilityId { id, reason } => {
+                write!(f, "Invalid capability ID '{}': {}", id, reason)
+            }
+            WasccCodecError::IncompatibleContract {
+                capability,
+                details,
+            } => write!(
+                f,
+                "Provider for capability '{}' is incompatible: {}",
+                capability, details
+            ),
+            WasccCodecError::PayloadTooLarge { actual, allowed } => write!(
+                f,
+                "Payload is {} bytes, exceeding the allowed size of {} bytes",
+                actual, allowed
+            ),
+            WasccCodecError::InvalidCloudEvent { reason } => {
+                write!(f, "Invalid CloudEvent: {}", reason)
+            }
+            WasccCodecError::UnknownField { path } => write!(f, "Unknown field '{}'", path),
+            WasccCodecError::MissingField { path } => write!(f, "Missing field '{}'", path),
+            WasccCodecError::MalformedPayload { reason } => {
+                write!(f, "Malformed payload: {}", reason)
+            }
+            WasccCodecError::BadDispatch { target, reason } => {
+                write!(f, "Failed to dispatch to '{}': {}", target, reason)
+            }
+            WasccCodecError::ProviderOperationRejected {
+                capability,
+                operation,
+                reason,
+            } => write!(
+                f,
+                "Capability '{}' rejected operation '{}': {}",
+                capability, operation, reason
+            ),
+            WasccCodecError::IncompatibleProviderAbi { expected, found } => write!(
+                f,
+                "Provider plugin uses ABI version {} but the host requires version {}",
+                found, expected
+            ),
+            WasccCodecError::NullProviderPlugin => {
+                write!(f, "Provider plugin constructor returned a null pointer")
+            }
+            WasccCodecError::MalformedCredentials { scheme, reason } => {
+                write!(f, "Malformed {} credentials: {}", scheme, reason)
+            }
+            WasccCodecError::InvalidPercentEncoding { value, reason } => {
+                write!(f, "Invalid percent-encoding in '{}': {}", value, reason)
+            }
+            WasccCodecError::EncryptionFailed { key_id, reason } => {
+                write!(f, "Encryption with key '{}' failed: {}", key_id, reason)
+            }
+            WasccCodecError::InvalidExpiration { reason } => {
+                write!(f, "Invalid expiration: {}", reason)
+            }
+            WasccCodecError::InvocationDigestMismatch {
+                origin,
+                target,
+                operation,
+            } => write!(
+                f,
+                "Digest of invocation '{}' from '{}' to '{}' does not match its contents",
+                operation, origin, target
+            ),
+            WasccCodecError::CrdtKindMismatch { expected, found } => write!(
+                f,
+                "Cannot merge a {} CRDT state into a {} CRDT state",
+                found, expected
+            ),
+            WasccCodecError::InvalidSignature { signer, reason } => {
+                write!(f, "Invalid signature by '{}': {}", signer, reason)
+            }
+            WasccCodecError::UnsupportedSchemaVersion {
+                type_name,
+                found,
+                supported,
+            } => write!(
+                f,
+                "{} uses schema version {}, but only versions up to {} are supported",
+                type_name, found, supported
+            ),
+        }
+    }
+}
+
+impl Error for WasccCodecError {}
+
+/// Recognizes a codec error within an error returned from `Dispatcher::dispatch`,
+/// `CapabilityProvider::handle_call`, or any other function returning `Box<dyn Error>`. The error's
+/// chain of sources is searched, so codec errors wrapped by other error types are also found
+pub fn as_wascc_error<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a WasccCodecError> {
+    let mut current = Some(err);
+    while let Some(e) = current {
+        if let Some(codec_err) = e.downcast_ref::<WasccCodecError>() {
+            return Some(codec_err);
+        }
+        current = e.source();
+    }
+    None
+}
+
+/// Tests whether an error (a `Box<dyn Error>` or `&dyn Error`) is, or wraps, a codec error matching
+/// one or more patterns, e.g.
+///
+/// ```
+/// # use wascc_codec::errors::WasccCodecError;
+/// # use wascc_codec::is_wascc_error;
+/// let err: Box<dyn std::error::Error> = Box::new(WasccCodecError::EmptyKey);
+/// assert!(is_wascc_error!(err, WasccCodecError::EmptyKey | WasccCodecError::KeyTooLong { .. }));
+/// assert!(!is_wascc_error!(err, WasccCodecError::BadDispatch { .. }));
+/// ```
+#[macro_export]
+macro_rules! is_wascc_error {
+    ($err:expr, $($pattern:pat)|+) => {
+        matches!(
+            $crate::errors::as_wascc_error(&*$err),
+            Some($($pattern)|+)
+        )
+    };
+    ($err:expr, $($pattern:pat)|+ if $guard:expr) => {
+        matches!(
+            $crate::errors::as_wascc_error(&*$err),
+            Some($($pattern)|+) if $guard
+        )
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::{as_wascc_error, WasccCodecError};
+    use std::error::Error;
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct Wrapper(WasccCodecError);
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "wrapped: {}", self.0)
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn recognizes_wrapped_codec_errors() {
+        let rejected = WasccCodecError::ProviderOperationRejected {
+            capability: "wascc:keyvalue".to_string(),
+            operation: "Set".to_string(),
+            reason: "read-only binding".to_string(),
+        };
+        let err: Box<dyn Error> = Box::new(Wrapper(rejected.clone()));
+        assert_eq!(as_wascc_error(&*err), Some(&rejected));
+        assert!(is_wascc_error!(
+            err,
+            WasccCodecError::ProviderOperationRejected { operation, .. } if operation == "Set"
+        ));
+        assert!(!is_wascc_error!(err, WasccCodecError::BadDispatch { .. }));
+
+        let other: Box<dyn Error> = "plain string error".into();
+        assert_eq!(as_wascc_error(&*other), None);
+    }
+}
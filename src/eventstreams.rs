@@ -4,7 +4,10 @@
 //! For more information on append-only event streams, event sourcing, and how they apply
 //! to waSCC actor development, check the documentation on [waSCC.dev](https://wascc.dev)
 
+use crate::access::pattern_matches;
+use crate::core::Timestamp;
 use crate::Sample;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 /// Capability provider uses this operation to deliver an event to an actor
@@ -13,6 +16,21 @@ pub const OP_DELIVER_EVENT: &str = "DeliverEvent";
 pub const OP_WRITE_EVENT: &str = "WriteEvent";
 /// Actor invokes this operation to execute a query against an event stream
 pub const OP_QUERY_STREAM: &str = "QueryStream";
+/// Actor invokes this operation to receive the events of several streams merged into a single delivery
+/// sequence. The provider responds with a MultiStreamSubscription and then delivers each event with
+/// OP_DELIVER_MERGED_EVENT
+pub const OP_SUBSCRIBE_STREAMS: &str = "SubscribeStreams";
+/// Capability provider uses this operation to deliver an event from a multi-stream subscription to an actor
+pub const OP_DELIVER_MERGED_EVENT: &str = "DeliverMergedEvent";
+/// Actor invokes this operation to end a multi-stream subscription
+pub const OP_UNSUBSCRIBE_STREAMS: &str = "UnsubscribeStreams";
+/// Actor invokes this operation to remove or collapse the older events of a stream
+pub const OP_COMPACT_STREAM: &str = "CompactStream";
+
+/// The event value under which a snapshot marker records the ID of its snapshot
+pub const SNAPSHOT_MARKER_ID: &str = "__wascc_snapshot_id";
+/// The event value under which a snapshot marker records the version of the state in its snapshot
+pub const SNAPSHOT_MARKER_VERSION: &str = "__wascc_snapshot_version";
 
 /// Represents an immutable event within a stream
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
@@ -26,6 +44,22 @@ pub struct Event {
     pub values: HashMap<String, String>,
 }
 
+impl Event {
+    /// Returns the snapshot marker carried by this event, if it is one
+    pub fn snapshot_marker(&self) -> Option<SnapshotMarker> {
+        let snapshot_id = self.values.get(SNAPSHOT_MARKER_ID)?;
+        let state_version = self
+            .values
+            .get(SNAPSHOT_MARKER_VERSION)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+        Some(SnapshotMarker {
+            snapshot_id: snapshot_id.to_string(),
+            state_version,
+        })
+    }
+}
+
 /// The response from the provider after writing an event to a stream
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -46,6 +80,9 @@ pub struct StreamQuery {
     /// A maximum count to return from the query. 0 will return the maximum available
     /// (which may not include all events--consult the individual provider documentation to verify this behavior)
     pub count: u64,
+    /// Only events matching the filter are returned. Providers apply the filter before `count`
+    #[serde(default, skip_serializing_if = "Filter::is_empty")]
+    pub filter: Filter,
 }
 
 impl Sample for StreamQuery {
@@ -57,6 +94,7 @@ impl Sample for StreamQuery {
                 max_time: 1000,
             }),
             count: 42,
+            filter: Filter::new().equals("region", "eu"),
         }
     }
 }
@@ -70,6 +108,20 @@ pub struct StreamResults {
     pub events: Vec<Event>,
 }
 
+impl StreamResults {
+    /// The events from which to rebuild state: those from the last snapshot marker onwards, or all
+    /// events if there is no marker. Replay should load the marker's snapshot, then apply the events
+    /// that follow it
+    pub fn from_latest_snapshot(&self) -> &[Event] {
+        let start = self
+            .events
+            .iter()
+            .rposition(|e| e.snapshot_marker().is_some())
+            .unwrap_or(0);
+        &self.events[start..]
+    }
+}
+
 /// Represents a timeslice range for a stream
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -79,3 +131,349 @@ pub struct TimeRange {
     /// Maximum time before which events must have occurred to be in the results (seconds since the epoch)
     pub max_time: u64,
 }
+
+/// A request to subscribe to several streams at once, with the provider merging their events
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiStreamSubscribe {
+    /// An actor-chosen ID that identifies the subscription in deliveries
+    pub subscription_id: String,
+    pub streams: StreamSelector,
+    /// The position from which to start delivering each stream, keyed by stream ID
+    #[serde(default)]
+    pub start_positions: HashMap<String, StartPosition>,
+    /// The position from which to start delivering streams without an entry in `start_positions`,
+    /// including streams matching a pattern that are created after the subscription
+    #[serde(default)]
+    pub default_start: StartPosition,
+    /// Only events matching the filter are delivered
+    #[serde(default, skip_serializing_if = "Filter::is_empty")]
+    pub filter: Filter,
+}
+
+impl Sample for MultiStreamSubscribe {
+    fn sample() -> Self {
+        let mut start_positions = HashMap::new();
+        start_positions.insert(
+            "orders-eu".to_string(),
+            StartPosition::AfterEvent("evt-1029".to_string()),
+        );
+        MultiStreamSubscribe {
+            subscription_id: "all-orders".to_string(),
+            streams: StreamSelector::Pattern("orders-*".to_string()),
+            start_positions,
+            default_start: StartPosition::Latest,
+            filter: Filter::new().range("total", Some("100"), None),
+        }
+    }
+}
+
+impl MultiStreamSubscribe {
+    /// The position from which the given stream should be delivered
+    pub fn start_for(&self, stream_id: &str) -> &StartPosition {
+        self.start_positions
+            .get(stream_id)
+            .unwrap_or(&self.default_start)
+    }
+}
+
+/// A portable expression selecting events by their values, evaluated by the provider so that events an
+/// actor is not interested in are never delivered. An event matches when it satisfies every condition,
+/// so an empty filter matches every event
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+impl Filter {
+    /// Creates an empty filter, which matches every event
+    pub fn new() -> Filter {
+        Filter::default()
+    }
+
+    pub fn equals(mut self, field: &str, value: &str) -> Filter {
+        self.conditions.push(Condition::Equals {
+            field: field.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn prefix(mut self, field: &str, prefix: &str) -> Filter {
+        self.conditions.push(Condition::Prefix {
+            field: field.to_string(),
+            prefix: prefix.to_string(),
+        });
+        self
+    }
+
+    pub fn range(mut self, field: &str, min: Option<&str>, max: Option<&str>) -> Filter {
+        self.conditions.push(Condition::Range {
+            field: field.to_string(),
+            min: min.map(|s| s.to_string()),
+            max: max.map(|s| s.to_string()),
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Indicates whether the event satisfies every condition of the filter
+    pub fn matches(&self, event: &Event) -> bool {
+        self.conditions.iter().all(|c| c.matches(&event.values))
+    }
+}
+
+/// A single condition on one of an event's values. A condition on a value the event does not have is
+/// never satisfied
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// The value is exactly the given string
+    Equals { field: String, value: String },
+    /// The value starts with the given string
+    Prefix { field: String, prefix: String },
+    /// The value lies between the bounds, inclusively. A missing bound is unbounded. When the value and
+    /// a bound both parse as numbers they are compared numerically, otherwise lexicographically
+    Range {
+        field: String,
+        #[serde(default)]
+        min: Option<String>,
+        #[serde(default)]
+        max: Option<String>,
+    },
+}
+
+impl Condition {
+    fn matches(&self, values: &HashMap<String, String>) -> bool {
+        match self {
+            Condition::Equals { field, value } => values.get(field) == Some(value),
+            Condition::Prefix { field, prefix } => {
+                values.get(field).is_some_and(|v| v.starts_with(prefix))
+            }
+            Condition::Range { field, min, max } => values.get(field).is_some_and(|v| {
+                min.as_ref()
+                    .is_none_or(|min| compare_values(v, min).is_ge())
+                    && max
+                        .as_ref()
+                        .is_none_or(|max| compare_values(v, max).is_le())
+            }),
+        }
+    }
+}
+
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+/// Selects the streams included in a multi-stream subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamSelector {
+    /// Exactly these streams
+    Ids(Vec<String>),
+    /// Every stream whose ID matches the pattern, in which `*` matches any sequence of characters
+    Pattern(String),
+}
+
+impl StreamSelector {
+    /// Indicates whether the given stream is included in the selection
+    pub fn matches(&self, stream_id: &str) -> bool {
+        match self {
+            StreamSelector::Ids(ids) => ids.iter().any(|id| id == stream_id),
+            StreamSelector::Pattern(pattern) => pattern_matches(pattern, stream_id),
+        }
+    }
+}
+
+/// The point in a stream from which a subscription starts delivering events
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StartPosition {
+    /// The first event in the stream
+    Beginning,
+    /// Only events written after the subscription was created
+    #[default]
+    Latest,
+    /// The events following the event with the given ID
+    AfterEvent(String),
+    /// The events that occurred at or after the given time
+    AtTime(Timestamp),
+}
+
+/// The provider's response to a multi-stream subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiStreamSubscription {
+    pub subscription_id: String,
+    /// The streams matched at the time the subscription was created
+    #[serde(default)]
+    pub matched_streams: Vec<String>,
+}
+
+/// An event delivered as part of a multi-stream subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedEvent {
+    pub subscription_id: String,
+    /// The stream from which the event was read
+    pub source_stream: String,
+    /// The position of this event in the merged delivery sequence, starting at 0. Events from a single
+    /// stream are always delivered in their stream order, but no ordering is guaranteed across streams
+    pub sequence_no: u64,
+    pub event: Event,
+}
+
+/// Ends a multi-stream subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiStreamUnsubscribe {
+    pub subscription_id: String,
+}
+
+/// Records that a snapshot of the state derived from a stream was taken at this point in the stream.
+/// Markers are written as ordinary events whose values carry the marker's fields, so that replay can
+/// start at the most recent marker instead of the beginning of the stream
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMarker {
+    /// Identifies the snapshot, e.g. the actor key used with `state::OP_LOAD_SNAPSHOT`
+    pub snapshot_id: String,
+    /// The version of the state captured in the snapshot
+    pub state_version: u64,
+}
+
+impl SnapshotMarker {
+    /// Produces the event to write (with `OP_WRITE_EVENT`) to record this marker in a stream
+    pub fn to_event(&self, event_id: &str, stream: &str) -> Event {
+        let mut values = HashMap::new();
+        values.insert(SNAPSHOT_MARKER_ID.to_string(), self.snapshot_id.to_string());
+        values.insert(
+            SNAPSHOT_MARKER_VERSION.to_string(),
+            self.state_version.to_string(),
+        );
+        Event {
+            event_id: event_id.to_string(),
+            stream: stream.to_string(),
+            values,
+        }
+    }
+}
+
+/// The point up to which a stream is compacted
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionPoint {
+    /// Up to and including the event with the given ID
+    ThroughEvent(String),
+    /// Events that occurred before the given time
+    BeforeTime(Timestamp),
+    /// Events preceding the most recent snapshot marker. The marker itself is always retained
+    LatestSnapshot,
+}
+
+/// How the events before the compaction point are compacted
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionStrategy {
+    /// Remove the events
+    #[default]
+    Truncate,
+    /// Keep only the most recent event for each distinct value of the named event value, e.g. an
+    /// entity ID, removing the rest
+    LatestPerKey(String),
+}
+
+/// A request to compact a stream
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactStreamRequest {
+    pub stream_id: String,
+    pub up_to: CompactionPoint,
+    #[serde(default)]
+    pub strategy: CompactionStrategy,
+}
+
+impl Sample for CompactStreamRequest {
+    fn sample() -> Self {
+        CompactStreamRequest {
+            stream_id: "orders-eu".to_string(),
+            up_to: CompactionPoint::LatestSnapshot,
+            strategy: CompactionStrategy::Truncate,
+        }
+    }
+}
+
+/// The result of compacting a stream
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactStreamResult {
+    /// The number of events removed from the stream
+    pub removed_count: u64,
+    /// The ID of the oldest event remaining in the stream, if any
+    #[serde(default)]
+    pub first_retained_event: Option<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Event, Filter, SnapshotMarker, StreamResults};
+    use std::collections::HashMap;
+
+    #[test]
+    fn replay_starts_at_latest_snapshot_marker() {
+        let event = |id: &str| Event {
+            event_id: id.to_string(),
+            stream: "orders".to_string(),
+            values: HashMap::new(),
+        };
+        let marker = SnapshotMarker {
+            snapshot_id: "order-1029".to_string(),
+            state_version: 2,
+        };
+        let mut results = StreamResults {
+            events: vec![event("e1"), event("e2")],
+        };
+        assert_eq!(results.from_latest_snapshot().len(), 2);
+
+        results.events.push(marker.to_event("e3", "orders"));
+        results.events.push(event("e4"));
+        let replay = results.from_latest_snapshot();
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replay[0].snapshot_marker(), Some(marker));
+        assert_eq!(replay[1].snapshot_marker(), None);
+    }
+
+    #[test]
+    fn filter_matches_all_conditions() {
+        let mut values = HashMap::new();
+        values.insert("region".to_string(), "eu-west".to_string());
+        values.insert("total".to_string(), "250".to_string());
+        let event = Event {
+            event_id: "e1".to_string(),
+            stream: "orders".to_string(),
+            values,
+        };
+        assert!(Filter::new().matches(&event));
+        assert!(Filter::new()
+            .prefix("region", "eu-")
+            .range("total", Some("100"), Some("1000"))
+            .matches(&event));
+        // 250 < 1000 numerically, although "250" > "1000" lexicographically
+        assert!(Filter::new()
+            .range("total", None, Some("1000"))
+            .matches(&event));
+        assert!(!Filter::new()
+            .prefix("region", "eu-")
+            .equals("region", "eu")
+            .matches(&event));
+        assert!(!Filter::new().equals("customer", "c1").matches(&event));
+    }
+}
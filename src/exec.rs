@@ -0,0 +1,176 @@
+//! # Interactive Sessions
+//!
+//! This module contains data types for the `wascc:terminal` capability provider, which lets operators
+//! open interactive sessions (e.g. a debugging console) to actors. Terminal input and output travel as
+//! sequenced frames in both directions, in the manner of streamed HTTP bodies, until either side closes
+//! the session
+
+use crate::preview::BytesPreview;
+use crate::Sample;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Provider sends an OpenSession to the guest when an operator connects, lack of error indicates the
+/// guest accepted the session
+pub const OP_OPEN_SESSION: &str = "OpenSession";
+/// Provider sends a SessionInput to the guest for each frame of operator input
+pub const OP_DELIVER_SESSION_INPUT: &str = "DeliverSessionInput";
+/// Guest sends a SessionOutput to the capability provider for each frame of output, lack of error
+/// indicates success
+pub const OP_SEND_SESSION_OUTPUT: &str = "SendSessionOutput";
+/// Either side sends a CloseSession to end a session, lack of error indicates success
+pub const OP_CLOSE_SESSION: &str = "CloseSession";
+
+/// The size of the operator's terminal, in character cells
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// Starts an interactive session
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenSession {
+    /// Identifies the session in all of its frames
+    pub session_id: String,
+    /// The identity of the operator, as authenticated by the provider
+    pub operator: String,
+    /// The command the operator asked for, e.g. `["repl"]`. Empty - the guest's default console
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The initial size of the operator's terminal. None - the session is not attached to a terminal,
+    /// e.g. when input is piped
+    #[serde(default)]
+    pub size: Option<TerminalSize>,
+}
+
+impl Sample for OpenSession {
+    fn sample() -> Self {
+        let mut env = HashMap::new();
+        env.insert("TERM".to_string(), "xterm-256color".to_string());
+        OpenSession {
+            session_id: "sess-7f3a".to_string(),
+            operator: "alice@example.com".to_string(),
+            command: vec!["repl".to_string()],
+            env,
+            size: Some(TerminalSize {
+                cols: 120,
+                rows: 40,
+            }),
+        }
+    }
+}
+
+/// A frame of operator input
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInput {
+    pub session_id: String,
+    /// A sequence number that can be used for ordering logic, starting at 0
+    pub sequence_no: u64,
+    /// The keystrokes or piped bytes, empty for frames that only resize the terminal
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+    /// Present when the operator's terminal was resized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resize: Option<TerminalSize>,
+}
+
+impl fmt::Debug for SessionInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionInput")
+            .field("session_id", &self.session_id)
+            .field("sequence_no", &self.sequence_no)
+            .field("bytes", &BytesPreview(&self.bytes))
+            .field("resize", &self.resize)
+            .finish()
+    }
+}
+
+impl SessionInput {
+    pub fn data(session_id: &str, sequence_no: u64, bytes: Vec<u8>) -> SessionInput {
+        SessionInput {
+            session_id: session_id.to_string(),
+            sequence_no,
+            bytes,
+            resize: None,
+        }
+    }
+
+    pub fn resize(session_id: &str, sequence_no: u64, size: TerminalSize) -> SessionInput {
+        SessionInput {
+            session_id: session_id.to_string(),
+            sequence_no,
+            bytes: Vec::new(),
+            resize: Some(size),
+        }
+    }
+}
+
+/// The stream a frame of output belongs to
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// A frame of output for the operator
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOutput {
+    pub session_id: String,
+    /// A sequence number that can be used for ordering logic, starting at 0
+    pub sequence_no: u64,
+    #[serde(default)]
+    pub stream: OutputStream,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+}
+
+impl fmt::Debug for SessionOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionOutput")
+            .field("session_id", &self.session_id)
+            .field("sequence_no", &self.sequence_no)
+            .field("stream", &self.stream)
+            .field("bytes", &BytesPreview(&self.bytes))
+            .finish()
+    }
+}
+
+impl SessionOutput {
+    pub fn new(
+        session_id: &str,
+        sequence_no: u64,
+        stream: OutputStream,
+        bytes: Vec<u8>,
+    ) -> SessionOutput {
+        SessionOutput {
+            session_id: session_id.to_string(),
+            sequence_no,
+            stream,
+            bytes,
+        }
+    }
+}
+
+/// Ends a session. No frames of the session are sent after it
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseSession {
+    pub session_id: String,
+    /// The exit status of the session's command, when the guest closes the session
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Why the session was closed, e.g. `operator disconnected` or `idle timeout`
+    #[serde(default)]
+    pub reason: String,
+}
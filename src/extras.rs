@@ -12,6 +12,10 @@ pub const OP_REQUEST_GUID: &str = "RequestGuid";
 pub const OP_REQUEST_SEQUENCE: &str = "RequestSequence";
 /// The operation to request a random number with an optional range
 pub const OP_REQUEST_RANDOM: &str = "RequestRandom";
+/// The operation to request a verifiable random output for a given input
+pub const OP_REQUEST_VRF: &str = "RequestVrf";
+/// The operation to request a reproducible sequence of random numbers from a seed
+pub const OP_REQUEST_SEEDED_RANDOM: &str = "RequestSeededRandom";
 
 /// The results of a generation request. The struct has been flattened rather than
 /// using an enum variant in order to make serialization compatibility easier
@@ -56,3 +60,66 @@ pub struct GeneratorRequest {
     /// Maximum value for a random number request
     pub max: u32,
 }
+
+/// A request to evaluate a verifiable random function (VRF) over an input. The output is
+/// unpredictable without the provider's secret key, yet anyone holding the corresponding public
+/// key can use the proof to verify that the output was computed correctly for this input
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfRequest {
+    /// The input to the function, e.g. a lottery round ID
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub input: Vec<u8>,
+    /// Identifies the key pair to use, as configured in the provider
+    pub key_ref: String,
+}
+
+impl Sample for VrfRequest {
+    fn sample() -> Self {
+        VrfRequest {
+            input: b"lottery-round-17".to_vec(),
+            key_ref: "lottery".to_string(),
+        }
+    }
+}
+
+/// The result of evaluating a verifiable random function
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VrfResult {
+    /// The pseudorandom output
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub output: Vec<u8>,
+    /// The proof that `output` was derived from the input with the key pair's secret key
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub proof: Vec<u8>,
+    /// The public key with which the proof can be verified
+    pub public_key: String,
+}
+
+/// A request for a sequence of random numbers derived deterministically from a seed. The same seed,
+/// count and range always produce the same numbers, so results can be audited by replaying the request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeededRandomRequest {
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub seed: Vec<u8>,
+    /// The number of random numbers to produce
+    pub count: u32,
+    /// Minimum value for each number
+    pub min: u32,
+    /// Maximum value for each number
+    pub max: u32,
+}
+
+/// The numbers produced for a seeded random request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SeededRandomResult {
+    #[serde(default)]
+    pub numbers: Vec<u32>,
+}
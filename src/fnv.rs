@@ -0,0 +1,20 @@
+// 64-bit FNV-1a, used for short fingerprints that need to be stable but not cryptographic. Each field
+// is followed by a separator byte, so that adjacent fields cannot run together.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// Hashes the fields in order, returning the hash as 16 lowercase hex digits
+pub(crate) fn fingerprint<'a, I>(fields: I, separator: u8) -> String
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut hash = OFFSET_BASIS;
+    for field in fields {
+        for b in field.iter().chain(std::iter::once(&separator)) {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,119 @@
+//! # GraphQL Gateway
+//!
+//! This module contains data types for the `wascc:graphql` capability provider. The provider terminates
+//! GraphQL-over-HTTP and websocket connections and delivers queries, mutations, and subscriptions to
+//! actors that implement the GraphQL backend. JSON documents (variables, data) are carried as raw
+//! JSON bytes so that actors can decode them into their own types
+
+use crate::Sample;
+
+/// Provider delivers a GraphQLRequest (query or mutation) to an actor, receives a GraphQLResponse back
+pub const OP_HANDLE_REQUEST: &str = "HandleGraphQLRequest";
+/// Provider delivers a SubscriptionStart to an actor when a client subscribes, lack of error indicates success
+pub const OP_START_SUBSCRIPTION: &str = "StartSubscription";
+/// Provider delivers a SubscriptionStop to an actor when a client unsubscribes or disconnects
+pub const OP_STOP_SUBSCRIPTION: &str = "StopSubscription";
+/// Actor sends a SubscriptionEvent to the provider to push a result to a subscribed client
+pub const OP_PUBLISH_EVENT: &str = "PublishSubscriptionEvent";
+/// Actor sends a SubscriptionStop to the provider to end a subscription from the server side
+pub const OP_COMPLETE_SUBSCRIPTION: &str = "CompleteSubscription";
+
+/// A GraphQL query or mutation
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLRequest {
+    /// The GraphQL document
+    pub query: String,
+    /// The name of the operation to execute, required if the document contains more than one operation
+    #[serde(default)]
+    pub operation_name: Option<String>,
+    /// The variables object, as JSON bytes. Empty if no variables were supplied
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub variables: Vec<u8>,
+}
+
+impl Sample for GraphQLRequest {
+    fn sample() -> Self {
+        GraphQLRequest {
+            query: "query User($id: ID!) { user(id: $id) { name } }".to_string(),
+            operation_name: Some("User".to_string()),
+            variables: br#"{"id":"42"}"#.to_vec(),
+        }
+    }
+}
+
+/// A location within a GraphQL document
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// An error produced while executing a GraphQL request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default)]
+    pub locations: Vec<SourceLocation>,
+    /// The path of the response field that produced the error. List indices are represented in decimal
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// The error's extensions object, as JSON bytes
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub extensions: Vec<u8>,
+}
+
+/// The result of executing a GraphQL request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphQLResponse {
+    /// The data object, as JSON bytes. Empty if execution did not produce data
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+}
+
+impl GraphQLResponse {
+    /// Creates a response containing only a single error
+    pub fn error(message: &str) -> GraphQLResponse {
+        GraphQLResponse {
+            data: vec![],
+            errors: vec![GraphQLError {
+                message: message.to_string(),
+                locations: vec![],
+                path: vec![],
+                extensions: vec![],
+            }],
+        }
+    }
+}
+
+/// Delivered to an actor when a client starts a subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStart {
+    /// A provider-assigned ID that identifies the subscription in subsequent events
+    pub subscription_id: String,
+    pub request: GraphQLRequest,
+}
+
+/// A single result pushed to a subscribed client
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionEvent {
+    pub subscription_id: String,
+    pub payload: GraphQLResponse,
+}
+
+/// Ends a subscription, either at the client's request or the actor's
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionStop {
+    pub subscription_id: String,
+}
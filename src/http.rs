@@ -2,21 +2,148 @@
 //!
 //! This module contains data types for the `wascc:http_server` and `wascc:httpclient` capabilities
 
+use crate::blobstore::BlobReference;
+use crate::errors::WasccCodecError;
+use crate::preview::BytesPreview;
 use crate::Sample;
-use serde::ser::Serialize;
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod auth;
+pub mod blobs;
+pub mod url;
 
 /// Operation invoked on a host to perform an HTTP request
 pub const OP_PERFORM_REQUEST: &str = "PerformRequest";
 /// Operation invoked on an actor in response to an inbound HTTP request
 pub const OP_HANDLE_REQUEST: &str = "HandleRequest";
+/// Operation invoked on an actor to deliver a chunk of a streamed request body
+pub const OP_RECEIVE_BODY_CHUNK: &str = "ReceiveBodyChunk";
+/// Operation invoked on a host to send a chunk of a streamed response body
+pub const OP_SEND_BODY_CHUNK: &str = "SendBodyChunk";
+
+/// The name of the header carrying the media type of a body
+pub const HEADER_CONTENT_TYPE: &str = "Content-Type";
+/// The name of the header carrying the length of a body
+pub const HEADER_CONTENT_LENGTH: &str = "Content-Length";
+
+/// An HTTP request method. Methods are serialized as plain strings, so this type is wire-compatible with
+/// payloads that carry the method as a string. Parsing is case-insensitive for the standard methods;
+/// any other method is preserved verbatim as an extension method
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Method {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+    /// An extension method, e.g. `PROPFIND`
+    Other(String),
+}
+
+impl Method {
+    /// Returns the canonical (upper case) name of the method
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::Other(m) => m,
+        }
+    }
+}
+
+impl From<&str> for Method {
+    fn from(s: &str) -> Method {
+        const STANDARD: [Method; 7] = [
+            Method::Get,
+            Method::Post,
+            Method::Put,
+            Method::Patch,
+            Method::Delete,
+            Method::Head,
+            Method::Options,
+        ];
+        STANDARD
+            .iter()
+            .find(|m| m.as_str().eq_ignore_ascii_case(s))
+            .cloned()
+            .unwrap_or_else(|| Method::Other(s.to_string()))
+    }
+}
+
+impl FromStr for Method {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Method, Self::Err> {
+        Ok(Method::from(s))
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for Method {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Method::Other(m) => m == other,
+            _ => self.as_str().eq_ignore_ascii_case(other),
+        }
+    }
+}
+
+impl PartialEq<&str> for Method {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for Method {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Method {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Method, D::Error> {
+        struct MethodVisitor;
+
+        impl<'de> Visitor<'de> for MethodVisitor {
+            type Value = Method;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an HTTP method name")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Method, E> {
+                Ok(Method::from(v))
+            }
+        }
+
+        deserializer.deserialize_str(MethodVisitor)
+    }
+}
 
 /// Describes an HTTP request
-#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[derive(PartialEq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Request {
     /// The HTTP method (e.g. GET, PUT, DELETE)
-    pub method: String,
+    pub method: Method,
     /// The path or URL of the request, leading slashes may not be trimmed
     pub path: String,
     /// The query string portion of the URL
@@ -28,16 +155,33 @@ pub struct Request {
     #[serde(with = "serde_bytes")]
     #[serde(default)]
     pub body: Vec<u8>,
+    /// Refers to the request body when it is too large to be carried inline, in which case `body` is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_ref: Option<BodyRef>,
+}
+
+impl fmt::Debug for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Request")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("query_string", &self.query_string)
+            .field("header", &self.header)
+            .field("body", &BytesPreview(&self.body))
+            .field("body_ref", &self.body_ref)
+            .finish()
+    }
 }
 
 impl Sample for Request {
     fn sample() -> Self {
         Request {
-            method: "GET".to_string(),
+            method: Method::Get,
             path: "/foo".to_string(),
             query_string: "a=1&b=2".to_string(),
             header: sample_header(),
             body: b"This is the body of a request".to_vec(),
+            body_ref: None,
         }
     }
 }
@@ -51,8 +195,9 @@ fn sample_header() -> HashMap<String, String> {
 }
 
 /// Represents an HTTP response
-#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[derive(PartialEq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct Response {
     /// The response's numerical status code (e.g. 200)
     pub status_code: u32,
@@ -65,6 +210,21 @@ pub struct Response {
     #[serde(with = "serde_bytes")]
     #[serde(default)]
     pub body: Vec<u8>,
+    /// Refers to the response body when it is too large to be carried inline, in which case `body` is empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_ref: Option<BodyRef>,
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Response")
+            .field("status_code", &self.status_code)
+            .field("status", &self.status)
+            .field("header", &self.header)
+            .field("body", &BytesPreview(&self.body))
+            .field("body_ref", &self.body_ref)
+            .finish()
+    }
 }
 
 impl Sample for Response {
@@ -74,11 +234,36 @@ impl Sample for Response {
             status: "OK".to_string(),
             header: sample_header(),
             body: b"This is the body of a response".to_vec(),
+            body_ref: None,
         }
     }
 }
 
 impl Response {
+    /// Creates a response with the given status and no headers or body
+    pub fn new(status_code: u32, status: &str) -> Response {
+        Response {
+            status_code,
+            status: status.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.header.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Response {
+        self.body = body;
+        self
+    }
+
+    pub fn with_body_ref(mut self, body_ref: BodyRef) -> Response {
+        self.body_ref = Some(body_ref);
+        self
+    }
+
     /// Creates a response with a given status code and serializes the given payload as JSON
     pub fn json<T>(payload: T, status_code: u32, status: &str) -> Response
     where
@@ -89,6 +274,7 @@ impl Response {
             header: HashMap::new(),
             status: status.to_string(),
             status_code,
+            body_ref: None,
         }
     }
 
@@ -129,3 +315,827 @@ impl Response {
         }
     }
 }
+
+/// The content type of an RFC 7807 problem details body
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+/// The problem type of problems that convey no more than the semantics of their HTTP status code
+pub const PROBLEM_TYPE_BLANK: &str = "about:blank";
+
+/// A machine-readable description of an error, as defined by RFC 7807. Actors return problems with
+/// `Response::problem` rather than inventing their own error body formats
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+pub struct Problem {
+    /// A URI reference identifying the problem type
+    #[serde(rename = "type", default = "blank_problem_type")]
+    pub problem_type: String,
+    /// A short, human-readable summary of the problem type
+    pub title: String,
+    /// The HTTP status code of the response carrying this problem
+    pub status: u32,
+    /// A human-readable explanation specific to this occurrence of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// A URI reference identifying this occurrence of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Additional, problem type specific members, serialized alongside the standard members
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+fn blank_problem_type() -> String {
+    PROBLEM_TYPE_BLANK.to_string()
+}
+
+impl Problem {
+    /// Creates a problem of type `about:blank`, whose title should be the reason phrase of the status code
+    pub fn new(status: u32, title: &str) -> Problem {
+        Problem {
+            problem_type: blank_problem_type(),
+            title: title.to_string(),
+            status,
+            detail: None,
+            instance: None,
+            extensions: serde_json::Map::new(),
+        }
+    }
+
+    pub fn with_type(self, problem_type: &str) -> Problem {
+        Problem {
+            problem_type: problem_type.to_string(),
+            ..self
+        }
+    }
+
+    pub fn with_detail(self, detail: &str) -> Problem {
+        Problem {
+            detail: Some(detail.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_instance(self, instance: &str) -> Problem {
+        Problem {
+            instance: Some(instance.to_string()),
+            ..self
+        }
+    }
+
+    /// Adds a problem type specific member. Names of the standard members are ignored
+    pub fn with_extension(mut self, name: &str, value: serde_json::Value) -> Problem {
+        if !["type", "title", "status", "detail", "instance"].contains(&name) {
+            self.extensions.insert(name.to_string(), value);
+        }
+        self
+    }
+}
+
+impl Response {
+    /// Creates a response carrying the given problem as its body, with the problem's status code and
+    /// the `application/problem+json` content type
+    pub fn problem(problem: Problem) -> Response {
+        let mut header = HashMap::new();
+        header.insert(
+            HEADER_CONTENT_TYPE.to_string(),
+            PROBLEM_CONTENT_TYPE.to_string(),
+        );
+        Response {
+            status_code: problem.status,
+            status: problem.title.to_string(),
+            header,
+            body: serde_json::to_vec(&problem).unwrap(),
+            body_ref: None,
+        }
+    }
+}
+
+/// A single byte range requested in a `Range` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ByteRange {
+    /// An inclusive range of byte offsets, e.g. `bytes=0-499`
+    FromTo(u64, u64),
+    /// All bytes from the given offset to the end, e.g. `bytes=500-`
+    From(u64),
+    /// The final n bytes, e.g. `bytes=-500`
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// Parses a single range specifier (without the `bytes=` unit prefix)
+    pub fn parse(spec: &str) -> Option<ByteRange> {
+        let (start, end) = spec.trim().split_once('-')?;
+        match (start.trim(), end.trim()) {
+            ("", "") => None,
+            ("", suffix) => suffix.parse().ok().map(ByteRange::Suffix),
+            (start, "") => start.parse().ok().map(ByteRange::From),
+            (start, end) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                if start <= end {
+                    Some(ByteRange::FromTo(start, end))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Resolves this range against a resource of the given total length, producing inclusive
+    /// start and end offsets. Returns `None` if the range cannot be satisfied
+    pub fn resolve(&self, total: u64) -> Option<(u64, u64)> {
+        if total == 0 {
+            return None;
+        }
+        match *self {
+            ByteRange::FromTo(start, end) if start < total => Some((start, end.min(total - 1))),
+            ByteRange::From(start) if start < total => Some((start, total - 1)),
+            ByteRange::Suffix(len) if len > 0 => Some((total.saturating_sub(len), total - 1)),
+            _ => None,
+        }
+    }
+}
+
+/// The set of byte ranges requested in a `Range` header, e.g. `bytes=0-99,200-299`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiRange {
+    pub ranges: Vec<ByteRange>,
+}
+
+impl MultiRange {
+    /// Parses the value of a `Range` header. Returns `None` if the header uses a unit other than
+    /// `bytes` or is malformed, in which case the header should be ignored and the full
+    /// resource returned
+    pub fn parse(header: &str) -> Option<MultiRange> {
+        let (unit, specs) = header.trim().split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+        let ranges = specs
+            .split(',')
+            .map(ByteRange::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(MultiRange { ranges })
+    }
+
+    /// Indicates whether the request asked for a single range (as opposed to a multipart response)
+    pub fn is_single(&self) -> bool {
+        self.ranges.len() == 1
+    }
+}
+
+impl Request {
+    /// Creates a request with the given method and path, and no query string, headers, or body
+    pub fn new(method: Method, path: &str) -> Request {
+        Request {
+            method,
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_query_string(mut self, query_string: &str) -> Request {
+        self.query_string = query_string.to_string();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Request {
+        self.header.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Request {
+        self.body = body;
+        self
+    }
+
+    pub fn with_body_ref(mut self, body_ref: BodyRef) -> Request {
+        self.body_ref = Some(body_ref);
+        self
+    }
+
+    /// Returns the value of a header, matching the header name case-insensitively
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Parses the request's `Range` header, if present and valid
+    pub fn range(&self) -> Option<MultiRange> {
+        self.header_value("Range").and_then(MultiRange::parse)
+    }
+}
+
+impl Response {
+    /// Creates a 206/Partial Content response for a single byte range of a resource with the given
+    /// total length, setting the `Content-Range` header. The `bytes` must contain exactly the requested
+    /// range. If the range cannot be satisfied, a 416/Range Not Satisfiable response is returned instead
+    pub fn partial_content(range: ByteRange, total: u64, bytes: Vec<u8>) -> Response {
+        match range.resolve(total) {
+            Some((start, end)) => {
+                let mut header = HashMap::new();
+                header.insert(
+                    "Content-Range".to_string(),
+                    format!("bytes {}-{}/{}", start, end, total),
+                );
+                header.insert("Accept-Ranges".to_string(), "bytes".to_string());
+                Response {
+                    status_code: 206,
+                    status: "Partial Content".to_string(),
+                    header,
+                    body: bytes,
+                    body_ref: None,
+                }
+            }
+            None => Response::range_not_satisfiable(total),
+        }
+    }
+
+    /// Creates a 416/Range Not Satisfiable response for a resource with the given total length
+    pub fn range_not_satisfiable(total: u64) -> Response {
+        let mut header = HashMap::new();
+        header.insert("Content-Range".to_string(), format!("bytes */{}", total));
+        Response {
+            status_code: 416,
+            status: "Range Not Satisfiable".to_string(),
+            header,
+            ..Default::default()
+        }
+    }
+}
+
+/// Controls how the http provider carries request and response bodies between itself and actors.
+/// Bodies up to `max_inline_bytes` are carried inline, larger bodies are handled according to the
+/// overflow strategy so that they do not exceed the host's invocation size limits
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BodyPolicy {
+    pub max_inline_bytes: u64,
+    pub overflow: OverflowStrategy,
+}
+
+/// What to do with a body that is larger than a policy's inline limit
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowStrategy {
+    /// Reject the request with a 413/Payload Too Large response
+    Reject,
+    /// Write the body to a blob in the given container and pass a `BodyRef::Blob` instead
+    SpillToBlobstore { container: String },
+    /// Deliver the body in chunks, passing a `BodyRef::Stream` in its place
+    Stream,
+}
+
+/// How a body of a given size is to be carried, as decided by a `BodyPolicy`
+#[derive(Debug, PartialEq, Clone)]
+pub enum BodyDisposition {
+    Inline,
+    SpillToBlobstore { container: String },
+    Stream,
+}
+
+impl BodyPolicy {
+    pub fn new(max_inline_bytes: u64, overflow: OverflowStrategy) -> BodyPolicy {
+        BodyPolicy {
+            max_inline_bytes,
+            overflow,
+        }
+    }
+
+    /// Decides how a body of the given length is to be carried. Fails with `PayloadTooLarge` if the
+    /// body exceeds the inline limit and the overflow strategy is to reject it
+    pub fn disposition(&self, length: u64) -> Result<BodyDisposition, WasccCodecError> {
+        if length <= self.max_inline_bytes {
+            return Ok(BodyDisposition::Inline);
+        }
+        match &self.overflow {
+            OverflowStrategy::Reject => Err(WasccCodecError::PayloadTooLarge {
+                actual: length,
+                allowed: self.max_inline_bytes,
+            }),
+            OverflowStrategy::SpillToBlobstore { container } => {
+                Ok(BodyDisposition::SpillToBlobstore {
+                    container: container.to_string(),
+                })
+            }
+            OverflowStrategy::Stream => Ok(BodyDisposition::Stream),
+        }
+    }
+}
+
+impl Default for BodyPolicy {
+    fn default() -> Self {
+        BodyPolicy {
+            max_inline_bytes: 1024 * 1024,
+            overflow: OverflowStrategy::Reject,
+        }
+    }
+}
+
+/// Refers to a body that is carried outside of the request or response itself
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyRef {
+    /// The body was written to a blob store object
+    Blob { blob: BlobReference, length: u64 },
+    /// The body is delivered as a sequence of `BodyChunk`s with the given stream ID. The length is
+    /// absent if the body uses chunked transfer encoding
+    #[serde(rename_all = "camelCase")]
+    Stream {
+        stream_id: String,
+        length: Option<u64>,
+    },
+}
+
+/// A chunk of a streamed body
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BodyChunk {
+    pub stream_id: String,
+    /// A sequence number that can be used for retry and ordering logic, starting at 0
+    pub sequence_no: u64,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+    /// Indicates that this is the final chunk of the body
+    pub last: bool,
+}
+
+impl fmt::Debug for BodyChunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyChunk")
+            .field("stream_id", &self.stream_id)
+            .field("sequence_no", &self.sequence_no)
+            .field("bytes", &BytesPreview(&self.bytes))
+            .field("last", &self.last)
+            .finish()
+    }
+}
+
+impl BodyChunk {
+    pub fn new(stream_id: &str, sequence_no: u64, bytes: Vec<u8>, last: bool) -> BodyChunk {
+        BodyChunk {
+            stream_id: stream_id.to_string(),
+            sequence_no,
+            bytes,
+            last,
+        }
+    }
+}
+
+impl Response {
+    /// Shortcut for creating a 413/Payload Too Large response
+    pub fn payload_too_large() -> Response {
+        Response {
+            status: "Payload Too Large".to_string(),
+            status_code: 413,
+            ..Default::default()
+        }
+    }
+}
+
+impl Request {
+    /// Indicates whether the request is a CORS preflight request, i.e. an `OPTIONS` request carrying
+    /// `Origin` and `Access-Control-Request-Method` headers
+    pub fn is_preflight(&self) -> bool {
+        self.method == Method::Options
+            && self.header_value("Origin").is_some()
+            && self.header_value("Access-Control-Request-Method").is_some()
+    }
+}
+
+impl Response {
+    /// Creates a 204/No Content response to a plain (non-preflight) `OPTIONS` request, listing the
+    /// methods the resource supports in the `Allow` header
+    pub fn options(allowed: &[Method]) -> Response {
+        Response::new(204, "No Content").with_header("Allow", &join_methods(allowed))
+    }
+
+    /// Turns the response to a `GET` request into the response to the equivalent `HEAD` request: the
+    /// body is dropped, and its length is reported in `Content-Length` unless that header is already set
+    pub fn into_head(mut self) -> Response {
+        if !has_header(&self.header, "Content-Length") && self.body_ref.is_none() {
+            self.header
+                .insert("Content-Length".to_string(), self.body.len().to_string());
+        }
+        self.body = Vec::new();
+        self.body_ref = None;
+        self
+    }
+}
+
+/// The cross-origin resource sharing rules of a web actor. Preflight requests are answered with
+/// `Response::cors_preflight`, and the headers of every other response are set with
+/// `Response::apply_cors_headers`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CorsPolicy {
+    /// The origins (e.g. `https://example.com`) allowed to make requests. `*` allows any origin
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// The methods allowed in cross-origin requests, in addition to the CORS-safelisted GET, HEAD and POST
+    #[serde(default)]
+    pub allowed_methods: Vec<Method>,
+    /// The request headers allowed in cross-origin requests, matched case-insensitively. `*` allows any
+    /// header
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    /// The response headers scripts may read, in addition to the CORS-safelisted ones
+    #[serde(default)]
+    pub exposed_headers: Vec<String>,
+    /// How long browsers may cache a preflight response, in seconds. None - the browser's default
+    #[serde(default)]
+    pub max_age_secs: Option<u32>,
+    /// Allow requests carrying credentials (cookies, authorization headers, or client certificates).
+    /// Only applies to origins listed explicitly: origins admitted by the `*` wildcard never receive
+    /// `Access-Control-Allow-Credentials`, since that would let any site make credentialed requests
+    #[serde(default)]
+    pub allow_credentials: bool,
+}
+
+impl CorsPolicy {
+    pub fn new() -> CorsPolicy {
+        CorsPolicy::default()
+    }
+
+    pub fn allow_origin(mut self, origin: &str) -> CorsPolicy {
+        self.allowed_origins.push(origin.to_string());
+        self
+    }
+
+    pub fn allow_method(mut self, method: Method) -> CorsPolicy {
+        self.allowed_methods.push(method);
+        self
+    }
+
+    pub fn allow_header(mut self, header: &str) -> CorsPolicy {
+        self.allowed_headers.push(header.to_string());
+        self
+    }
+
+    pub fn expose_header(mut self, header: &str) -> CorsPolicy {
+        self.exposed_headers.push(header.to_string());
+        self
+    }
+
+    pub fn with_max_age(mut self, secs: u32) -> CorsPolicy {
+        self.max_age_secs = Some(secs);
+        self
+    }
+
+    pub fn with_credentials(mut self) -> CorsPolicy {
+        self.allow_credentials = true;
+        self
+    }
+
+    /// Indicates whether requests from the given origin are allowed
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|o| o == "*" || o.eq_ignore_ascii_case(origin))
+    }
+
+    /// Indicates whether the given method is allowed in cross-origin requests
+    pub fn allows_method(&self, method: &Method) -> bool {
+        matches!(method, Method::Get | Method::Head | Method::Post)
+            || self.allowed_methods.contains(method)
+    }
+
+    /// Indicates whether the given request header is allowed in cross-origin requests
+    pub fn allows_header(&self, header: &str) -> bool {
+        self.allowed_headers
+            .iter()
+            .any(|h| h == "*" || h.eq_ignore_ascii_case(header))
+    }
+
+    // Indicates whether the given origin is listed by name, rather than admitted by the wildcard
+    fn lists_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|o| o.eq_ignore_ascii_case(origin))
+    }
+
+    // The value of Access-Control-Allow-Origin for a request from an allowed origin. Listed origins are
+    // echoed back, so that they may be sent credentials, the others get the wildcard
+    fn allow_origin_value(&self, origin: &str) -> String {
+        if self.lists_origin(origin) {
+            origin.to_string()
+        } else {
+            "*".to_string()
+        }
+    }
+
+    // Indicates whether a response to the given origin may carry Access-Control-Allow-Credentials
+    fn allows_credentials_for(&self, origin: &str) -> bool {
+        self.allow_credentials && self.lists_origin(origin)
+    }
+}
+
+impl Response {
+    /// Answers a CORS preflight request according to the policy. If the request's origin, method and
+    /// headers are allowed, the response is a 204/No Content carrying the `Access-Control-Allow-*`
+    /// headers, otherwise it is a 403/Forbidden without them, which makes the browser fail the request
+    pub fn cors_preflight(req: &Request, policy: &CorsPolicy) -> Response {
+        let forbidden = Response::new(403, "Forbidden").with_header("Vary", "Origin");
+        let origin = match req.header_value("Origin") {
+            Some(o) if policy.allows_origin(o) => o,
+            _ => return forbidden,
+        };
+        let method = match req
+            .header_value("Access-Control-Request-Method")
+            .map(|m| Method::from(m.trim()))
+        {
+            Some(m) if policy.allows_method(&m) => m,
+            _ => return forbidden,
+        };
+        let requested_headers: Vec<&str> = req
+            .header_value("Access-Control-Request-Headers")
+            .map(|h| {
+                h.split(',')
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !requested_headers.iter().all(|h| policy.allows_header(h)) {
+            return forbidden;
+        }
+
+        let mut methods = policy.allowed_methods.clone();
+        if !methods.contains(&method) {
+            methods.push(method);
+        }
+        let mut resp = Response::new(204, "No Content")
+            .with_header(
+                "Access-Control-Allow-Origin",
+                &policy.allow_origin_value(origin),
+            )
+            .with_header("Access-Control-Allow-Methods", &join_methods(&methods))
+            .with_header("Vary", "Origin");
+        if !requested_headers.is_empty() {
+            resp = resp.with_header(
+                "Access-Control-Allow-Headers",
+                &requested_headers.join(", "),
+            );
+        }
+        if let Some(secs) = policy.max_age_secs {
+            resp = resp.with_header("Access-Control-Max-Age", &secs.to_string());
+        }
+        if policy.allows_credentials_for(origin) {
+            resp = resp.with_header("Access-Control-Allow-Credentials", "true");
+        }
+        resp
+    }
+
+    /// Adds the CORS headers for the given (non-preflight) request to the response. Responses to
+    /// requests without an `Origin` header, or from an origin the policy does not allow, only gain a
+    /// `Vary: Origin` header
+    pub fn apply_cors_headers(mut self, req: &Request, policy: &CorsPolicy) -> Response {
+        add_vary(&mut self.header, "Origin");
+        let origin = match req.header_value("Origin") {
+            Some(o) if policy.allows_origin(o) => o,
+            _ => return self,
+        };
+        self = self.with_header(
+            "Access-Control-Allow-Origin",
+            &policy.allow_origin_value(origin),
+        );
+        if policy.allows_credentials_for(origin) {
+            self = self.with_header("Access-Control-Allow-Credentials", "true");
+        }
+        if !policy.exposed_headers.is_empty() {
+            self = self.with_header(
+                "Access-Control-Expose-Headers",
+                &policy.exposed_headers.join(", "),
+            );
+        }
+        self
+    }
+}
+
+fn join_methods(methods: &[Method]) -> String {
+    methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn has_header(header: &HashMap<String, String>, name: &str) -> bool {
+    header.keys().any(|k| k.eq_ignore_ascii_case(name))
+}
+
+// Adds a value to the Vary header, which may already exist under any capitalization
+fn add_vary(header: &mut HashMap<String, String>, value: &str) {
+    match header
+        .iter_mut()
+        .find(|(k, _)| k.eq_ignore_ascii_case("Vary"))
+    {
+        Some((_, v)) => {
+            if !v.split(',').any(|x| x.trim().eq_ignore_ascii_case(value)) {
+                v.push_str(", ");
+                v.push_str(value);
+            }
+        }
+        None => {
+            header.insert("Vary".to_string(), value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BodyDisposition, BodyPolicy, BodyRef, ByteRange, CorsPolicy, Method, MultiRange,
+        OverflowStrategy, Problem, Request, Response, PROBLEM_CONTENT_TYPE,
+    };
+    use crate::errors::WasccCodecError;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parse_and_resolve_ranges() {
+        let mr = MultiRange::parse("bytes=0-99, 200-, -50").unwrap();
+        assert_eq!(
+            mr.ranges,
+            vec![
+                ByteRange::FromTo(0, 99),
+                ByteRange::From(200),
+                ByteRange::Suffix(50)
+            ]
+        );
+        assert_eq!(mr.ranges[0].resolve(1000), Some((0, 99)));
+        assert_eq!(mr.ranges[1].resolve(1000), Some((200, 999)));
+        assert_eq!(mr.ranges[2].resolve(1000), Some((950, 999)));
+        assert_eq!(ByteRange::From(1000).resolve(1000), None);
+        assert_eq!(MultiRange::parse("items=0-1"), None);
+        assert_eq!(MultiRange::parse("bytes=5-1"), None);
+
+        let resp = Response::partial_content(ByteRange::FromTo(0, 1), 10, vec![1, 2]);
+        assert_eq!(resp.status_code, 206);
+        assert_eq!(resp.header["Content-Range"], "bytes 0-1/10");
+        assert_eq!(
+            Response::partial_content(ByteRange::From(10), 10, vec![]).status_code,
+            416
+        );
+    }
+
+    #[test]
+    fn problem_response_follows_rfc7807() {
+        let problem = Problem::new(403, "Forbidden")
+            .with_type("https://example.com/probs/out-of-credit")
+            .with_detail("Your current balance is 30, but that costs 50")
+            .with_extension("balance", serde_json::json!(30))
+            .with_extension("status", serde_json::json!(200));
+        let resp = Response::problem(problem.clone());
+        assert_eq!(resp.status_code, 403);
+        assert_eq!(resp.header["Content-Type"], PROBLEM_CONTENT_TYPE);
+        let body: serde_json::Value = serde_json::from_slice(&resp.body).unwrap();
+        assert_eq!(body["type"], "https://example.com/probs/out-of-credit");
+        assert_eq!(body["status"], 403);
+        assert_eq!(body["balance"], 30);
+        assert!(body.get("instance").is_none());
+        let parsed: Problem = serde_json::from_slice(&resp.body).unwrap();
+        assert_eq!(parsed, problem);
+
+        let blank: Problem = serde_json::from_str(r#"{"title":"Not Found","status":404}"#).unwrap();
+        assert_eq!(blank, Problem::new(404, "Not Found"));
+    }
+
+    #[test]
+    fn method_is_wire_compatible_with_strings() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LegacyRequest {
+            method: String,
+            path: String,
+            query_string: String,
+            header: HashMap<String, String>,
+        }
+        for (raw, expected) in &[
+            ("get", Method::Get),
+            ("Patch", Method::Patch),
+            ("PROPFIND", Method::Other("PROPFIND".to_string())),
+        ] {
+            let legacy = LegacyRequest {
+                method: raw.to_string(),
+                path: "/".to_string(),
+                query_string: String::new(),
+                header: HashMap::new(),
+            };
+            let req: Request = crate::deserialize(&crate::serialize(&legacy).unwrap()).unwrap();
+            assert_eq!(&req.method, expected);
+            assert_eq!(req.method, *raw);
+        }
+        let buf = crate::serialize(Request::new(Method::Delete, "/items/1")).unwrap();
+        let req: Request = crate::deserialize(&buf).unwrap();
+        assert_eq!(req.method.to_string(), "DELETE");
+    }
+
+    #[test]
+    fn cors_preflight_and_response_headers() {
+        let policy = CorsPolicy::new()
+            .allow_origin("https://app.example.com")
+            .allow_method(Method::Put)
+            .allow_header("Content-Type")
+            .expose_header("ETag")
+            .with_max_age(600)
+            .with_credentials();
+        let preflight = Request::new(Method::Options, "/items/1")
+            .with_header("origin", "https://app.example.com")
+            .with_header("Access-Control-Request-Method", "PUT")
+            .with_header("Access-Control-Request-Headers", "content-type");
+        assert!(preflight.is_preflight());
+        let resp = Response::cors_preflight(&preflight, &policy);
+        assert_eq!(resp.status_code, 204);
+        assert_eq!(
+            resp.header["Access-Control-Allow-Origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(resp.header["Access-Control-Allow-Methods"], "PUT");
+        assert_eq!(resp.header["Access-Control-Allow-Headers"], "content-type");
+        assert_eq!(resp.header["Access-Control-Max-Age"], "600");
+        assert_eq!(resp.header["Access-Control-Allow-Credentials"], "true");
+
+        let denied = preflight
+            .with_header("Access-Control-Request-Headers", "X-Secret")
+            .with_header("origin", "https://app.example.com");
+        let resp = Response::cors_preflight(&denied, &policy);
+        assert_eq!(resp.status_code, 403);
+        assert!(!resp.header.contains_key("Access-Control-Allow-Origin"));
+
+        let req = Request::new(Method::Get, "/items/1").with_header("Origin", "https://evil.com");
+        let resp = Response::ok()
+            .with_header("vary", "Accept")
+            .apply_cors_headers(&req, &policy);
+        assert_eq!(resp.header["vary"], "Accept, Origin");
+        assert!(!resp.header.contains_key("Access-Control-Allow-Origin"));
+
+        let public = CorsPolicy::new().allow_origin("*");
+        let req = Request::new(Method::Get, "/").with_header("Origin", "https://evil.com");
+        let resp = Response::ok()
+            .with_body(b"hello".to_vec())
+            .apply_cors_headers(&req, &public)
+            .into_head();
+        assert_eq!(resp.header["Access-Control-Allow-Origin"], "*");
+        assert_eq!(resp.header["Content-Length"], "5");
+        assert!(resp.body.is_empty());
+    }
+
+    #[test]
+    fn body_policy_reports_sizes_beyond_4_gib() {
+        let (four_gib, five_gib) = (4u64 << 30, 5u64 << 30);
+        let policy = BodyPolicy::new(four_gib, OverflowStrategy::Reject);
+        assert_eq!(
+            policy.disposition(five_gib),
+            Err(WasccCodecError::PayloadTooLarge {
+                actual: five_gib,
+                allowed: four_gib,
+            })
+        );
+        assert_eq!(policy.disposition(1024), Ok(BodyDisposition::Inline));
+        let policy = BodyPolicy::new(1024, OverflowStrategy::Stream);
+        assert_eq!(policy.disposition(five_gib), Ok(BodyDisposition::Stream));
+    }
+
+    #[test]
+    fn stream_body_refs_use_camel_case_fields() {
+        let body_ref = BodyRef::Stream {
+            stream_id: "s1".to_string(),
+            length: Some(5),
+        };
+        let value = serde_json::to_value(&body_ref).unwrap();
+        assert_eq!(value["stream"]["streamId"], "s1");
+        let buf = crate::serialize(&body_ref).unwrap();
+        assert_eq!(crate::deserialize::<BodyRef>(&buf).unwrap(), body_ref);
+    }
+
+    #[test]
+    fn cors_wildcard_origins_never_receive_credentials() {
+        let policy = CorsPolicy::new()
+            .allow_origin("*")
+            .allow_origin("https://app.example.com")
+            .with_credentials();
+        let preflight = Request::new(Method::Options, "/account")
+            .with_header("Origin", "https://evil.com")
+            .with_header("Access-Control-Request-Method", "GET");
+        let resp = Response::cors_preflight(&preflight, &policy);
+        assert_eq!(resp.status_code, 204);
+        assert_eq!(resp.header["Access-Control-Allow-Origin"], "*");
+        assert!(!resp.header.contains_key("Access-Control-Allow-Credentials"));
+
+        let req = Request::new(Method::Get, "/account").with_header("Origin", "https://evil.com");
+        let resp = Response::ok().apply_cors_headers(&req, &policy);
+        assert_eq!(resp.header["Access-Control-Allow-Origin"], "*");
+        assert!(!resp.header.contains_key("Access-Control-Allow-Credentials"));
+
+        let req = req.with_header("Origin", "https://app.example.com");
+        let resp = Response::ok().apply_cors_headers(&req, &policy);
+        assert_eq!(
+            resp.header["Access-Control-Allow-Origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(resp.header["Access-Control-Allow-Credentials"], "true");
+    }
+}
@@ -0,0 +1,125 @@
+//! Helpers that bridge the `wascc:blobstore` and `wascc:http_server` capabilities, for actors that
+//! serve blobs over HTTP or store uploaded request bodies as blobs.
+//!
+//! To serve a blob, an actor responds with `blob_response`, starts a download of the blob with
+//! `blobstore::OP_START_DOWNLOAD`, and forwards each `FileChunk` it receives to the http provider
+//! (with `OP_SEND_BODY_CHUNK`) after converting it with `body_chunk`. Blobs small enough to be
+//! carried inline can instead be assembled into a single response with `inline_blob_response`
+
+use super::{BodyChunk, BodyRef, Request, Response, HEADER_CONTENT_LENGTH, HEADER_CONTENT_TYPE};
+use crate::blobstore::{Blob, BlobReference, FileChunk};
+
+/// Creates a 200/OK response whose body will be streamed from the given blob, with the content
+/// type and length headers set. The body is delivered in `BodyChunk`s with the given stream ID
+pub fn blob_response(blob: &Blob, content_type: &str, stream_id: &str) -> Response {
+    Response::ok()
+        .with_header(HEADER_CONTENT_TYPE, content_type)
+        .with_header(HEADER_CONTENT_LENGTH, &blob.byte_size.to_string())
+        .with_body_ref(BodyRef::Stream {
+            stream_id: stream_id.to_string(),
+            length: Some(blob.byte_size),
+        })
+}
+
+/// Converts a downloaded chunk of a blob into a chunk of a streamed response body
+pub fn body_chunk(stream_id: &str, chunk: FileChunk) -> BodyChunk {
+    let last = chunk.is_last();
+    BodyChunk::new(stream_id, chunk.sequence_no, chunk.chunk_bytes, last)
+}
+
+/// Assembles the downloaded chunks of a blob, in any order, into a 200/OK response carrying the
+/// entire blob inline, with the content type and length headers set
+pub fn inline_blob_response(mut chunks: Vec<FileChunk>, content_type: &str) -> Response {
+    chunks.sort_by_key(|c| c.sequence_no);
+    let body: Vec<u8> = chunks.into_iter().flat_map(|c| c.chunk_bytes).collect();
+    Response::ok()
+        .with_header(HEADER_CONTENT_TYPE, content_type)
+        .with_header(HEADER_CONTENT_LENGTH, &body.len().to_string())
+        .with_body(body)
+}
+
+/// Splits the inline body of a request into chunks of at most `chunk_size` bytes, ready to be uploaded
+/// to the given blob. The first chunk should be sent with `OP_START_UPLOAD` and the remainder with
+/// `OP_UPLOAD_CHUNK`. A request with an empty body produces a single empty chunk. A `chunk_size` of 0 is
+/// treated as 1, and the chunks record the size actually used
+pub fn upload_chunks(request: &Request, blob: &BlobReference, chunk_size: u64) -> Vec<FileChunk> {
+    let total_bytes = request.body.len() as u64;
+    let chunk_size = chunk_size.max(1);
+    if request.body.is_empty() {
+        return vec![FileChunk::new(
+            0,
+            &blob.container,
+            &blob.id,
+            0,
+            chunk_size,
+            Vec::new(),
+        )];
+    }
+    request
+        .body
+        .chunks(chunk_size.min(usize::MAX as u64) as usize)
+        .enumerate()
+        .map(|(i, bytes)| {
+            FileChunk::new(
+                i as u64,
+                &blob.container,
+                &blob.id,
+                total_bytes,
+                chunk_size,
+                bytes.to_vec(),
+            )
+        })
+        .collect()
+}
+
+/// Converts a chunk of a streamed request body into a chunk to be uploaded to the given blob. The http
+/// provider delivers streamed bodies in chunks of `chunk_size` bytes (except the last), and the total
+/// length is taken from the request's `BodyRef::Stream`
+pub fn upload_chunk(
+    blob: &BlobReference,
+    total_bytes: u64,
+    chunk_size: u64,
+    chunk: BodyChunk,
+) -> FileChunk {
+    FileChunk::new(
+        chunk.sequence_no,
+        &blob.container,
+        &blob.id,
+        total_bytes,
+        chunk_size,
+        chunk.bytes,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{body_chunk, inline_blob_response, upload_chunks, HEADER_CONTENT_LENGTH};
+    use crate::blobstore::BlobReference;
+    use crate::http::{Method, Request};
+
+    #[test]
+    fn request_bodies_round_trip_through_blob_chunks() {
+        let blob = BlobReference::new("uploads", "photo.jpg");
+        let request = Request::new(Method::Put, "/photos").with_body(b"0123456789".to_vec());
+        let mut chunks = upload_chunks(&request, &blob, 4);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].chunk_bytes, b"89".to_vec());
+        assert!(!chunks[1].is_last());
+        assert!(chunks[2].is_last());
+
+        chunks.reverse();
+        let response = inline_blob_response(chunks, "image/jpeg");
+        assert_eq!(response.body, b"0123456789".to_vec());
+        assert_eq!(response.header[HEADER_CONTENT_LENGTH], "10");
+
+        let last = upload_chunks(&request, &blob, 4).pop().unwrap();
+        let streamed = body_chunk("s1", last);
+        assert_eq!(streamed.sequence_no, 2);
+        assert!(streamed.last);
+
+        let bytewise = upload_chunks(&request, &blob, 0);
+        assert_eq!(bytewise.len(), 10);
+        assert!(bytewise.iter().all(|c| c.chunk_size == 1));
+        assert!(bytewise[9].is_last() && !bytewise[8].is_last());
+    }
+}
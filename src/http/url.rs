@@ -0,0 +1,207 @@
+//! URL construction and percent-encoding helpers. Encoding follows RFC 3986: every byte other than the
+//! unreserved characters (ASCII letters, digits, `-`, `.`, `_` and `~`) is encoded as `%XX`. The helpers
+//! are pure Rust with no host dependencies, so they can run inside wasm guests
+
+use super::{Method, Request};
+use crate::errors::WasccCodecError;
+use std::fmt::Write;
+
+/// Percent-encodes a value for use as a single path segment or query string component
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'_' || b == b'~' {
+            encoded.push(b as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", b);
+        }
+    }
+    encoded
+}
+
+/// Decodes a percent-encoded value. Fails if a `%` is not followed by two hex digits or the decoded
+/// bytes are not valid UTF-8
+pub fn percent_decode(value: &str) -> Result<String, WasccCodecError> {
+    let invalid = |reason: &str| WasccCodecError::InvalidPercentEncoding {
+        value: value.to_string(),
+        reason: reason.to_string(),
+    };
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .ok_or_else(|| invalid("incomplete escape sequence"))?;
+            // from_str_radix would accept a sign, e.g. `%+A`
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return Err(invalid("invalid escape sequence"));
+            }
+            decoded.push(hex_value(hex[0]) << 4 | hex_value(hex[1]));
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| invalid("not valid UTF-8"))
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+/// Decodes a query string component, in which `+` also represents a space
+pub fn query_decode(value: &str) -> Result<String, WasccCodecError> {
+    percent_decode(&value.replace('+', " "))
+}
+
+/// Builds a URL from unencoded parts, encoding each path segment and query pair
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct UrlBuilder {
+    scheme: Option<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    segments: Vec<String>,
+    query: Vec<(String, String)>,
+}
+
+impl UrlBuilder {
+    pub fn new() -> UrlBuilder {
+        UrlBuilder::default()
+    }
+
+    pub fn scheme(mut self, scheme: &str) -> UrlBuilder {
+        self.scheme = Some(scheme.to_string());
+        self
+    }
+
+    pub fn host(mut self, host: &str) -> UrlBuilder {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> UrlBuilder {
+        self.port = Some(port);
+        self
+    }
+
+    /// Appends a path segment. Any `/` within the segment is encoded rather than starting a new segment
+    pub fn segment(mut self, segment: &str) -> UrlBuilder {
+        self.segments.push(segment.to_string());
+        self
+    }
+
+    /// Appends the segments of an unencoded path, split on `/`. Empty segments are skipped
+    pub fn path(mut self, path: &str) -> UrlBuilder {
+        self.segments.extend(
+            path.split('/')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+        );
+        self
+    }
+
+    /// Appends a query pair. Pairs are kept in the order they are added, and names may repeat
+    pub fn query(mut self, name: &str, value: &str) -> UrlBuilder {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// The encoded path, always beginning with `/`
+    pub fn encoded_path(&self) -> String {
+        if self.segments.is_empty() {
+            return "/".to_string();
+        }
+        self.segments
+            .iter()
+            .fold(String::new(), |path, s| path + "/" + &percent_encode(s))
+    }
+
+    /// The encoded query string, without a leading `?`
+    pub fn query_string(&self) -> String {
+        self.query
+            .iter()
+            .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    /// The complete URL. If no host was set, only the path and query string are produced. The scheme
+    /// defaults to `https`
+    pub fn build(&self) -> String {
+        let mut url = String::new();
+        if let Some(ref host) = self.host {
+            let scheme = self.scheme.as_deref().unwrap_or("https");
+            let _ = write!(url, "{}://{}", scheme, host);
+            if let Some(port) = self.port {
+                let _ = write!(url, ":{}", port);
+            }
+        }
+        url.push_str(&self.encoded_path());
+        let query = self.query_string();
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+        url
+    }
+
+    /// Creates a request for the URL. The request's path is the full URL without its query string if a
+    /// host was set, as expected by http client providers, or the encoded path otherwise
+    pub fn to_request(&self, method: Method) -> Request {
+        let path = if self.host.is_some() {
+            UrlBuilder {
+                query: Vec::new(),
+                ..self.clone()
+            }
+            .build()
+        } else {
+            self.encoded_path()
+        };
+        Request::new(method, &path).with_query_string(&self.query_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{percent_decode, percent_encode, query_decode, UrlBuilder};
+    use crate::http::Method;
+
+    #[test]
+    fn builds_encoded_urls() {
+        assert_eq!(percent_encode("a b/ü~"), "a%20b%2F%C3%BC~");
+        assert_eq!(percent_decode("a%20b%2F%C3%BC~").unwrap(), "a b/ü~");
+        assert_eq!(query_decode("a+b%2B").unwrap(), "a b+");
+        assert!(percent_decode("100%").is_err());
+        assert!(percent_decode("%C3").is_err());
+        assert!(percent_decode("%+A").is_err());
+        assert!(percent_decode("%-1").is_err());
+        assert_eq!(percent_decode("%2f%2F").unwrap(), "//");
+
+        let url = UrlBuilder::new()
+            .host("api.example.com")
+            .port(8443)
+            .path("/v1/users/")
+            .segment("José Díaz")
+            .query("q", "a&b=c")
+            .query("page", "2");
+        assert_eq!(
+            url.build(),
+            "https://api.example.com:8443/v1/users/Jos%C3%A9%20D%C3%ADaz?q=a%26b%3Dc&page=2"
+        );
+
+        let request = url.to_request(Method::Get);
+        assert_eq!(
+            request.path,
+            "https://api.example.com:8443/v1/users/Jos%C3%A9%20D%C3%ADaz"
+        );
+        assert_eq!(request.query_string, "q=a%26b%3Dc&page=2");
+        assert_eq!(UrlBuilder::new().to_request(Method::Get).path, "/");
+    }
+}
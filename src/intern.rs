@@ -0,0 +1,187 @@
+//! # Interned Identifiers
+//!
+//! Hosts handling high invocation rates see the same operation names and actor keys on nearly every
+//! call. `OpName` and `ActorRef` hold these identifiers in a shared, reference-counted string so that
+//! cloning one is a pointer copy rather than an allocation, and an `Interner` hands out the same
+//! allocation for every occurrence of an identifier. Both types serialize as plain strings, so they
+//! can replace `String` fields without changing the wire format
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+
+macro_rules! interned_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(Arc<str>);
+
+        impl $name {
+            /// Creates an identifier with its own allocation. Use an `Interner` to share allocations
+            pub fn new(id: &str) -> $name {
+                $name(Arc::from(id))
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Indicates whether both identifiers share the same allocation, which is always the
+            /// case for equal identifiers obtained from the same `Interner`
+            pub fn ptr_eq(&self, other: &$name) -> bool {
+                Arc::ptr_eq(&self.0, &other.0)
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> $name {
+                $name::new(id)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> $name {
+                $name(Arc::from(id))
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                &*self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                &*self.0 == *other
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                String::deserialize(deserializer).map($name::from)
+            }
+        }
+    };
+}
+
+interned_id!(
+    /// The name of an operation, e.g. `HandleRequest`. Serialized as a plain string
+    OpName
+);
+
+interned_id!(
+    /// The public key (or other identifier) of an actor. Serialized as a plain string
+    ActorRef
+);
+
+/// A registry of identifiers that returns the same allocation for every occurrence of an identifier.
+/// Interned identifiers are never released, so an interner should only be fed identifiers drawn from a
+/// bounded set, such as the operations of bound providers and the keys of running actors
+#[derive(Debug, Default)]
+pub struct Interner {
+    ids: RwLock<HashSet<Arc<str>>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// Interns an operation name
+    pub fn op(&self, name: &str) -> OpName {
+        OpName(self.intern(name))
+    }
+
+    /// Interns an actor identifier
+    pub fn actor(&self, actor: &str) -> ActorRef {
+        ActorRef(self.intern(actor))
+    }
+
+    /// The number of distinct identifiers interned so far
+    pub fn len(&self) -> usize {
+        self.ids.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn intern(&self, id: &str) -> Arc<str> {
+        if let Some(existing) = self.ids.read().unwrap().get(id) {
+            return existing.clone();
+        }
+        let mut ids = self.ids.write().unwrap();
+        match ids.get(id) {
+            Some(existing) => existing.clone(),
+            None => {
+                let interned: Arc<str> = Arc::from(id);
+                ids.insert(interned.clone());
+                interned
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ActorRef, Interner, OpName};
+    use std::collections::HashMap;
+
+    #[test]
+    fn interned_ids_share_allocations_and_serialize_as_strings() {
+        let interner = Interner::new();
+        let a = interner.op("HandleRequest");
+        let b = interner.op("HandleRequest");
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&OpName::new("HandleRequest")));
+        assert_eq!(a, OpName::new("HandleRequest"));
+        assert_eq!(interner.actor("Mxxx"), "Mxxx");
+        assert_eq!(interner.len(), 2);
+
+        let mut counts: HashMap<OpName, u32> = HashMap::new();
+        counts.insert(a.clone(), 1);
+        assert_eq!(counts.get("HandleRequest"), Some(&1));
+
+        let buf = crate::serialize(&a).unwrap();
+        assert_eq!(buf, crate::serialize("HandleRequest").unwrap());
+        let actor: ActorRef = crate::deserialize(&crate::serialize("Mxxx").unwrap()).unwrap();
+        assert_eq!(actor.as_str(), "Mxxx");
+    }
+}
@@ -2,34 +2,115 @@
 //!
 //! This module contains data types for the `wascc:keyvalue` capability provider
 
+use crate::capabilities::CapabilityDescriptor;
+use crate::core::{DurationMs, Timestamp};
+use crate::errors::WasccCodecError;
 use crate::Sample;
+use std::convert::TryFrom;
+use std::error::Error;
 
+/// Guest sends an AddRequest to the provider, receives an AddResponse back
 pub const OP_ADD: &str = "Add";
+/// Guest sends a GetRequest to the provider, receives a GetResponse back
 pub const OP_GET: &str = "Get";
+/// Guest sends a SetRequest to the provider, receives a SetResponse back
 pub const OP_SET: &str = "Set";
+/// Guest sends a DelRequest to the provider, receives a DelResponse back
 pub const OP_DEL: &str = "Del";
+/// Guest sends a ListClearRequest to the provider, receives a DelResponse back
 pub const OP_CLEAR: &str = "Clear";
+/// Guest sends a ListRangeRequest to the provider, receives a ListRangeResponse back
 pub const OP_RANGE: &str = "Range";
+/// Guest sends a ListPushRequest to the provider, receives a ListResponse back
 pub const OP_PUSH: &str = "Push";
+/// Guest sends a ListDelItemRequest to the provider, receives a ListResponse back
 pub const OP_LIST_DEL: &str = "ListItemDelete";
 
+/// Guest sends a SetAddRequest to the provider, receives a SetOperationResponse back
 pub const OP_SET_ADD: &str = "SetAdd";
+/// Guest sends a SetRemoveRequest to the provider, receives a SetOperationResponse back
 pub const OP_SET_REMOVE: &str = "SetRemove";
+/// Guest sends a SetUnionRequest to the provider, receives a SetQueryResponse back
 pub const OP_SET_UNION: &str = "SetUnion";
+/// Guest sends a SetIntersectionRequest to the provider, receives a SetQueryResponse back
 pub const OP_SET_INTERSECT: &str = "SetIntersection";
+/// Guest sends a SetQueryRequest to the provider, receives a SetQueryResponse back
 pub const OP_SET_QUERY: &str = "SetQuery";
+/// Guest sends a KeyExistsQuery to the provider, receives a GetResponse back whose `exists` field
+/// holds the answer
 pub const OP_KEY_EXISTS: &str = "KeyExists";
 
+/// Guest sends a SetWithExpirationRequest to the provider, receives a SetResponse back
+pub const OP_SET_WITH_EXPIRATION: &str = "SetWithExpiration";
+/// Guest sends a CompareAndSetRequest to the provider, receives a CompareAndSetResponse back
+pub const OP_COMPARE_AND_SET: &str = "CompareAndSet";
+
+pub const OP_APPEND: &str = "Append";
+pub const OP_GET_RANGE: &str = "GetRange";
+
+pub const OP_JSON_GET: &str = "JsonGet";
+pub const OP_JSON_SET: &str = "JsonSet";
+
+/// Guest sends a PfAddRequest to the provider, receives a PfAddResponse back
+pub const OP_PF_ADD: &str = "PfAdd";
+/// Guest sends a PfCountRequest to the provider, receives a PfCountResponse back
+pub const OP_PF_COUNT: &str = "PfCount";
+/// Guest sends a PfMergeRequest to the provider, lack of error indicates success
+pub const OP_PF_MERGE: &str = "PfMerge";
+/// Guest sends a BloomReserveRequest to the provider, lack of error indicates success
+pub const OP_BLOOM_RESERVE: &str = "BloomReserve";
+/// Guest sends a BloomAddRequest to the provider, receives a BloomAddResponse back
+pub const OP_BLOOM_ADD: &str = "BloomAdd";
+/// Guest sends a BloomCheckRequest to the provider, receives a BloomCheckResponse back
+pub const OP_BLOOM_CHECK: &str = "BloomCheck";
+
+/// Guest sends an ExportRequest to the provider, receives an ExportStarted back. The guest will then
+/// start receiving OP_RECEIVE_EXPORT_CHUNK operations from the provider as the snapshot is streamed
+pub const OP_START_EXPORT: &str = "StartExport";
+/// Provider sends an ExportChunk to the guest for each chunk of an export in progress
+pub const OP_RECEIVE_EXPORT_CHUNK: &str = "ReceiveExportChunk";
+/// Guest sends an ImportChunk to the provider, receives an ImportResult back
+pub const OP_IMPORT_CHUNK: &str = "ImportChunk";
+
+/// Guest sends a SubscribeKeyspace to the provider, lack of error indicates success. The guest will then
+/// receive OP_DELIVER_CACHE_FILL and OP_DELIVER_CACHE_INVALIDATE operations for keys beneath the prefix
+pub const OP_SUBSCRIBE_KEYSPACE: &str = "SubscribeKeyspace";
+/// Guest sends an UnsubscribeKeyspace to the provider, lack of error indicates success
+pub const OP_UNSUBSCRIBE_KEYSPACE: &str = "UnsubscribeKeyspace";
+/// Provider sends a CacheFillEvent to the guest when a key in a subscribed keyspace is written
+pub const OP_DELIVER_CACHE_FILL: &str = "DeliverCacheFill";
+/// Provider sends a CacheInvalidateEvent to the guest when keys in a subscribed keyspace are changed or removed
+pub const OP_DELIVER_CACHE_INVALIDATE: &str = "DeliverCacheInvalidate";
+
 /// A request to get a single value from the K/V store
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct GetRequest {
     pub key: String,
 }
 
+impl Sample for GetRequest {
+    fn sample() -> Self {
+        GetRequest {
+            key: "counter1".to_string(),
+        }
+    }
+}
+
+impl GetRequest {
+    /// Creates a new get request
+    pub fn new(key: &str) -> GetRequest {
+        GetRequest {
+            key: key.to_string(),
+        }
+    }
+}
+
 /// The result of a get request
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct GetResponse {
     /// The value returned from the data store
     pub value: String,
@@ -37,9 +118,29 @@ pub struct GetResponse {
     pub exists: bool,
 }
 
+impl Sample for GetResponse {
+    fn sample() -> Self {
+        GetResponse {
+            value: "12".to_string(),
+            exists: true,
+        }
+    }
+}
+
+impl GetResponse {
+    /// Creates a new get response
+    pub fn new(value: &str, exists: bool) -> GetResponse {
+        GetResponse {
+            value: value.to_string(),
+            exists,
+        }
+    }
+}
+
 /// A request to set a value
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetRequest {
     /// Key of the item to set
     pub key: String,
@@ -48,6 +149,8 @@ pub struct SetRequest {
     /// Seconds after which the key will expire, 0 - no expiration
     #[serde(rename = "expires")]
     pub expires_s: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
 }
 
 impl Sample for SetRequest {
@@ -56,6 +159,251 @@ impl Sample for SetRequest {
             key: "counter1".to_string(),
             value: "12".to_string(),
             expires_s: 100,
+            write_concern: None,
+        }
+    }
+}
+
+impl SetRequest {
+    /// Creates a new set request
+    pub fn new(key: &str, value: &str, expires_s: i32) -> SetRequest {
+        SetRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            expires_s,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> SetRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    /// Creates a set request, first checking the key and value against a provider's limits
+    pub fn validated(
+        key: &str,
+        value: &str,
+        expires_s: i32,
+        limits: &KeyValidation,
+    ) -> Result<SetRequest, WasccCodecError> {
+        limits.validate_key(key)?;
+        limits.validate_value(value.as_bytes())?;
+        Ok(SetRequest::new(key, value, expires_s))
+    }
+}
+
+/// When a key expires. Flattened into the requests that carry it, so that it serializes as an
+/// `expiration` tag (`never`, `at` or `after`) alongside an `expiresAt` timestamp or `expiresAfter`
+/// duration, e.g. `{"key": "k", "value": "v", "expiration": "after", "expiresAfter": 30000}`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(tag = "expiration", rename_all = "snake_case")]
+pub enum Expiration {
+    /// The key never expires
+    Never,
+    /// The key expires at the given time
+    At {
+        #[serde(rename = "expiresAt")]
+        expires_at: Timestamp,
+    },
+    /// The key expires once the given duration has elapsed after the write
+    After {
+        #[serde(rename = "expiresAfter")]
+        expires_after: DurationMs,
+    },
+}
+
+impl Expiration {
+    pub fn at(expires_at: Timestamp) -> Expiration {
+        Expiration::At { expires_at }
+    }
+
+    pub fn after(expires_after: DurationMs) -> Expiration {
+        Expiration::After { expires_after }
+    }
+
+    /// Interprets the `expires_s` field of a `SetRequest`, where 0 means no expiration. Negative values
+    /// are rejected
+    pub fn from_legacy_secs(expires_s: i32) -> Result<Expiration, WasccCodecError> {
+        match expires_s {
+            0 => Ok(Expiration::Never),
+            s if s > 0 => Ok(Expiration::after(DurationMs::from_secs(s as u64))),
+            s => Err(WasccCodecError::InvalidExpiration {
+                reason: format!("{} seconds is negative", s),
+            }),
+        }
+    }
+
+    /// Verifies that this expiration would not expire a key written at the given time immediately,
+    /// i.e. that durations are non-zero and timestamps are in the future
+    pub fn validate(&self, now: Timestamp) -> Result<(), WasccCodecError> {
+        match self {
+            Expiration::Never => Ok(()),
+            Expiration::At { expires_at } if *expires_at <= now => {
+                Err(WasccCodecError::InvalidExpiration {
+                    reason: format!(
+                        "expiry time {} is not after the current time {}",
+                        expires_at.as_millis(),
+                        now.as_millis()
+                    ),
+                })
+            }
+            Expiration::After { expires_after } if expires_after.as_millis() == 0 => {
+                Err(WasccCodecError::InvalidExpiration {
+                    reason: "duration is zero".to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The time at which a key written at the given time expires, or `None` if it never expires
+    pub fn deadline(&self, written_at: Timestamp) -> Option<Timestamp> {
+        match self {
+            Expiration::Never => None,
+            Expiration::At { expires_at } => Some(*expires_at),
+            Expiration::After { expires_after } => Some(
+                written_at
+                    .checked_add(*expires_after)
+                    .unwrap_or(Timestamp(u64::MAX)),
+            ),
+        }
+    }
+}
+
+/// A request to set a value with an explicit expiration
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SetWithExpirationRequest {
+    pub key: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub expiration: Expiration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for SetWithExpirationRequest {
+    fn sample() -> Self {
+        SetWithExpirationRequest {
+            key: "session:af03".to_string(),
+            value: "alice".to_string(),
+            expiration: Expiration::after(DurationMs::from_secs(1800)),
+            write_concern: Some(WriteConcern::quorum()),
+        }
+    }
+}
+
+impl SetWithExpirationRequest {
+    /// Creates a new set request with an expiration
+    pub fn new(key: &str, value: &str, expiration: Expiration) -> SetWithExpirationRequest {
+        SetWithExpirationRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            expiration,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> SetWithExpirationRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    /// Creates a set request, first checking the key and value against a provider's limits and the
+    /// expiration against the current time
+    pub fn validated(
+        key: &str,
+        value: &str,
+        expiration: Expiration,
+        limits: &KeyValidation,
+        now: Timestamp,
+    ) -> Result<SetWithExpirationRequest, WasccCodecError> {
+        limits.validate_key(key)?;
+        limits.validate_value(value.as_bytes())?;
+        expiration.validate(now)?;
+        Ok(SetWithExpirationRequest::new(key, value, expiration))
+    }
+}
+
+impl TryFrom<SetRequest> for SetWithExpirationRequest {
+    type Error = WasccCodecError;
+
+    fn try_from(req: SetRequest) -> Result<SetWithExpirationRequest, WasccCodecError> {
+        Ok(SetWithExpirationRequest {
+            expiration: Expiration::from_legacy_secs(req.expires_s)?,
+            key: req.key,
+            value: req.value,
+            write_concern: req.write_concern,
+        })
+    }
+}
+
+/// A request to set a value only if the key currently holds an expected value
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CompareAndSetRequest {
+    pub key: String,
+    /// The value the key must hold for the write to happen. None - the key must not exist
+    #[serde(default)]
+    pub expected: Option<String>,
+    pub value: String,
+    #[serde(flatten)]
+    pub expiration: Expiration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl CompareAndSetRequest {
+    /// Creates a new compare and set request
+    pub fn new(
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+        expiration: Expiration,
+    ) -> CompareAndSetRequest {
+        CompareAndSetRequest {
+            key: key.to_string(),
+            expected: expected.map(|s| s.to_string()),
+            value: value.to_string(),
+            expiration,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> CompareAndSetRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+}
+
+/// The result of a compare-and-set request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CompareAndSetResponse {
+    /// Indicates whether the value was written
+    pub swapped: bool,
+    /// The value the key holds after the request. None - the key does not exist
+    #[serde(default)]
+    pub current: Option<String>,
+    /// Present if the request carried a write concern and the value was written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<WriteReceipt>,
+}
+
+impl CompareAndSetResponse {
+    /// Creates a new compare and set response
+    pub fn new(swapped: bool, current: Option<&str>) -> CompareAndSetResponse {
+        CompareAndSetResponse {
+            swapped,
+            current: current.map(|s| s.to_string()),
+            receipt: None,
         }
     }
 }
@@ -63,142 +411,1698 @@ impl Sample for SetRequest {
 /// A request to delete a key
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DelRequest {
     pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for DelRequest {
+    fn sample() -> Self {
+        DelRequest {
+            key: "counter1".to_string(),
+            write_concern: None,
+        }
+    }
+}
+
+impl DelRequest {
+    /// Creates a new delete request
+    pub fn new(key: &str) -> DelRequest {
+        DelRequest {
+            key: key.to_string(),
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> DelRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct DelResponse {
     pub key: String,
+    /// Present if the request carried a write concern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<WriteReceipt>,
+}
+
+impl Sample for DelResponse {
+    fn sample() -> Self {
+        DelResponse {
+            key: "counter1".to_string(),
+            receipt: None,
+        }
+    }
+}
+
+impl DelResponse {
+    /// Creates a new delete response
+    pub fn new(key: &str) -> DelResponse {
+        DelResponse {
+            key: key.to_string(),
+            receipt: None,
+        }
+    }
+
+    /// Attaches the receipt describing which regions acknowledged the write
+    pub fn with_receipt(mut self, receipt: WriteReceipt) -> DelResponse {
+        self.receipt = Some(receipt);
+        self
+    }
 }
 
 /// Response to a set request
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetResponse {
     pub value: String,
+    /// Present if the request carried a write concern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<WriteReceipt>,
+}
+
+impl Sample for SetResponse {
+    fn sample() -> Self {
+        SetResponse {
+            value: "12".to_string(),
+            receipt: None,
+        }
+    }
+}
+
+impl SetResponse {
+    /// Creates a new set response
+    pub fn new(value: &str) -> SetResponse {
+        SetResponse {
+            value: value.to_string(),
+            receipt: None,
+        }
+    }
+
+    /// Attaches the receipt describing which regions acknowledged the write
+    pub fn with_receipt(mut self, receipt: WriteReceipt) -> SetResponse {
+        self.receipt = Some(receipt);
+        self
+    }
+}
+
+/// The durability a write requires before the provider reports it, for providers backed by stores
+/// replicated across regions. Writes without a write concern use the provider's default
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteConcern {
+    pub ack: AckLevel,
+    /// How long to wait for the acknowledgements. If it elapses, the provider responds with a receipt
+    /// whose concern is not met; the write is not rolled back and may still replicate. None - the
+    /// provider's default
+    #[serde(default)]
+    pub timeout: Option<DurationMs>,
+}
+
+impl WriteConcern {
+    pub fn local() -> WriteConcern {
+        WriteConcern {
+            ack: AckLevel::Local,
+            timeout: None,
+        }
+    }
+
+    pub fn quorum() -> WriteConcern {
+        WriteConcern {
+            ack: AckLevel::Quorum,
+            timeout: None,
+        }
+    }
+
+    pub fn all_regions() -> WriteConcern {
+        WriteConcern {
+            ack: AckLevel::AllRegions,
+            timeout: None,
+        }
+    }
+
+    /// Sets how long the provider waits for the acknowledgements
+    pub fn with_timeout(self, timeout: DurationMs) -> WriteConcern {
+        WriteConcern {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Indicates whether the given number of region acknowledgements, out of the given number of
+    /// regions, satisfies this concern
+    pub fn is_satisfied_by(&self, acks: usize, regions: usize) -> bool {
+        match self.ack {
+            AckLevel::Local => acks >= 1,
+            AckLevel::Quorum => acks > regions / 2,
+            AckLevel::AllRegions => acks >= regions.max(1),
+        }
+    }
+}
+
+/// The acknowledgements a write waits for
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum AckLevel {
+    /// The region that received the write has persisted it
+    Local,
+    /// A majority of regions have persisted the write
+    Quorum,
+    /// Every region has persisted the write
+    AllRegions,
+}
+
+/// Reports how far a write had replicated when the provider responded
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteReceipt {
+    /// The regions that acknowledged the write, in the order they did so
+    #[serde(default)]
+    pub acks: Vec<RegionAck>,
+    /// The number of regions the store replicates to
+    pub regions: u32,
+    /// Indicates whether the acknowledgements satisfy the request's write concern
+    pub concern_met: bool,
+}
+
+impl WriteReceipt {
+    /// Creates a receipt for the given acknowledgements, checking them against a write concern
+    pub fn new(concern: &WriteConcern, acks: Vec<RegionAck>, regions: u32) -> WriteReceipt {
+        WriteReceipt {
+            concern_met: concern.is_satisfied_by(acks.len(), regions as usize),
+            acks,
+            regions,
+        }
+    }
+}
+
+/// A region's acknowledgement of a write
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionAck {
+    pub region: String,
+    pub acked_at: Timestamp,
 }
 
 /// A request to perform an atomic add operation
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AddRequest {
     pub key: String,
     pub value: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for AddRequest {
+    fn sample() -> Self {
+        AddRequest {
+            key: "counter1".to_string(),
+            value: 5,
+            write_concern: None,
+        }
+    }
+}
+
+impl AddRequest {
+    /// Creates a new add request
+    pub fn new(key: &str, value: i32) -> AddRequest {
+        AddRequest {
+            key: key.to_string(),
+            value,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> AddRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
 }
 
 /// Result of an atomic add operation
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct AddResponse {
     pub value: i32,
 }
 
+impl Sample for AddResponse {
+    fn sample() -> Self {
+        AddResponse { value: 17 }
+    }
+}
+
+impl AddResponse {
+    /// Creates a new add response
+    pub fn new(value: i32) -> AddResponse {
+        AddResponse { value }
+    }
+}
+
 /// A request to add an item to the end of a list
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListPushRequest {
     pub key: String,
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for ListPushRequest {
+    fn sample() -> Self {
+        ListPushRequest {
+            key: "recent-orders".to_string(),
+            value: "order-1042".to_string(),
+            write_concern: None,
+        }
+    }
+}
+
+impl ListPushRequest {
+    /// Creates a new list push request
+    pub fn new(key: &str, value: &str) -> ListPushRequest {
+        ListPushRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> ListPushRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
 }
 
 /// A request to delete all occurences of an item from a list
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListDelItemRequest {
     pub key: String,
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for ListDelItemRequest {
+    fn sample() -> Self {
+        ListDelItemRequest {
+            key: "recent-orders".to_string(),
+            value: "order-1039".to_string(),
+            write_concern: None,
+        }
+    }
+}
+
+impl ListDelItemRequest {
+    /// Creates a new request to delete an item from a list
+    pub fn new(key: &str, value: &str) -> ListDelItemRequest {
+        ListDelItemRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> ListDelItemRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
 }
 
 /// A request to clear a list at a given key
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListClearRequest {
     pub key: String,
 }
 
+impl Sample for ListClearRequest {
+    fn sample() -> Self {
+        ListClearRequest {
+            key: "recent-orders".to_string(),
+        }
+    }
+}
+
+impl ListClearRequest {
+    /// Creates a new list clear request
+    pub fn new(key: &str) -> ListClearRequest {
+        ListClearRequest {
+            key: key.to_string(),
+        }
+    }
+}
+
 /// A request to retrieve a range of values from a list
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListRangeRequest {
     pub key: String,
     pub start: i32,
     pub stop: i32,
 }
 
+impl Sample for ListRangeRequest {
+    fn sample() -> Self {
+        ListRangeRequest {
+            key: "recent-orders".to_string(),
+            start: 0,
+            stop: 9,
+        }
+    }
+}
+
+impl ListRangeRequest {
+    /// Creates a new list range request
+    pub fn new(key: &str, start: i32, stop: i32) -> ListRangeRequest {
+        ListRangeRequest {
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+}
+
 /// List of values returned from a range request
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListRangeResponse {
     #[serde(default)]
     pub values: Vec<String>,
 }
 
+impl Sample for ListRangeResponse {
+    fn sample() -> Self {
+        ListRangeResponse {
+            values: vec!["order-1042".to_string(), "order-1041".to_string()],
+        }
+    }
+}
+
+impl ListRangeResponse {
+    /// Creates a new list range response
+    pub fn new(values: Vec<String>) -> ListRangeResponse {
+        ListRangeResponse { values }
+    }
+}
+
 /// Return response from non-range list requests like push and clear
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct ListResponse {
     pub new_count: i32,
 }
 
+impl Sample for ListResponse {
+    fn sample() -> Self {
+        ListResponse { new_count: 3 }
+    }
+}
+
+impl ListResponse {
+    /// Creates a new list response
+    pub fn new(new_count: i32) -> ListResponse {
+        ListResponse { new_count }
+    }
+}
+
 /// Request to add an item to a set
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetAddRequest {
     pub key: String,
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for SetAddRequest {
+    fn sample() -> Self {
+        SetAddRequest {
+            key: "online-users".to_string(),
+            value: "alice".to_string(),
+            write_concern: None,
+        }
+    }
+}
+
+impl SetAddRequest {
+    /// Creates a new set add request
+    pub fn new(key: &str, value: &str) -> SetAddRequest {
+        SetAddRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> SetAddRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
 }
 
 /// Request to remove a specific value from a set
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetRemoveRequest {
     pub key: String,
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
 }
 
-/// Request to query the contents of a set
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct SetQueryRequest {
-    pub key: String,
+impl Sample for SetRemoveRequest {
+    fn sample() -> Self {
+        SetRemoveRequest {
+            key: "online-users".to_string(),
+            value: "bob".to_string(),
+            write_concern: None,
+        }
+    }
 }
 
-/// Response to an operation that requests members of a list (query, intersect, union)
+impl SetRemoveRequest {
+    /// Creates a new set remove request
+    pub fn new(key: &str, value: &str) -> SetRemoveRequest {
+        SetRemoveRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> SetRemoveRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+}
+
+/// Request to query the contents of a set
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SetQueryRequest {
+    pub key: String,
+}
+
+impl Sample for SetQueryRequest {
+    fn sample() -> Self {
+        SetQueryRequest {
+            key: "online-users".to_string(),
+        }
+    }
+}
+
+impl SetQueryRequest {
+    /// Creates a new set query request
+    pub fn new(key: &str) -> SetQueryRequest {
+        SetQueryRequest {
+            key: key.to_string(),
+        }
+    }
+}
+
+/// Response to an operation that requests members of a list (query, intersect, union)
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetQueryResponse {
     #[serde(default)]
     pub values: Vec<String>,
 }
 
+impl Sample for SetQueryResponse {
+    fn sample() -> Self {
+        SetQueryResponse {
+            values: vec!["alice".to_string(), "carol".to_string()],
+        }
+    }
+}
+
+impl SetQueryResponse {
+    /// Creates a new set query response
+    pub fn new(values: Vec<String>) -> SetQueryResponse {
+        SetQueryResponse { values }
+    }
+}
+
 /// Request for the intersection of multiple sets
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetIntersectionRequest {
     #[serde(default)]
     pub keys: Vec<String>,
 }
 
+impl Sample for SetIntersectionRequest {
+    fn sample() -> Self {
+        SetIntersectionRequest {
+            keys: vec!["online-users".to_string(), "admins".to_string()],
+        }
+    }
+}
+
+impl SetIntersectionRequest {
+    /// Creates a new set intersection request
+    pub fn new(keys: Vec<String>) -> SetIntersectionRequest {
+        SetIntersectionRequest { keys }
+    }
+}
+
 /// Request for the union of multiple sets
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetUnionRequest {
     #[serde(default)]
     pub keys: Vec<String>,
 }
 
+impl Sample for SetUnionRequest {
+    fn sample() -> Self {
+        SetUnionRequest {
+            keys: vec!["online-users".to_string(), "admins".to_string()],
+        }
+    }
+}
+
+impl SetUnionRequest {
+    /// Creates a new set union request
+    pub fn new(keys: Vec<String>) -> SetUnionRequest {
+        SetUnionRequest { keys }
+    }
+}
+
 /// Response to a set query, add, or delete
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct SetOperationResponse {
     pub new_count: i32,
 }
 
+impl Sample for SetOperationResponse {
+    fn sample() -> Self {
+        SetOperationResponse { new_count: 2 }
+    }
+}
+
+impl SetOperationResponse {
+    /// Creates a new set operation response
+    pub fn new(new_count: i32) -> SetOperationResponse {
+        SetOperationResponse { new_count }
+    }
+}
+
 /// Test for the existence of a key
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct KeyExistsQuery {
     pub key: String,
 }
+
+impl Sample for KeyExistsQuery {
+    fn sample() -> Self {
+        KeyExistsQuery {
+            key: "counter1".to_string(),
+        }
+    }
+}
+
+impl KeyExistsQuery {
+    /// Creates a new key exists query
+    pub fn new(key: &str) -> KeyExistsQuery {
+        KeyExistsQuery {
+            key: key.to_string(),
+        }
+    }
+}
+
+/// A request to append bytes to the end of the value stored at a key. If the key does
+/// not exist, it is created with the supplied bytes as its value
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AppendRequest {
+    pub key: String,
+    /// The raw bytes to append to the existing value
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub value: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl Sample for AppendRequest {
+    fn sample() -> Self {
+        AppendRequest {
+            key: "log1".to_string(),
+            value: b"line of text\n".to_vec(),
+            write_concern: None,
+        }
+    }
+}
+
+impl AppendRequest {
+    /// Creates a new append request
+    pub fn new(key: &str, value: Vec<u8>) -> AppendRequest {
+        AppendRequest {
+            key: key.to_string(),
+            value,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> AppendRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    /// Creates an append request, first checking the key and appended bytes against a provider's limits.
+    /// The provider may still reject the request if the combined value exceeds its limits
+    pub fn validated(
+        key: &str,
+        value: Vec<u8>,
+        limits: &KeyValidation,
+    ) -> Result<AppendRequest, WasccCodecError> {
+        limits.validate_key(key)?;
+        limits.validate_value(&value)?;
+        Ok(AppendRequest::new(key, value))
+    }
+}
+
+/// Result of an append operation
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AppendResponse {
+    /// The length, in bytes, of the value after the append
+    pub new_length: u64,
+}
+
+impl AppendResponse {
+    /// Creates a new append response
+    pub fn new(new_length: u64) -> AppendResponse {
+        AppendResponse { new_length }
+    }
+}
+
+/// A request to retrieve a byte range of the value stored at a key. Offsets are inclusive,
+/// and negative offsets are counted from the end of the value (-1 is the last byte)
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct GetRangeRequest {
+    pub key: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl GetRangeRequest {
+    /// Creates a new get range request
+    pub fn new(key: &str, start: i64, end: i64) -> GetRangeRequest {
+        GetRangeRequest {
+            key: key.to_string(),
+            start,
+            end,
+        }
+    }
+}
+
+/// The bytes returned from a range request
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct GetRangeResponse {
+    /// The requested portion of the value. Out of range offsets are truncated to the value's bounds
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub value: Vec<u8>,
+    /// Indicates whether the key existed
+    pub exists: bool,
+}
+
+impl GetRangeResponse {
+    /// Creates a new get range response
+    pub fn new(value: Vec<u8>, exists: bool) -> GetRangeResponse {
+        GetRangeResponse { value, exists }
+    }
+}
+
+/// A request to retrieve part of a JSON document stored at a key. Paths use the JSONPath-like syntax
+/// common to document-capable stores: `$` is the document root, `.name` selects an object member,
+/// and `[n]` selects an array element, e.g. `$.user.addresses[0].city`
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct JsonGetRequest {
+    pub key: String,
+    /// The path of the sub-document to retrieve
+    pub path: String,
+}
+
+impl JsonGetRequest {
+    /// Creates a new json get request
+    pub fn new(key: &str, path: &str) -> JsonGetRequest {
+        JsonGetRequest {
+            key: key.to_string(),
+            path: path.to_string(),
+        }
+    }
+}
+
+/// The result of a JSON get request
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct JsonGetResponse {
+    /// The sub-document at the requested path, as JSON text
+    pub value: String,
+    /// Indicates whether both the key and the path existed
+    pub exists: bool,
+}
+
+impl JsonGetResponse {
+    /// Creates a new json get response
+    pub fn new(value: &str, exists: bool) -> JsonGetResponse {
+        JsonGetResponse {
+            value: value.to_string(),
+            exists,
+        }
+    }
+}
+
+/// A request to replace part of a JSON document stored at a key, using the same path syntax as `JsonGetRequest`.
+/// Setting the root path (`$`) on a missing key creates a new document
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct JsonSetRequest {
+    pub key: String,
+    /// The path of the sub-document to set
+    pub path: String,
+    /// The new value for the sub-document, as JSON text
+    pub value: String,
+    /// The condition under which the value is set, i.e. Redis' `NX` and `XX`
+    #[serde(default)]
+    pub condition: WriteCondition,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_concern: Option<WriteConcern>,
+}
+
+impl JsonSetRequest {
+    /// Creates a new json set request
+    pub fn new(key: &str, path: &str, value: &str) -> JsonSetRequest {
+        JsonSetRequest {
+            key: key.to_string(),
+            path: path.to_string(),
+            value: value.to_string(),
+            condition: WriteCondition::Always,
+            write_concern: None,
+        }
+    }
+
+    /// Requests that the write be acknowledged according to the given write concern
+    pub fn with_write_concern(mut self, write_concern: WriteConcern) -> JsonSetRequest {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn only_if_missing(mut self) -> JsonSetRequest {
+        self.condition = WriteCondition::IfMissing;
+        self
+    }
+
+    pub fn only_if_exists(mut self) -> JsonSetRequest {
+        self.condition = WriteCondition::IfExists;
+        self
+    }
+}
+
+/// The condition under which a conditional write takes place
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteCondition {
+    #[default]
+    Always,
+    /// Only write if nothing currently exists at the target
+    IfMissing,
+    /// Only write if something already exists at the target
+    IfExists,
+}
+
+/// The result of a JSON set request
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct JsonSetResponse {
+    /// Indicates whether the value was written. This is false when a condition on the request was not met
+    pub updated: bool,
+}
+
+impl JsonSetResponse {
+    /// Creates a new json set response
+    pub fn new(updated: bool) -> JsonSetResponse {
+        JsonSetResponse { updated }
+    }
+}
+
+/// A request to add elements to the HyperLogLog stored at a key, creating it if it does not exist.
+/// A HyperLogLog estimates the number of distinct elements added to it using a small, fixed amount
+/// of memory, at the cost of a standard error of roughly 1%
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PfAddRequest {
+    pub key: String,
+    #[serde(default)]
+    pub elements: Vec<String>,
+}
+
+impl Sample for PfAddRequest {
+    fn sample() -> Self {
+        PfAddRequest::new("visitors:2020-07-01", &["alice", "bob", "carol"])
+    }
+}
+
+impl PfAddRequest {
+    /// Creates a new HyperLogLog add request
+    pub fn new(key: &str, elements: &[&str]) -> PfAddRequest {
+        PfAddRequest {
+            key: key.to_string(),
+            elements: elements.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+/// The result of adding elements to a HyperLogLog
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PfAddResponse {
+    /// Indicates whether the estimated cardinality changed as a result of the request
+    pub changed: bool,
+}
+
+impl PfAddResponse {
+    /// Creates a new HyperLogLog add response
+    pub fn new(changed: bool) -> PfAddResponse {
+        PfAddResponse { changed }
+    }
+}
+
+/// A request for the estimated number of distinct elements added to one or more HyperLogLogs. When
+/// more than one key is given, the estimate is for the union of their elements. Missing keys count
+/// as empty
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PfCountRequest {
+    pub keys: Vec<String>,
+}
+
+impl PfCountRequest {
+    /// Creates a new HyperLogLog count request
+    pub fn new(keys: &[&str]) -> PfCountRequest {
+        PfCountRequest {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+}
+
+/// The estimated cardinality of one or more HyperLogLogs
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PfCountResponse {
+    pub count: u64,
+}
+
+impl PfCountResponse {
+    /// Creates a new HyperLogLog count response
+    pub fn new(count: u64) -> PfCountResponse {
+        PfCountResponse { count }
+    }
+}
+
+/// A request to merge several HyperLogLogs into the one stored at the destination key, which is
+/// created if it does not exist. The destination's existing elements are kept
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PfMergeRequest {
+    pub destination: String,
+    pub sources: Vec<String>,
+}
+
+impl PfMergeRequest {
+    /// Creates a new HyperLogLog merge request
+    pub fn new(destination: &str, sources: &[&str]) -> PfMergeRequest {
+        PfMergeRequest {
+            destination: destination.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// A request to create an empty bloom filter at a key, sized for the given capacity and false
+/// positive rate. Adding items to a key without a filter creates one with provider-defined defaults
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BloomReserveRequest {
+    pub key: String,
+    /// The desired probability of a false positive, between 0 and 1 exclusive, e.g. `0.001`
+    pub error_rate: f64,
+    /// The number of items the filter is expected to hold. Providers may grow or reject additions
+    /// beyond this, and the false positive rate is not guaranteed once it is exceeded
+    pub capacity: u64,
+}
+
+impl BloomReserveRequest {
+    /// Creates a new bloom reserve request
+    pub fn new(key: &str, error_rate: f64, capacity: u64) -> BloomReserveRequest {
+        BloomReserveRequest {
+            key: key.to_string(),
+            error_rate,
+            capacity,
+        }
+    }
+}
+
+/// A request to add items to the bloom filter stored at a key
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BloomAddRequest {
+    pub key: String,
+    pub items: Vec<String>,
+}
+
+impl BloomAddRequest {
+    /// Creates a new bloom add request
+    pub fn new(key: &str, items: &[&str]) -> BloomAddRequest {
+        BloomAddRequest {
+            key: key.to_string(),
+            items: items.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+/// The result of adding items to a bloom filter
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BloomAddResponse {
+    /// For each item in the request, in order, indicates whether it was newly added. False means the
+    /// item may already have been present
+    pub added: Vec<bool>,
+}
+
+impl BloomAddResponse {
+    /// Creates a new bloom add response
+    pub fn new(added: Vec<bool>) -> BloomAddResponse {
+        BloomAddResponse { added }
+    }
+}
+
+/// A request to check whether items may be present in the bloom filter stored at a key
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BloomCheckRequest {
+    pub key: String,
+    pub items: Vec<String>,
+}
+
+impl BloomCheckRequest {
+    /// Creates a new bloom check request
+    pub fn new(key: &str, items: &[&str]) -> BloomCheckRequest {
+        BloomCheckRequest {
+            key: key.to_string(),
+            items: items.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+/// The result of checking items against a bloom filter
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BloomCheckResponse {
+    /// For each item in the request, in order, indicates whether it may be present. False is always
+    /// accurate, while true may be a false positive. Every item is absent from a missing key
+    pub present: Vec<bool>,
+}
+
+impl BloomCheckResponse {
+    /// Creates a new bloom check response
+    pub fn new(present: Vec<bool>) -> BloomCheckResponse {
+        BloomCheckResponse { present }
+    }
+}
+
+/// A request to stream a snapshot of the store (or the keys beneath a prefix) to the guest
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ExportRequest {
+    /// Only keys beginning with this prefix are exported. An empty prefix exports the entire store
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// The preferred maximum size of each chunk in bytes. Chunks only ever contain whole entries, so a
+    /// single large entry may produce a chunk larger than this
+    pub chunk_size: u64,
+}
+
+impl Sample for ExportRequest {
+    fn sample() -> Self {
+        ExportRequest {
+            prefix: "user:".to_string(),
+            format: ExportFormat::Msgpack,
+            chunk_size: 65536,
+        }
+    }
+}
+
+impl ExportRequest {
+    /// Creates a new export request
+    pub fn new(prefix: &str, format: ExportFormat, chunk_size: u64) -> ExportRequest {
+        ExportRequest {
+            prefix: prefix.to_string(),
+            format,
+            chunk_size,
+        }
+    }
+}
+
+/// The provider's response to an export request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ExportStarted {
+    /// Identifies the export in each of the chunks delivered to the guest
+    pub export_id: String,
+    /// The number of keys that will be exported, if the provider knows it in advance
+    #[serde(default)]
+    pub total_keys: Option<u64>,
+}
+
+impl ExportStarted {
+    /// Creates a new response to an export request
+    pub fn new(export_id: &str) -> ExportStarted {
+        ExportStarted {
+            export_id: export_id.to_string(),
+            total_keys: None,
+        }
+    }
+
+    /// Sets the number of keys the export will contain
+    pub fn with_total_keys(mut self, total_keys: u64) -> ExportStarted {
+        self.total_keys = Some(total_keys);
+        self
+    }
+}
+
+/// The encoding of the entries contained in export and import chunks
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// A message pack array of `ExportEntry` records
+    #[default]
+    Msgpack,
+    /// One JSON-encoded `ExportEntry` record per line
+    JsonLines,
+}
+
+/// A single key and its value, as contained in an export or import chunk
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ExportEntry {
+    pub key: String,
+    pub value: ExportedValue,
+    /// The remaining time-to-live of the key, if it has an expiration
+    #[serde(default)]
+    pub expires_in: Option<DurationMs>,
+}
+
+impl ExportEntry {
+    /// Creates a new export entry
+    pub fn new(key: &str, value: ExportedValue) -> ExportEntry {
+        ExportEntry {
+            key: key.to_string(),
+            value,
+            expires_in: None,
+        }
+    }
+
+    /// Sets the remaining time-to-live of the exported key
+    pub fn with_expires_in(mut self, expires_in: DurationMs) -> ExportEntry {
+        self.expires_in = Some(expires_in);
+        self
+    }
+}
+
+/// The value of an exported key
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportedValue {
+    String(String),
+    List(Vec<String>),
+    Set(Vec<String>),
+}
+
+/// Encodes a set of entries into the body of an export or import chunk
+pub fn encode_entries(
+    entries: &[ExportEntry],
+    format: ExportFormat,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    match format {
+        ExportFormat::Msgpack => crate::serialize(entries),
+        ExportFormat::JsonLines => {
+            let mut buf = Vec::new();
+            for entry in entries {
+                serde_json::to_writer(&mut buf, entry)?;
+                buf.push(b'\n');
+            }
+            Ok(buf)
+        }
+    }
+}
+
+/// Decodes the entries contained in the body of an export or import chunk
+pub fn decode_entries(
+    bytes: &[u8],
+    format: ExportFormat,
+) -> Result<Vec<ExportEntry>, Box<dyn Error>> {
+    match format {
+        ExportFormat::Msgpack => crate::deserialize(bytes),
+        ExportFormat::JsonLines => bytes
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .map(|line| serde_json::from_slice(line).map_err(|e| e.into()))
+            .collect(),
+    }
+}
+
+/// A chunk of an export in progress, delivered from the provider to the guest. The chunking scheme
+/// mirrors the blobstore's `FileChunk`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ExportChunk {
+    pub export_id: String,
+    /// A sequence number that can be used for retry and ordering logic
+    pub sequence_no: u64,
+    pub format: ExportFormat,
+    /// The encoded entries contained in this chunk, see `decode_entries`
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub chunk_bytes: Vec<u8>,
+    /// Indicates that this is the final chunk of the export
+    pub last: bool,
+}
+
+impl ExportChunk {
+    /// Creates a new export chunk
+    pub fn new(
+        export_id: &str,
+        sequence_no: u64,
+        format: ExportFormat,
+        chunk_bytes: Vec<u8>,
+        last: bool,
+    ) -> ExportChunk {
+        ExportChunk {
+            export_id: export_id.to_string(),
+            sequence_no,
+            format,
+            chunk_bytes,
+            last,
+        }
+    }
+
+    /// Decodes the entries contained in this chunk
+    pub fn entries(&self) -> Result<Vec<ExportEntry>, Box<dyn Error>> {
+        decode_entries(&self.chunk_bytes, self.format)
+    }
+
+    /// Converts this chunk into an import chunk that loads the same entries into another store
+    pub fn into_import(self, import_id: &str, overwrite: bool) -> ImportChunk {
+        ImportChunk {
+            import_id: import_id.to_string(),
+            sequence_no: self.sequence_no,
+            format: self.format,
+            chunk_bytes: self.chunk_bytes,
+            last: self.last,
+            overwrite,
+        }
+    }
+}
+
+/// A chunk of entries to be bulk loaded into the store
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ImportChunk {
+    /// Identifies the import that this chunk belongs to, chosen by the guest
+    pub import_id: String,
+    /// A sequence number that can be used for retry and ordering logic
+    pub sequence_no: u64,
+    pub format: ExportFormat,
+    /// The encoded entries contained in this chunk, see `encode_entries`
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub chunk_bytes: Vec<u8>,
+    /// Indicates that this is the final chunk of the import
+    pub last: bool,
+    /// Replace keys that already exist in the store. When false, existing keys are skipped
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl ImportChunk {
+    /// Creates a new import chunk
+    pub fn new(
+        import_id: &str,
+        sequence_no: u64,
+        format: ExportFormat,
+        chunk_bytes: Vec<u8>,
+        last: bool,
+    ) -> ImportChunk {
+        ImportChunk {
+            import_id: import_id.to_string(),
+            sequence_no,
+            format,
+            chunk_bytes,
+            last,
+            overwrite: false,
+        }
+    }
+
+    pub fn overwrite(mut self) -> ImportChunk {
+        self.overwrite = true;
+        self
+    }
+}
+
+/// The result of importing a single chunk
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ImportResult {
+    /// The number of keys written by this chunk
+    pub imported: u64,
+    /// The number of keys skipped because they already existed
+    pub skipped: u64,
+}
+
+impl ImportResult {
+    /// Creates a new import result
+    pub fn new(imported: u64, skipped: u64) -> ImportResult {
+        ImportResult { imported, skipped }
+    }
+}
+
+/// A request to be notified of changes to the keys beneath a prefix, so that the guest can keep a local
+/// replica (near-cache) of those keys
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SubscribeKeyspace {
+    /// Only changes to keys beginning with this prefix are delivered. An empty prefix subscribes to the
+    /// entire store
+    #[serde(default)]
+    pub prefix: String,
+    /// Deliver the new value of a written key as a `CacheFillEvent`. When false, writes are delivered as
+    /// invalidations and the guest re-reads the key on its next access
+    #[serde(default)]
+    pub fill: bool,
+}
+
+impl Sample for SubscribeKeyspace {
+    fn sample() -> Self {
+        SubscribeKeyspace {
+            prefix: "user:".to_string(),
+            fill: true,
+        }
+    }
+}
+
+impl SubscribeKeyspace {
+    /// Creates a request to receive changes to the keys under the given prefix
+    pub fn new(prefix: &str, fill: bool) -> SubscribeKeyspace {
+        SubscribeKeyspace {
+            prefix: prefix.to_string(),
+            fill,
+        }
+    }
+}
+
+/// A request to stop receiving changes for a prefix subscribed with `SubscribeKeyspace`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct UnsubscribeKeyspace {
+    #[serde(default)]
+    pub prefix: String,
+}
+
+impl UnsubscribeKeyspace {
+    /// Creates a request to stop receiving changes for the given prefix
+    pub fn new(prefix: &str) -> UnsubscribeKeyspace {
+        UnsubscribeKeyspace {
+            prefix: prefix.to_string(),
+        }
+    }
+}
+
+/// The new value of a key written in a subscribed keyspace
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CacheFillEvent {
+    /// The prefix of the subscription this event was delivered for
+    pub prefix: String,
+    /// Increases with every event delivered for the subscription. A guest that has applied an event must
+    /// ignore fills with a lower sequence number, which describe older writes
+    pub sequence_no: u64,
+    pub entry: ExportEntry,
+}
+
+impl CacheFillEvent {
+    /// Creates a new cache fill event
+    pub fn new(prefix: &str, sequence_no: u64, entry: ExportEntry) -> CacheFillEvent {
+        CacheFillEvent {
+            prefix: prefix.to_string(),
+            sequence_no,
+            entry,
+        }
+    }
+}
+
+/// Notifies the guest that cached keys in a subscribed keyspace are stale and must be dropped
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct CacheInvalidateEvent {
+    /// The prefix of the subscription this event was delivered for
+    pub prefix: String,
+    /// Increases with every event delivered for the subscription
+    pub sequence_no: u64,
+    /// The keys that were changed, removed or expired
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Every key beneath the prefix must be dropped, e.g. because the store was cleared or the provider
+    /// may have missed changes while reconnecting to it
+    #[serde(default)]
+    pub all: bool,
+}
+
+impl CacheInvalidateEvent {
+    /// Creates a new cache invalidate event
+    pub fn new(prefix: &str, sequence_no: u64, keys: Vec<String>) -> CacheInvalidateEvent {
+        CacheInvalidateEvent {
+            prefix: prefix.to_string(),
+            sequence_no,
+            keys,
+            all: false,
+        }
+    }
+
+    /// Produces an event invalidating the entire keyspace of a subscription
+    pub fn all(prefix: &str, sequence_no: u64) -> CacheInvalidateEvent {
+        CacheInvalidateEvent {
+            prefix: prefix.to_string(),
+            sequence_no,
+            keys: vec![],
+            all: true,
+        }
+    }
+
+    /// Indicates whether a cached copy of the given key must be dropped
+    pub fn invalidates(&self, key: &str) -> bool {
+        if self.all {
+            key.starts_with(&self.prefix)
+        } else {
+            self.keys.iter().any(|k| k == key)
+        }
+    }
+}
+
+/// Key and value limits enforced by a key-value provider. Providers advertise these limits as an
+/// extension of their capability descriptor so that actors can validate keys and values before
+/// dispatching a request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct KeyValidation {
+    /// Maximum length of a key in bytes, 0 - no limit
+    pub max_key_bytes: u32,
+    /// Maximum size of a value in bytes, 0 - no limit
+    pub max_value_bytes: u64,
+    /// The set of characters permitted in keys
+    pub charset: KeyCharset,
+}
+
+/// The set of characters a provider permits in keys
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCharset {
+    /// Any UTF-8 string is a valid key
+    #[default]
+    Any,
+    /// Printable ASCII characters, including the space character
+    PrintableAscii,
+    /// ASCII letters, digits, and the characters `-`, `_`, `.`, `:`, and `/`
+    SafeIdentifier,
+}
+
+impl KeyCharset {
+    /// Indicates whether the given character is permitted by this character set
+    pub fn permits(&self, c: char) -> bool {
+        match self {
+            KeyCharset::Any => true,
+            KeyCharset::PrintableAscii => c.is_ascii() && !c.is_ascii_control(),
+            KeyCharset::SafeIdentifier => c.is_ascii_alphanumeric() || "-_.:/".contains(c),
+        }
+    }
+}
+
+impl KeyValidation {
+    /// The name of the capability descriptor extension holding a provider's limits
+    pub const EXTENSION: &'static str = "keyValidation";
+
+    /// Creates a new set of key and value limits
+    pub fn new(max_key_bytes: u32, max_value_bytes: u64, charset: KeyCharset) -> KeyValidation {
+        KeyValidation {
+            max_key_bytes,
+            max_value_bytes,
+            charset,
+        }
+    }
+
+    /// Verifies that a key is non-empty, within the maximum length, and contains only permitted characters
+    pub fn validate_key(&self, key: &str) -> Result<(), WasccCodecError> {
+        if key.is_empty() {
+            return Err(WasccCodecError::EmptyKey);
+        }
+        if self.max_key_bytes > 0 && key.len() > self.max_key_bytes as usize {
+            return Err(WasccCodecError::KeyTooLong {
+                length: key.len(),
+                max: self.max_key_bytes as usize,
+            });
+        }
+        match key.chars().find(|c| !self.charset.permits(*c)) {
+            Some(character) => Err(WasccCodecError::InvalidKeyCharacter {
+                key: key.to_string(),
+                character,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Verifies that a value is within the maximum value size
+    pub fn validate_value(&self, value: &[u8]) -> Result<(), WasccCodecError> {
+        if self.max_value_bytes > 0 && value.len() as u64 > self.max_value_bytes {
+            Err(WasccCodecError::ValueTooLarge {
+                length: value.len() as u64,
+                max: self.max_value_bytes,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Encodes these limits as a capability descriptor extension, to be added under `EXTENSION`
+    pub fn to_extension(&self) -> serde_json::Value {
+        // A struct of integers and a unit enum always has a JSON representation
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Reads the limits advertised in a provider's capability descriptor. Returns `None` if the
+    /// provider does not advertise any
+    pub fn from_descriptor(
+        descriptor: &CapabilityDescriptor,
+    ) -> Result<Option<KeyValidation>, WasccCodecError> {
+        descriptor
+            .extensions
+            .get(KeyValidation::EXTENSION)
+            .map(|v| {
+                serde_json::from_value(v.clone()).map_err(|e| WasccCodecError::MalformedPayload {
+                    reason: e.to_string(),
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        AddRequest, DelRequest, DelResponse, Expiration, KeyCharset, KeyValidation, RegionAck,
+        WriteConcern, WriteReceipt,
+    };
+    use crate::capabilities::CapabilityDescriptor;
+    use crate::core::{DurationMs, Timestamp};
+    use crate::errors::WasccCodecError;
+    use crate::{deserialize, deserialize_strict, serialize};
+
+    #[test]
+    fn key_validation_round_trips_through_descriptor_extensions() {
+        let limits = KeyValidation::new(250, 5, KeyCharset::SafeIdentifier);
+        let descriptor = CapabilityDescriptor::builder()
+            .id("wascc:keyvalue")
+            .with_extension(KeyValidation::EXTENSION, limits.to_extension())
+            .build();
+        let advertised = KeyValidation::from_descriptor(&descriptor)
+            .unwrap()
+            .unwrap();
+        assert_eq!(advertised, limits);
+        assert_eq!(
+            advertised.validate_value(b"too long"),
+            Err(WasccCodecError::ValueTooLarge { length: 8, max: 5 })
+        );
+        assert_eq!(
+            KeyValidation::from_descriptor(&CapabilityDescriptor::default()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn legacy_seconds_map_to_expirations() {
+        assert_eq!(Expiration::from_legacy_secs(0), Ok(Expiration::Never));
+        assert_eq!(
+            Expiration::from_legacy_secs(30),
+            Ok(Expiration::after(DurationMs::from_secs(30)))
+        );
+        assert!(matches!(
+            Expiration::from_legacy_secs(-1),
+            Err(WasccCodecError::InvalidExpiration { .. })
+        ));
+    }
+
+    #[test]
+    fn expirations_that_expire_immediately_are_rejected() {
+        let now = Timestamp::from_secs(1000);
+        assert!(Expiration::Never.validate(now).is_ok());
+        assert!(Expiration::at(Timestamp::from_secs(1001))
+            .validate(now)
+            .is_ok());
+        assert!(Expiration::at(now).validate(now).is_err());
+        assert!(Expiration::at(Timestamp::from_secs(999))
+            .validate(now)
+            .is_err());
+        assert!(Expiration::after(DurationMs::from_millis(1))
+            .validate(now)
+            .is_ok());
+        assert_eq!(
+            Expiration::after(DurationMs::from_millis(0)).validate(now),
+            Err(WasccCodecError::InvalidExpiration {
+                reason: "duration is zero".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn deadlines_saturate_instead_of_overflowing() {
+        let written_at = Timestamp::from_secs(1000);
+        assert_eq!(Expiration::Never.deadline(written_at), None);
+        assert_eq!(
+            Expiration::at(Timestamp::from_secs(5)).deadline(written_at),
+            Some(Timestamp::from_secs(5))
+        );
+        assert_eq!(
+            Expiration::after(DurationMs::from_secs(30)).deadline(written_at),
+            Some(Timestamp::from_secs(1030))
+        );
+        assert_eq!(
+            Expiration::after(DurationMs::from_millis(u64::MAX)).deadline(written_at),
+            Some(Timestamp::from_millis(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn write_concern_is_optional_on_the_wire() {
+        #[derive(Serialize)]
+        struct LegacyDel {
+            key: String,
+        }
+        let buf = serialize(LegacyDel {
+            key: "k".to_string(),
+        })
+        .unwrap();
+        let req = deserialize_strict::<DelRequest>(&buf).unwrap();
+        assert_eq!(req.write_concern, None);
+
+        let add = AddRequest::new("k", 1).with_write_concern(WriteConcern::local());
+        let buf = serialize(&add).unwrap();
+        assert_eq!(deserialize_strict::<AddRequest>(&buf).unwrap(), add);
+
+        let concern = WriteConcern::quorum();
+        let ack = |region: &str| RegionAck {
+            region: region.to_string(),
+            acked_at: Timestamp::from_secs(1),
+        };
+        let receipt = WriteReceipt::new(&concern, vec![ack("us-east"), ack("eu-west")], 3);
+        assert!(receipt.concern_met);
+        assert!(
+            !WriteReceipt::new(&WriteConcern::all_regions(), receipt.acks.clone(), 3).concern_met
+        );
+        let resp = DelResponse::new("k").with_receipt(receipt);
+        let buf = serialize(&resp).unwrap();
+        assert_eq!(deserialize::<DelResponse>(&buf).unwrap(), resp);
+    }
+}
@@ -0,0 +1,192 @@
+//! # Leaderboards
+//!
+//! This module contains data types for the `wascc:leaderboard` capability provider, which keeps ranked
+//! scores for members of named boards, e.g. players in a game. Providers are typically backed by sorted
+//! sets. Boards may be all-time or reset on a daily, weekly or monthly window
+
+use crate::core::Timestamp;
+use crate::Sample;
+
+/// Guest sends a SubmitScore to the capability provider, receives a MemberRank back
+pub const OP_SUBMIT_SCORE: &str = "SubmitScore";
+/// Guest sends a TopNQuery to the capability provider, receives RankedEntries back
+pub const OP_TOP_N: &str = "TopN";
+/// Guest sends a RankOfMemberQuery to the capability provider, receives a MemberRank back
+pub const OP_RANK_OF_MEMBER: &str = "RankOfMember";
+/// Guest sends a RemoveMember to the capability provider, lack of error indicates success
+pub const OP_REMOVE_MEMBER: &str = "RemoveMember";
+
+/// The period over which a board accumulates scores before starting afresh. Windows are aligned to UTC
+/// calendar boundaries, weeks start on Monday
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardWindow {
+    #[default]
+    AllTime,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Identifies a board and the window of it being addressed
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Board {
+    pub name: String,
+    #[serde(default)]
+    pub window: BoardWindow,
+    /// Any time within a past window, for querying that window's final standings. None - the current
+    /// window. Ignored for all-time boards
+    #[serde(default)]
+    pub at: Option<Timestamp>,
+}
+
+impl Board {
+    /// Refers to the current window of a board
+    pub fn new(name: &str, window: BoardWindow) -> Board {
+        Board {
+            name: name.to_string(),
+            window,
+            at: None,
+        }
+    }
+
+    /// Refers to the window of the board containing the given time
+    pub fn at(self, at: Timestamp) -> Board {
+        Board {
+            at: Some(at),
+            ..self
+        }
+    }
+}
+
+/// How a submitted score is combined with a member's existing score
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreMode {
+    /// Keeps whichever of the existing and submitted scores ranks higher
+    #[default]
+    Best,
+    /// Overwrites the existing score
+    Replace,
+    /// Adds the submitted score to the existing score
+    Increment,
+}
+
+/// The direction in which scores are ranked
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RankOrder {
+    /// Higher scores rank first, e.g. points
+    #[default]
+    Descending,
+    /// Lower scores rank first, e.g. lap times
+    Ascending,
+}
+
+/// Submits a member's score to the current window of a board
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitScore {
+    pub board: String,
+    #[serde(default)]
+    pub window: BoardWindow,
+    pub member: String,
+    pub score: f64,
+    #[serde(default)]
+    pub mode: ScoreMode,
+    #[serde(default)]
+    pub order: RankOrder,
+}
+
+impl Sample for SubmitScore {
+    fn sample() -> Self {
+        SubmitScore {
+            board: "arena".to_string(),
+            window: BoardWindow::Weekly,
+            member: "player-4711".to_string(),
+            score: 1820.0,
+            mode: ScoreMode::Best,
+            order: RankOrder::Descending,
+        }
+    }
+}
+
+/// A request for the highest ranked members of a board, optionally starting past the first `offset`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TopNQuery {
+    pub board: Board,
+    pub count: u32,
+    #[serde(default)]
+    pub offset: u32,
+    #[serde(default)]
+    pub order: RankOrder,
+}
+
+impl Sample for TopNQuery {
+    fn sample() -> Self {
+        TopNQuery {
+            board: Board::new("arena", BoardWindow::Weekly),
+            count: 10,
+            offset: 0,
+            order: RankOrder::Descending,
+        }
+    }
+}
+
+/// A request for a member's rank, along with the members ranked immediately around them
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankOfMemberQuery {
+    pub board: Board,
+    pub member: String,
+    /// The number of neighbours to include on either side of the member
+    #[serde(default)]
+    pub neighbours: u32,
+    #[serde(default)]
+    pub order: RankOrder,
+}
+
+/// A member's position on a board. Ranks start at 1, members with equal scores share a rank
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedEntry {
+    pub member: String,
+    pub score: f64,
+    pub rank: u64,
+}
+
+/// A contiguous run of entries on a board, highest ranked first
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedEntries {
+    #[serde(default)]
+    pub entries: Vec<RankedEntry>,
+    /// The number of members on the board
+    pub total_members: u64,
+}
+
+/// The rank of a single member. `entry` is None if the member has no score on the board
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberRank {
+    #[serde(default)]
+    pub entry: Option<RankedEntry>,
+    /// The members ranked immediately above and below the member, highest ranked first, including the
+    /// member's own entry
+    #[serde(default)]
+    pub neighbours: Vec<RankedEntry>,
+    /// The number of members on the board
+    pub total_members: u64,
+}
+
+/// Removes a member's score from the current window of a board
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveMember {
+    pub board: String,
+    #[serde(default)]
+    pub window: BoardWindow,
+    pub member: String,
+}
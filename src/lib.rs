@@ -22,6 +22,9 @@
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 /// The string used for the originator of messages dispatched by the host runtime
 pub const SYSTEM_ACTOR: &str = "system";
+/// The member name under which `deserialize_to_value` places base64-encoded binary values, e.g.
+/// `{"$binary": "AQID"}`
+pub const BINARY_TAG: &str = "$binary";
 
 #[macro_use]
 extern crate serde_derive;
@@ -30,7 +33,7 @@ extern crate log;
 extern crate rmp_serde as rmps;
 use rmps::{Deserializer, Serializer};
 use serde::{Deserialize, Serialize};
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 
 /// The standard function for serializing codec structs into a format that can be
 /// used for message exchange between actor and host. Use of any other function to
@@ -57,16 +60,368 @@ pub fn deserialize<'de, T: Deserialize<'de>>(
     }
 }
 
+/// A strict alternative to `deserialize` for callers that must not silently accept malformed payloads.
+/// In addition to the checks `deserialize` performs, the payload must not contain fields that `T` does
+/// not declare, and errors name the offending field by its path. Fields that are optional or declare a
+/// default may still be omitted, so payloads written by older peers remain acceptable
+pub fn deserialize_strict<T>(buf: &[u8]) -> ::std::result::Result<T, errors::WasccCodecError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    strict::from_slice(buf)
+}
+
+/// Serializes a JSON value tree into a payload, as `serialize` would serialize the typed value it
+/// represents. Objects consisting solely of a `BINARY_TAG` member are written as binary values. Used by
+/// generic actors, such as gateways, that route or transform payloads without knowing their types
+pub fn serialize_value(
+    value: &serde_json::Value,
+) -> ::std::result::Result<Vec<u8>, Box<dyn ::std::error::Error>> {
+    serialize(passthrough::ValueIn(value))
+}
+
+/// Deserializes a payload into a JSON value tree that `serialize_value` converts back into an equivalent
+/// payload. Binary values become objects with a single `BINARY_TAG` member holding their base64
+/// encoding. Payloads JSON cannot represent without loss fail to convert: maps with keys that are not
+/// strings, maps whose only member is a `BINARY_TAG` string, and non-finite floats
+pub fn deserialize_to_value(
+    buf: &[u8],
+) -> ::std::result::Result<serde_json::Value, Box<dyn ::std::error::Error>> {
+    deserialize::<passthrough::ValueOut>(buf).map(|v| v.0)
+}
+
+/// Computes the number of bytes an item will occupy when serialized with `serialize`, without
+/// allocating a buffer for the serialized output
+pub fn measure_serialized_size<T>(
+    item: &T,
+) -> ::std::result::Result<usize, Box<dyn ::std::error::Error>>
+where
+    T: Serialize,
+{
+    let mut counter = ByteCounter(0);
+    item.serialize(&mut Serializer::new(&mut counter).with_struct_map())?;
+    Ok(counter.0)
+}
+
+/// Verifies that an item's serialized size does not exceed the given limit, returning the
+/// size on success so callers can check a payload before dispatching it
+pub fn check_serialized_size<T>(
+    item: &T,
+    allowed: usize,
+) -> ::std::result::Result<usize, Box<dyn ::std::error::Error>>
+where
+    T: Serialize,
+{
+    let actual = measure_serialized_size(item)?;
+    if actual > allowed {
+        Err(Box::new(errors::WasccCodecError::PayloadTooLarge {
+            actual: actual as u64,
+            allowed: allowed as u64,
+        }))
+    } else {
+        Ok(actual)
+    }
+}
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 pub trait Sample {
     fn sample() -> Self;
 }
 
+pub mod access;
+pub mod analytics;
+pub mod attestation;
+pub mod barcode;
+pub mod batchjobs;
 pub mod blobstore;
+pub mod calendar;
 pub mod capabilities;
+pub mod compat;
+pub mod conformance;
+pub mod contract;
+pub mod coordination;
 pub mod core;
+pub mod crdt;
+pub mod datafeed;
+pub mod device;
+pub mod discovery;
+pub mod docextract;
+pub mod docgen;
+pub mod envelope;
+pub mod errors;
 pub mod eventstreams;
+pub mod exec;
 pub mod extras;
+mod fnv;
+pub mod graphql;
 pub mod http;
+pub mod intern;
 pub mod keyvalue;
+pub mod leaderboard;
 pub mod logging;
 pub mod messaging;
+pub mod metering;
+pub mod mime;
+pub mod net;
+pub mod operations;
+pub mod outbox;
+mod passthrough;
+pub mod pipeline;
+pub mod plugin;
+mod preview;
+pub mod probes;
+pub mod remote_provider;
+pub mod session;
+mod sha256;
+pub mod sqldb;
+pub mod state;
+mod strict;
+pub mod telephony;
+pub mod textgen;
+mod value;
+pub mod vectorstore;
+pub mod webhooks;
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check_serialized_size, deserialize, deserialize_strict, deserialize_to_value,
+        measure_serialized_size, serialize, serialize_value, Sample,
+    };
+    use crate::core::Timestamp;
+    use crate::errors::WasccCodecError;
+    use crate::http::{Method, Request};
+    use crate::keyvalue::{
+        AppendRequest, ExportChunk, ExportFormat, RegionAck, WriteConcern, WriteReceipt,
+    };
+    use crate::messaging::{BrokerMessage, SubscribeRequest};
+    use std::collections::HashMap;
+
+    #[test]
+    fn measured_size_matches_serialized_length() {
+        let req = Request::sample();
+        let size = measure_serialized_size(&req).unwrap();
+        assert_eq!(size, serialize(&req).unwrap().len());
+        assert!(check_serialized_size(&req, size).is_ok());
+        assert!(check_serialized_size(&req, size - 1).is_err());
+    }
+
+    #[test]
+    fn strict_deserialization_identifies_offending_field() {
+        #[derive(Serialize)]
+        struct Extra {
+            key: String,
+            value: String,
+            bogus: bool,
+        }
+        #[derive(Serialize)]
+        struct Partial {
+            value: String,
+        }
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Chunk {
+            export_id: String,
+            sequence_no: u64,
+            format: ExportFormat,
+            last: bool,
+        }
+
+        let buf = serialize(AppendRequest::sample()).unwrap();
+        assert_eq!(
+            deserialize_strict::<AppendRequest>(&buf).unwrap(),
+            AppendRequest::sample()
+        );
+
+        let extra = Extra {
+            key: "k".to_string(),
+            value: "v".to_string(),
+            bogus: true,
+        };
+        let buf = serialize(&extra).unwrap();
+        assert!(deserialize::<AppendRequest>(&buf).is_ok());
+        assert_eq!(
+            deserialize_strict::<AppendRequest>(&buf),
+            Err(WasccCodecError::UnknownField {
+                path: "bogus".to_string()
+            })
+        );
+
+        let buf = serialize(Partial {
+            value: "v".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            deserialize_strict::<AppendRequest>(&buf),
+            Err(WasccCodecError::MissingField {
+                path: "key".to_string()
+            })
+        );
+
+        // chunkBytes declares a default, so payloads that omit it are accepted
+        let buf = serialize(Chunk {
+            export_id: "e".to_string(),
+            sequence_no: 0,
+            format: ExportFormat::Msgpack,
+            last: true,
+        })
+        .unwrap();
+        let chunk = deserialize_strict::<ExportChunk>(&buf).unwrap();
+        assert!(chunk.chunk_bytes.is_empty());
+    }
+
+    #[test]
+    fn strict_deserialization_accepts_omitted_defaults() {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Headers {
+            subject: String,
+            reply_to: String,
+            headers: HashMap<String, String>,
+        }
+        let buf = serialize(Headers {
+            subject: "orders.placed".to_string(),
+            reply_to: String::new(),
+            headers: HashMap::new(),
+        })
+        .unwrap();
+        assert!(deserialize_strict::<BrokerMessage>(&buf)
+            .unwrap()
+            .headers
+            .is_empty());
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct LegacySubscribe {
+            subject: String,
+        }
+        let buf = serialize(LegacySubscribe {
+            subject: "orders.*".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            deserialize_strict::<SubscribeRequest>(&buf)
+                .unwrap()
+                .max_in_flight,
+            None
+        );
+    }
+
+    #[test]
+    fn strict_deserialization_reports_nested_paths() {
+        let ack = |region: &str| RegionAck {
+            region: region.to_string(),
+            acked_at: Timestamp::from_secs(1),
+        };
+        let receipt = WriteReceipt::new(&WriteConcern::quorum(), vec![ack("a"), ack("b")], 3);
+        let value = deserialize_to_value(&serialize(&receipt).unwrap()).unwrap();
+
+        let mut unknown = value.clone();
+        unknown["acks"][1]["typoField"] = serde_json::Value::Null;
+        let buf = serialize_value(&unknown).unwrap();
+        assert_eq!(
+            deserialize_strict::<WriteReceipt>(&buf),
+            Err(WasccCodecError::UnknownField {
+                path: "acks[1].typoField".to_string()
+            })
+        );
+
+        let mut missing = value;
+        missing["acks"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("ackedAt");
+        let buf = serialize_value(&missing).unwrap();
+        assert_eq!(
+            deserialize_strict::<WriteReceipt>(&buf),
+            Err(WasccCodecError::MissingField {
+                path: "acks[0].ackedAt".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn strict_deserialization_accepts_explicit_nil_for_skipped_fields() {
+        let msg = BrokerMessage::new("orders.placed", b"{}".to_vec());
+        let mut value = deserialize_to_value(&serialize(&msg).unwrap()).unwrap();
+        assert!(value.get("signature").is_none());
+        value["signature"] = serde_json::Value::Null;
+        value["signer"] = serde_json::Value::Null;
+        let buf = serialize_value(&value).unwrap();
+        assert_eq!(deserialize_strict::<BrokerMessage>(&buf).unwrap(), msg);
+
+        let req = Request::new(Method::Get, "/items/1");
+        let mut value = deserialize_to_value(&serialize(&req).unwrap()).unwrap();
+        value["bodyRef"] = serde_json::Value::Null;
+        let buf = serialize_value(&value).unwrap();
+        assert_eq!(deserialize_strict::<Request>(&buf).unwrap(), req);
+
+        value["bogus"] = serde_json::Value::Bool(true);
+        let buf = serialize_value(&value).unwrap();
+        assert_eq!(
+            deserialize_strict::<Request>(&buf),
+            Err(WasccCodecError::UnknownField {
+                path: "bogus".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn value_passthrough_preserves_payloads() {
+        let req = Request::sample();
+        let mut value = deserialize_to_value(&serialize(&req).unwrap()).unwrap();
+        assert_eq!(value["method"], "GET");
+        assert_eq!(
+            value["body"]["$binary"],
+            base64::encode(b"This is the body of a request")
+        );
+
+        value["method"] = "POST".into();
+        value["body"]["$binary"] = base64::encode(b"changed").into();
+        let rewritten: Request = deserialize(&serialize_value(&value).unwrap()).unwrap();
+        assert_eq!(rewritten.method, Method::Post);
+        assert_eq!(rewritten.body, b"changed".to_vec());
+        assert_eq!(rewritten.header, req.header);
+
+        let numbers = serde_json::json!({"neg": -3, "big": u64::MAX, "pi": 3.25, "none": null});
+        assert_eq!(
+            deserialize_to_value(&serialize_value(&numbers).unwrap()).unwrap(),
+            numbers
+        );
+    }
+
+    #[test]
+    fn value_passthrough_rejects_lossy_payloads() {
+        let mut by_id = HashMap::new();
+        by_id.insert(7u32, "seven".to_string());
+        assert!(deserialize_to_value(&serialize(&by_id).unwrap()).is_err());
+
+        let mut tagged = HashMap::new();
+        tagged.insert(crate::BINARY_TAG.to_string(), "AQID".to_string());
+        assert!(deserialize_to_value(&serialize(&tagged).unwrap()).is_err());
+
+        // A tag alongside other members, or holding a non-string, cannot be mistaken for binary
+        tagged.insert("other".to_string(), "x".to_string());
+        let value = deserialize_to_value(&serialize(&tagged).unwrap()).unwrap();
+        assert_eq!(value["$binary"], "AQID");
+        let mut numbered = HashMap::new();
+        numbered.insert(crate::BINARY_TAG.to_string(), 5u8);
+        let value = deserialize_to_value(&serialize(&numbered).unwrap()).unwrap();
+        assert_eq!(
+            deserialize_to_value(&serialize_value(&value).unwrap()).unwrap(),
+            value
+        );
+    }
+}
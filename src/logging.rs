@@ -2,11 +2,14 @@
 //!
 //! This module contains data types for the `wascc:logging` capability provider
 
+use crate::core::Timestamp;
 use crate::Sample;
 
 /// An operation to request a log write
 pub const OP_LOG: &str = "WriteLog";
 pub const ACTOR_LOG_FLAG: &str = "[ActorLog]";
+/// Management actor sends a LogQueryRequest to the log aggregation provider, receives LogQueryResults back
+pub const OP_QUERY_LOG: &str = "QueryLog";
 
 /// Represents a request to write a log entry. Use this type of log entry if you are
 /// pulling or aggregating logs on a per-actor basis from the host. If you just need
@@ -40,3 +43,279 @@ impl Sample for WriteLogRequest {
         }
     }
 }
+
+/// A query against the logs accumulated from actors. All criteria are optional and are combined with AND
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryRequest {
+    /// Only return entries written by the actor with this public key
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// The most severe level to return, using the same numbering as `WriteLogRequest` (e.g. 1 for ERROR).
+    /// 0 - no lower bound
+    #[serde(default)]
+    pub min_level: u32,
+    /// The least severe level to return (e.g. 3 to exclude DEBUG and TRACE). 0 - no upper bound
+    #[serde(default)]
+    pub max_level: u32,
+    /// Only return entries written at or after this time
+    #[serde(default)]
+    pub since: Option<Timestamp>,
+    /// Only return entries written before this time
+    #[serde(default)]
+    pub until: Option<Timestamp>,
+    /// Only return entries whose body contains this text (case-insensitive)
+    #[serde(default)]
+    pub text: Option<String>,
+    /// The maximum number of entries to return, 0 - provider default
+    #[serde(default)]
+    pub limit: u32,
+    /// The `next_cursor` from a previous page of results
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl Sample for LogQueryRequest {
+    fn sample() -> Self {
+        LogQueryRequest {
+            actor: Some("MB4OLDIC3TCZ4Q4TGGOVAZC43VXFE2JQVRAXQMQFXUCREOOFEKOKZTY2".to_string()),
+            min_level: 1,
+            max_level: 2,
+            since: Some(Timestamp(1_593_000_000_000)),
+            until: None,
+            text: Some("timeout".to_string()),
+            limit: 100,
+            cursor: None,
+        }
+    }
+}
+
+impl LogQueryRequest {
+    /// Indicates whether an entry satisfies the query's criteria (pagination aside)
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        let level = entry.record.level;
+        self.actor.as_ref().is_none_or(|a| *a == entry.actor)
+            && (self.min_level == 0 || level >= self.min_level)
+            && (self.max_level == 0 || level <= self.max_level)
+            && self.since.is_none_or(|t| entry.timestamp >= t)
+            && self.until.is_none_or(|t| entry.timestamp < t)
+            && self.text.as_ref().is_none_or(|text| {
+                entry
+                    .record
+                    .body
+                    .to_lowercase()
+                    .contains(&text.to_lowercase())
+            })
+    }
+}
+
+/// A single log entry written by an actor, as recorded by the host
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// The public key of the actor that wrote the entry
+    pub actor: String,
+    /// The time at which the host received the entry
+    pub timestamp: Timestamp,
+    pub record: WriteLogRequest,
+}
+
+/// A page of log entries matching a query, oldest first
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryResults {
+    #[serde(default)]
+    pub entries: Vec<LogEntry>,
+    /// Pass this in a subsequent query to retrieve the next page. `None` if there are no more entries
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+/// Bridges between actor log requests and the `tracing` ecosystem used by native capability providers.
+/// Requires the `tracing` feature
+#[cfg(feature = "tracing")]
+pub mod tracing_bridge {
+    use super::{WriteLogRequest, OP_LOG};
+    use crate::capabilities::Dispatcher;
+    use std::fmt::{Debug, Write};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+
+    /// The tracing target used for events produced from actor log requests
+    pub const ACTOR_LOG_TARGET: &str = "wascc_actor";
+
+    /// Converts a numeric log level from a `WriteLogRequest` into a tracing level. Level 0 ("OFF")
+    /// and unknown levels produce `None`
+    pub fn level_from_u32(level: u32) -> Option<Level> {
+        match level {
+            1 => Some(Level::ERROR),
+            2 => Some(Level::WARN),
+            3 => Some(Level::INFO),
+            4 => Some(Level::DEBUG),
+            5 => Some(Level::TRACE),
+            _ => None,
+        }
+    }
+
+    /// Converts a tracing level into the numeric level used by `WriteLogRequest`
+    pub fn level_to_u32(level: &Level) -> u32 {
+        match *level {
+            Level::ERROR => 1,
+            Level::WARN => 2,
+            Level::INFO => 3,
+            Level::DEBUG => 4,
+            Level::TRACE => 5,
+        }
+    }
+
+    /// Emits a log request as a tracing event in the current subscriber. The request's target, file, and
+    /// line are recorded in the `log.target`, `log.file`, and `log.line` fields (the same convention
+    /// used by `tracing-log`), since tracing metadata for an event must be known at compile time
+    pub fn emit_event(req: &WriteLogRequest) {
+        macro_rules! emit {
+            ($lvl:expr) => {
+                tracing::event!(
+                    target: ACTOR_LOG_TARGET,
+                    $lvl,
+                    log.target = req.target.as_str(),
+                    log.file = req.file.as_str(),
+                    log.line = req.line,
+                    "{}",
+                    req.body
+                )
+            };
+        }
+        match level_from_u32(req.level) {
+            Some(Level::ERROR) => emit!(Level::ERROR),
+            Some(Level::WARN) => emit!(Level::WARN),
+            Some(Level::INFO) => emit!(Level::INFO),
+            Some(Level::DEBUG) => emit!(Level::DEBUG),
+            Some(Level::TRACE) => emit!(Level::TRACE),
+            None => {}
+        }
+    }
+
+    /// Converts a tracing event into a log request. Fields other than the message are appended to the
+    /// body as `key=value` pairs. The `log.*` fields written by `emit_event` take precedence over the
+    /// event's own metadata
+    pub fn request_from_event(event: &Event<'_>) -> WriteLogRequest {
+        let meta = event.metadata();
+        let mut visitor = RequestVisitor {
+            req: WriteLogRequest {
+                level: level_to_u32(meta.level()),
+                body: String::new(),
+                line: meta.line().unwrap_or(0),
+                file: meta.file().unwrap_or_default().to_string(),
+                target: meta.target().to_string(),
+            },
+            fields: String::new(),
+        };
+        event.record(&mut visitor);
+        let mut req = visitor.req;
+        req.body.push_str(&visitor.fields);
+        req
+    }
+
+    struct RequestVisitor {
+        req: WriteLogRequest,
+        fields: String,
+    }
+
+    impl Visit for RequestVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            match field.name() {
+                "message" => self.req.body.push_str(value),
+                "log.target" => self.req.target = value.to_string(),
+                "log.file" => self.req.file = value.to_string(),
+                _ => self.record_debug(field, &value),
+            }
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            match field.name() {
+                "log.line" => self.req.line = value as u32,
+                _ => self.record_debug(field, &value),
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            match field.name() {
+                "message" => {
+                    let _ = write!(self.req.body, "{:?}", value);
+                }
+                name => {
+                    let _ = write!(self.fields, " {}={:?}", name, value);
+                }
+            }
+        }
+    }
+
+    /// A tracing subscriber layer that converts every event into a `WriteLogRequest` and dispatches it to
+    /// an actor with the `OP_LOG` operation. Do not combine this layer with `emit_event` on the same
+    /// subscriber unless events targeting `ACTOR_LOG_TARGET` are filtered out, or logs will loop
+    pub struct WriteLogLayer {
+        dispatcher: Box<dyn Dispatcher>,
+        actor: String,
+    }
+
+    impl WriteLogLayer {
+        /// Creates a new layer that dispatches log requests to the given actor
+        pub fn new(dispatcher: Box<dyn Dispatcher>, actor: &str) -> WriteLogLayer {
+            WriteLogLayer {
+                dispatcher,
+                actor: actor.to_string(),
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for WriteLogLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let req = request_from_event(event);
+            if let Ok(buf) = crate::serialize(&req) {
+                let _ = self.dispatcher.dispatch(&self.actor, OP_LOG, &buf);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::{emit_event, WriteLogLayer};
+        use crate::capabilities::Dispatcher;
+        use crate::logging::WriteLogRequest;
+        use crate::Sample;
+        use std::error::Error;
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::layer::SubscriberExt;
+
+        struct CaptureDispatcher(Arc<Mutex<Vec<Vec<u8>>>>);
+
+        impl Dispatcher for CaptureDispatcher {
+            fn dispatch(
+                &self,
+                _actor: &str,
+                _op: &str,
+                msg: &[u8],
+            ) -> Result<Vec<u8>, Box<dyn Error>> {
+                self.0.lock().unwrap().push(msg.to_vec());
+                Ok(vec![])
+            }
+        }
+
+        #[test]
+        fn log_request_round_trips_through_tracing() {
+            let captured = Arc::new(Mutex::new(Vec::new()));
+            let layer = WriteLogLayer::new(Box::new(CaptureDispatcher(captured.clone())), "actor");
+            let subscriber = tracing_subscriber::registry().with(layer);
+            tracing::subscriber::with_default(
+                subscriber,
+                || emit_event(&WriteLogRequest::sample()),
+            );
+
+            let captured = captured.lock().unwrap();
+            assert_eq!(captured.len(), 1);
+            let req: WriteLogRequest = crate::deserialize(&captured[0]).unwrap();
+            assert_eq!(req, WriteLogRequest::sample());
+        }
+    }
+}
@@ -2,7 +2,13 @@
 //!
 //! This module contains data types for the `wascc:messaging` capability provider
 
+use crate::core::{DurationMs, Timestamp};
+use crate::preview::BytesPreview;
 use crate::Sample;
+use std::collections::HashMap;
+use std::fmt;
+
+pub mod cloudevents;
 
 /// The operation to publish a message from an actor
 pub const OP_PUBLISH_MESSAGE: &str = "Publish";
@@ -10,10 +16,29 @@ pub const OP_PUBLISH_MESSAGE: &str = "Publish";
 pub const OP_DELIVER_MESSAGE: &str = "DeliverMessage";
 /// The operation for an actor to perform a request-reply operation
 pub const OP_PERFORM_REQUEST: &str = "Request";
+/// The operation for an actor to subscribe to a subject with explicit delivery semantics
+pub const OP_SUBSCRIBE: &str = "Subscribe";
+/// The operation for an actor to end a subscription created with `OP_SUBSCRIBE`
+pub const OP_UNSUBSCRIBE: &str = "Unsubscribe";
+/// The operation for an actor to acknowledge a message delivered on a manually acknowledged subscription
+pub const OP_ACK_DELIVERY: &str = "AckDelivery";
+/// The operation for an actor to declare the exchanges, queues and bindings it relies on. Declarations are
+/// idempotent, lack of error indicates the topology exists as declared
+pub const OP_DECLARE_TOPOLOGY: &str = "DeclareTopology";
+
+/// The lowest message priority
+pub const PRIORITY_LOWEST: u8 = 0;
+/// The priority of messages published without one
+pub const PRIORITY_NORMAL: u8 = 4;
+/// The highest message priority. Providers clamp higher priorities to this value, and map the range
+/// onto the broker's native priorities (e.g. AMQP's 0-9). Providers for brokers without priorities
+/// deliver messages in publication order regardless of priority
+pub const PRIORITY_HIGHEST: u8 = 9;
 
 /// A representation of a broker message
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct BrokerMessage {
     /// The message subject or topic
     pub subject: String,
@@ -23,12 +48,204 @@ pub struct BrokerMessage {
     #[serde(with = "serde_bytes")]
     #[serde(default)]
     pub body: Vec<u8>,
+    /// Optional message headers, for brokers that support them
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// The time-to-live of the message after publication. Providers should map this to the broker's
+    /// native expiry and must not deliver the message once it has expired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<DurationMs>,
+    /// Identifies a delivery on a manually acknowledged subscription. Set by the provider on delivered
+    /// messages, and passed back in an `AckDelivery`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_tag: Option<String>,
+    /// The priority of the message, from `PRIORITY_LOWEST` to `PRIORITY_HIGHEST`. Higher priority
+    /// messages waiting on a subject are delivered first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Messages with the same group ID are delivered in publication order, one at a time, as with SQS
+    /// FIFO message groups. Messages in different groups may be processed concurrently. Providers for
+    /// brokers without native grouping must preserve per-group ordering themselves or reject the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<String>,
+    /// The Ed25519 signature of the message's `signing_bytes`, for messages crossing trust boundaries
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "serde_bytes")]
+    pub signature: Option<Vec<u8>>,
+    /// The public key, as an nkey (e.g. `UAB...`), of the producer that signed the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+}
+
+impl fmt::Debug for BrokerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BrokerMessage")
+            .field("subject", &self.subject)
+            .field("reply_to", &self.reply_to)
+            .field("body", &BytesPreview(&self.body))
+            .field("headers", &self.headers)
+            .field("expiration", &self.expiration)
+            .field("delivery_tag", &self.delivery_tag)
+            .field("priority", &self.priority)
+            .field("group_id", &self.group_id)
+            .field("signature", &self.signature.as_deref().map(BytesPreview))
+            .field("signer", &self.signer)
+            .finish()
+    }
+}
+
+impl BrokerMessage {
+    /// Creates a message with no reply subject or headers
+    pub fn new(subject: &str, body: Vec<u8>) -> BrokerMessage {
+        BrokerMessage {
+            subject: subject.to_string(),
+            reply_to: String::new(),
+            body,
+            headers: HashMap::new(),
+            expiration: None,
+            delivery_tag: None,
+            priority: None,
+            group_id: None,
+            signature: None,
+            signer: None,
+        }
+    }
+
+    pub fn with_reply_to(mut self, reply_to: &str) -> BrokerMessage {
+        self.reply_to = reply_to.to_string();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> BrokerMessage {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_expiration(mut self, expiration: DurationMs) -> BrokerMessage {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn with_delivery_tag(mut self, delivery_tag: &str) -> BrokerMessage {
+        self.delivery_tag = Some(delivery_tag.to_string());
+        self
+    }
+
+    /// Sets the priority of the message, clamped to `PRIORITY_HIGHEST`
+    pub fn with_priority(mut self, priority: u8) -> BrokerMessage {
+        self.priority = Some(priority.min(PRIORITY_HIGHEST));
+        self
+    }
+
+    pub fn with_group_id(mut self, group_id: &str) -> BrokerMessage {
+        self.group_id = Some(group_id.to_string());
+        self
+    }
+
+    /// The canonical bytes a producer signs: a domain prefix followed by the subject, reply subject,
+    /// body, headers (sorted by name) and signer, each preceded by its length as a big-endian `u64`.
+    /// Expiration, priority and group ID are not covered, as brokers may not carry them end to end, and
+    /// neither is the delivery tag, which the provider assigns
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut buf = b"wascc-broker-message-v1".to_vec();
+        let mut part = |bytes: &[u8]| {
+            buf.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+            buf.extend_from_slice(bytes);
+        };
+        part(self.subject.as_bytes());
+        part(self.reply_to.as_bytes());
+        part(&self.body);
+        let mut headers: Vec<(&String, &String)> = self.headers.iter().collect();
+        headers.sort();
+        part(&(headers.len() as u64).to_be_bytes());
+        for (k, v) in headers {
+            part(k.as_bytes());
+            part(v.as_bytes());
+        }
+        part(self.signer.as_deref().unwrap_or_default().as_bytes());
+        buf
+    }
+
+    /// The priority of the message, or `PRIORITY_NORMAL` if it was published without one
+    pub fn effective_priority(&self) -> u8 {
+        self.priority
+            .map_or(PRIORITY_NORMAL, |p| p.min(PRIORITY_HIGHEST))
+    }
+
+    /// Indicates whether a message published at the given time has expired. Messages without an
+    /// expiration never expire
+    pub fn is_expired(&self, published_at: Timestamp, now: Timestamp) -> bool {
+        self.expiration.is_some_and(|ttl| {
+            now.as_millis() >= published_at.as_millis().saturating_add(ttl.as_millis())
+        })
+    }
+
+    /// Produces the message to publish (with `OP_PUBLISH_MESSAGE`) in reply to this message, addressed to
+    /// this message's reply-to subject. Returns `None` if this message has no reply subject
+    pub fn reply(&self, body: Vec<u8>) -> Option<BrokerMessage> {
+        self.reply_context().reply(body)
+    }
+
+    /// Captures the reply addressing information of this message, so a reply can be produced after the
+    /// message itself has been consumed
+    pub fn reply_context(&self) -> ReplyContext {
+        ReplyContext {
+            reply_to: if self.reply_to.is_empty() {
+                None
+            } else {
+                Some(self.reply_to.to_string())
+            },
+        }
+    }
+
+    /// Returns the value of the named header, comparing header names case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// The reply addressing information of a delivered message. Messages published without a reply
+/// subject produce a context that does not expect a reply
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ReplyContext {
+    reply_to: Option<String>,
+}
+
+impl ReplyContext {
+    /// Indicates whether the sender of the original message is waiting for a reply
+    pub fn expects_reply(&self) -> bool {
+        self.reply_to.is_some()
+    }
+
+    /// The subject to which replies should be published, if any
+    pub fn subject(&self) -> Option<&str> {
+        self.reply_to.as_deref()
+    }
+
+    /// Produces the message to publish in reply, or `None` if no reply is expected
+    pub fn reply(&self, body: Vec<u8>) -> Option<BrokerMessage> {
+        self.reply_to.as_ref().map(|subject| BrokerMessage {
+            subject: subject.to_string(),
+            reply_to: String::new(),
+            body,
+            headers: HashMap::new(),
+            expiration: None,
+            delivery_tag: None,
+            priority: None,
+            group_id: None,
+            signature: None,
+            signer: None,
+        })
+    }
 }
 
 /// A request for the broker to make a request-and-reply publication. Inbox management
 /// is handled by the provider implementation, not by the actor
-#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[derive(PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub struct RequestMessage {
     /// Subject on which to publish the request
     pub subject: String,
@@ -39,6 +256,21 @@ pub struct RequestMessage {
     /// The timeout (milliseconds) to await a reply before giving up
     #[serde(rename = "timeout")]
     pub timeout_ms: i64,
+    /// The time-to-live of the request message after publication. A request that expires before any
+    /// responder receives it is reported as `Expired` rather than timing out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<DurationMs>,
+}
+
+impl fmt::Debug for RequestMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestMessage")
+            .field("subject", &self.subject)
+            .field("body", &BytesPreview(&self.body))
+            .field("timeout_ms", &self.timeout_ms)
+            .field("expiration", &self.expiration)
+            .finish()
+    }
 }
 
 impl Sample for RequestMessage {
@@ -47,6 +279,463 @@ impl Sample for RequestMessage {
             subject: "user.profile.175".to_string(),
             body: b"raw query bytes".to_vec(),
             timeout_ms: 100,
+            expiration: None,
+        }
+    }
+}
+
+impl RequestMessage {
+    pub fn new(subject: &str, body: Vec<u8>, timeout_ms: i64) -> RequestMessage {
+        RequestMessage {
+            subject: subject.to_string(),
+            body,
+            timeout_ms,
+            expiration: None,
         }
     }
+
+    pub fn with_expiration(mut self, expiration: DurationMs) -> RequestMessage {
+        self.expiration = Some(expiration);
+        self
+    }
+}
+
+/// The outcome of attempting to deliver a message that was published with an expiration
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// The message was delivered before it expired
+    Delivered,
+    /// The message expired before it could be delivered
+    Expired(Expired),
+}
+
+/// Describes a message that expired before it was delivered
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Expired {
+    /// The subject on which the message was published
+    pub subject: String,
+    /// The time-to-live the message was published with
+    pub expiration: DurationMs,
+    /// The time at which the message expired
+    pub expired_at: Timestamp,
+}
+
+impl Expired {
+    pub fn new(subject: &str, expiration: DurationMs, expired_at: Timestamp) -> Expired {
+        Expired {
+            subject: subject.to_string(),
+            expiration,
+            expired_at,
+        }
+    }
+}
+
+/// Determines how deliveries on a subscription are acknowledged
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AckMode {
+    /// The provider acknowledges each message once the actor has handled its delivery without error
+    #[default]
+    Auto,
+    /// The actor acknowledges each message with `OP_ACK_DELIVERY`. Unacknowledged messages are
+    /// redelivered according to the broker's redelivery policy
+    Manual,
+    /// Messages are not acknowledged and are never redelivered (at-most-once delivery)
+    None,
+}
+
+/// A request to subscribe to a subject
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SubscribeRequest {
+    /// The subject or topic to subscribe to. May contain the broker's wildcards
+    pub subject: String,
+    #[serde(default)]
+    pub ack_mode: AckMode,
+    /// The maximum number of delivered but unacknowledged messages. The provider pauses delivery while
+    /// this many messages are outstanding. If not supplied, the broker's default applies
+    #[serde(default)]
+    pub max_in_flight: Option<u32>,
+    /// Subscriptions sharing a queue group share the subject's messages, each message being delivered
+    /// to only one member of the group. Used to spread work across the instances of a scaled out actor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_group: Option<String>,
+    /// Where in the subject's retained messages delivery starts. Providers for brokers that do not retain
+    /// messages only support `DeliverPolicy::New`
+    #[serde(default)]
+    pub deliver_policy: DeliverPolicy,
+}
+
+impl Sample for SubscribeRequest {
+    fn sample() -> Self {
+        SubscribeRequest {
+            subject: "orders.*".to_string(),
+            ack_mode: AckMode::Manual,
+            max_in_flight: Some(16),
+            queue_group: Some("order-workers".to_string()),
+            deliver_policy: DeliverPolicy::ByStartSequence(1024),
+        }
+    }
+}
+
+impl SubscribeRequest {
+    pub fn new(subject: &str, ack_mode: AckMode) -> SubscribeRequest {
+        SubscribeRequest {
+            subject: subject.to_string(),
+            ack_mode,
+            max_in_flight: None,
+            queue_group: None,
+            deliver_policy: DeliverPolicy::New,
+        }
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: u32) -> SubscribeRequest {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    pub fn with_queue_group(mut self, queue_group: &str) -> SubscribeRequest {
+        self.queue_group = Some(queue_group.to_string());
+        self
+    }
+
+    pub fn with_deliver_policy(mut self, deliver_policy: DeliverPolicy) -> SubscribeRequest {
+        self.deliver_policy = deliver_policy;
+        self
+    }
+}
+
+/// Determines which of a subject's retained messages a new subscription receives
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliverPolicy {
+    /// Every retained message, oldest first
+    All,
+    /// Only the most recent retained message, followed by new messages
+    Last,
+    /// Only messages published after the subscription was created
+    #[default]
+    New,
+    /// Retained messages starting at the given broker sequence number
+    ByStartSequence(u64),
+    /// Retained messages published at or after the given time
+    ByStartTime(Timestamp),
 }
+
+/// A request to end a subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct UnsubscribeRequest {
+    pub subject: String,
+}
+
+impl UnsubscribeRequest {
+    pub fn new(subject: &str) -> UnsubscribeRequest {
+        UnsubscribeRequest {
+            subject: subject.to_string(),
+        }
+    }
+}
+
+/// How an actor settles a delivery on a manually acknowledged subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AckOutcome {
+    /// The message was handled and should not be redelivered
+    #[default]
+    Ack,
+    /// The message was not handled and should be redelivered
+    Nack,
+    /// The message cannot be handled and should not be redelivered
+    Reject,
+}
+
+/// Settles a delivery on a manually acknowledged subscription
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AckDelivery {
+    /// The delivery tag of the message being settled
+    pub delivery_tag: String,
+    #[serde(default)]
+    pub outcome: AckOutcome,
+}
+
+impl AckDelivery {
+    pub fn new(delivery_tag: &str, outcome: AckOutcome) -> AckDelivery {
+        AckDelivery {
+            delivery_tag: delivery_tag.to_string(),
+            outcome,
+        }
+    }
+}
+
+/// How an exchange routes the messages published to it
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangeType {
+    /// Routes to queues bound with a routing key equal to the message's
+    Direct,
+    /// Routes to every bound queue, ignoring routing keys
+    Fanout,
+    /// Routes to queues bound with a pattern matching the message's routing key
+    Topic,
+    /// Routes on message headers rather than the routing key
+    Headers,
+}
+
+/// Declares an exchange
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ExchangeDeclaration {
+    pub name: String,
+    pub exchange_type: ExchangeType,
+    /// Indicates the exchange survives a broker restart
+    pub durable: bool,
+    /// Indicates the broker deletes the exchange once its last binding is removed
+    #[serde(default)]
+    pub auto_delete: bool,
+}
+
+impl ExchangeDeclaration {
+    /// Declares a durable exchange
+    pub fn new(name: &str, exchange_type: ExchangeType) -> ExchangeDeclaration {
+        ExchangeDeclaration {
+            name: name.to_string(),
+            exchange_type,
+            durable: true,
+            auto_delete: false,
+        }
+    }
+
+    pub fn transient(mut self) -> ExchangeDeclaration {
+        self.durable = false;
+        self.auto_delete = true;
+        self
+    }
+}
+
+/// Declares a queue
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct QueueDeclaration {
+    pub name: String,
+    /// Indicates the queue survives a broker restart
+    pub durable: bool,
+    /// Indicates the queue may only be consumed by the declaring actor's connection, and is deleted
+    /// when that connection closes
+    #[serde(default)]
+    pub exclusive: bool,
+    /// Indicates the broker deletes the queue once its last consumer unsubscribes
+    #[serde(default)]
+    pub auto_delete: bool,
+    /// Broker-specific queue arguments (e.g. `x-message-ttl`, `x-dead-letter-exchange`). Providers convert
+    /// values to the type the broker expects for each argument
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub arguments: HashMap<String, String>,
+}
+
+impl QueueDeclaration {
+    /// Declares a durable, shared queue
+    pub fn new(name: &str) -> QueueDeclaration {
+        QueueDeclaration {
+            name: name.to_string(),
+            durable: true,
+            exclusive: false,
+            auto_delete: false,
+            arguments: HashMap::new(),
+        }
+    }
+
+    pub fn exclusive(mut self) -> QueueDeclaration {
+        self.durable = false;
+        self.exclusive = true;
+        self
+    }
+
+    pub fn with_argument(mut self, name: &str, value: &str) -> QueueDeclaration {
+        self.arguments.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Binds a queue to an exchange
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct BindingDeclaration {
+    pub exchange: String,
+    pub queue: String,
+    /// The routing key or pattern. Ignored by fanout and headers exchanges
+    #[serde(default)]
+    pub routing_key: String,
+    /// Binding arguments, e.g. the headers to match on a headers exchange
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub arguments: HashMap<String, String>,
+}
+
+impl BindingDeclaration {
+    pub fn new(exchange: &str, queue: &str, routing_key: &str) -> BindingDeclaration {
+        BindingDeclaration {
+            exchange: exchange.to_string(),
+            queue: queue.to_string(),
+            routing_key: routing_key.to_string(),
+            arguments: HashMap::new(),
+        }
+    }
+
+    pub fn with_argument(mut self, name: &str, value: &str) -> BindingDeclaration {
+        self.arguments.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+/// A request to declare messaging topology. Providers declare exchanges first, then queues, then
+/// bindings, so bindings may refer to exchanges and queues declared in the same request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DeclareTopologyRequest {
+    #[serde(default)]
+    pub exchanges: Vec<ExchangeDeclaration>,
+    #[serde(default)]
+    pub queues: Vec<QueueDeclaration>,
+    #[serde(default)]
+    pub bindings: Vec<BindingDeclaration>,
+}
+
+impl Sample for DeclareTopologyRequest {
+    fn sample() -> Self {
+        DeclareTopologyRequest::new()
+            .with_exchange(ExchangeDeclaration::new("orders", ExchangeType::Topic))
+            .with_queue(
+                QueueDeclaration::new("orders.fulfillment")
+                    .with_argument("x-dead-letter-exchange", "orders.dlx"),
+            )
+            .with_binding(BindingDeclaration::new(
+                "orders",
+                "orders.fulfillment",
+                "orders.*.placed",
+            ))
+    }
+}
+
+impl DeclareTopologyRequest {
+    pub fn new() -> DeclareTopologyRequest {
+        DeclareTopologyRequest::default()
+    }
+
+    pub fn with_exchange(mut self, exchange: ExchangeDeclaration) -> DeclareTopologyRequest {
+        self.exchanges.push(exchange);
+        self
+    }
+
+    pub fn with_queue(mut self, queue: QueueDeclaration) -> DeclareTopologyRequest {
+        self.queues.push(queue);
+        self
+    }
+
+    pub fn with_binding(mut self, binding: BindingDeclaration) -> DeclareTopologyRequest {
+        self.bindings.push(binding);
+        self
+    }
+}
+
+#[cfg(feature = "signing")]
+mod signing {
+    use super::BrokerMessage;
+    use crate::errors::WasccCodecError;
+    use nkeys::KeyPair;
+    use std::error::Error;
+
+    /// Signs a message with a producer's key pair, setting its `signer` field to the pair's public key
+    /// and its `signature` field to the Ed25519 signature of its `signing_bytes`
+    pub fn sign_message(
+        mut msg: BrokerMessage,
+        key: &KeyPair,
+    ) -> Result<BrokerMessage, Box<dyn Error>> {
+        msg.signer = Some(key.public_key());
+        msg.signature = Some(key.sign(&msg.signing_bytes())?);
+        Ok(msg)
+    }
+
+    /// Verifies that a message was signed by one of the trusted public keys and has not been altered
+    /// since. Unsigned messages are rejected
+    pub fn verify_message(msg: &BrokerMessage, trusted: &[&str]) -> Result<(), WasccCodecError> {
+        let invalid = |signer: &str, reason: &str| WasccCodecError::InvalidSignature {
+            signer: signer.to_string(),
+            reason: reason.to_string(),
+        };
+        let (signer, signature) = match (&msg.signer, &msg.signature) {
+            (Some(signer), Some(signature)) => (signer, signature),
+            _ => return Err(invalid("", "message is not signed")),
+        };
+        if !trusted.contains(&signer.as_str()) {
+            return Err(invalid(signer, "signer is not trusted"));
+        }
+        let key = KeyPair::from_public_key(signer)
+            .map_err(|e| invalid(signer, &format!("invalid public key: {}", e)))?;
+        key.verify(&msg.signing_bytes(), signature)
+            .map_err(|_| invalid(signer, "signature does not match the message"))
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::{sign_message, verify_message};
+        use crate::messaging::BrokerMessage;
+        use nkeys::KeyPair;
+
+        #[test]
+        fn signed_messages_verify_round_trip() {
+            let key = KeyPair::new_user();
+            let producer = key.public_key();
+            let msg = BrokerMessage::new("orders.placed", b"{}".to_vec()).with_header("a", "1");
+            let signed = sign_message(msg, &key).unwrap();
+            assert_eq!(signed.signer.as_deref(), Some(producer.as_str()));
+
+            let buf = crate::serialize(&signed).unwrap();
+            let received: BrokerMessage = crate::deserialize(&buf).unwrap();
+            assert!(verify_message(&received, &[&producer]).is_ok());
+            let other = KeyPair::new_user().public_key();
+            assert!(verify_message(&received, &[&other]).is_err());
+            let unsigned = BrokerMessage::new("orders.placed", vec![]);
+            assert!(verify_message(&unsigned, &[&producer]).is_err());
+        }
+
+        #[test]
+        fn tampering_is_detected() {
+            let key = KeyPair::new_user();
+            let producer = key.public_key();
+            let signed = sign_message(BrokerMessage::new("orders.placed", b"{}".to_vec()), &key)
+                .unwrap()
+                .with_header("a", "2");
+            assert!(verify_message(&signed, &[&producer]).is_err());
+
+            let mut body =
+                sign_message(BrokerMessage::new("orders.placed", b"{}".to_vec()), &key).unwrap();
+            body.body = b"{\"total\":0}".to_vec();
+            assert!(verify_message(&body, &[&producer]).is_err());
+
+            let impostor = KeyPair::new_user();
+            let mut forged = sign_message(
+                BrokerMessage::new("orders.placed", b"{}".to_vec()),
+                &impostor,
+            )
+            .unwrap();
+            forged.signer = Some(producer.clone());
+            assert!(verify_message(&forged, &[&producer]).is_err());
+        }
+    }
+}
+
+#[cfg(feature = "signing")]
+pub use nkeys::KeyPair;
+#[cfg(feature = "signing")]
+pub use signing::{sign_message, verify_message};
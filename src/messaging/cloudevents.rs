@@ -0,0 +1,385 @@
+//! # CloudEvents
+//!
+//! This module contains a [CloudEvents 1.0](https://github.com/cloudevents/spec) envelope along with
+//! helpers to carry it inside a `BrokerMessage`. In _binary_ mode the event attributes travel as
+//! `ce-` prefixed message headers and the message body is the event data. In _structured_ mode the
+//! entire event is encoded as a JSON document in the message body
+
+use super::BrokerMessage;
+use crate::errors::WasccCodecError;
+use crate::preview::BytesPreview;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The CloudEvents specification version implemented by this module
+pub const SPEC_VERSION: &str = "1.0";
+/// Content type of a structured-mode event
+pub const CONTENT_TYPE_CLOUDEVENTS_JSON: &str = "application/cloudevents+json";
+/// Content type of a structured-mode batch of events
+pub const CONTENT_TYPE_CLOUDEVENTS_BATCH_JSON: &str = "application/cloudevents-batch+json";
+/// Content type of JSON event data
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+/// Prefix of the message headers that carry event attributes in binary mode
+pub const HEADER_PREFIX: &str = "ce-";
+/// Message header that carries the data content type in binary mode, and the event format in structured mode
+pub const HEADER_CONTENT_TYPE: &str = "content-type";
+
+const ATTR_ID: &str = "id";
+const ATTR_SOURCE: &str = "source";
+const ATTR_SPEC_VERSION: &str = "specversion";
+const ATTR_TYPE: &str = "type";
+const ATTR_DATA_CONTENT_TYPE: &str = "datacontenttype";
+const ATTR_DATA_SCHEMA: &str = "dataschema";
+const ATTR_SUBJECT: &str = "subject";
+const ATTR_TIME: &str = "time";
+const ATTR_DATA: &str = "data";
+const ATTR_DATA_BASE64: &str = "data_base64";
+
+/// A CloudEvent, consisting of its context attributes and raw data
+#[derive(PartialEq, Deserialize, Serialize, Clone)]
+pub struct CloudEvent {
+    /// Identifies the event. Producers must ensure that `source` + `id` is unique for each distinct event
+    pub id: String,
+    /// A URI-reference identifying the context in which the event happened
+    pub source: String,
+    #[serde(rename = "specversion")]
+    pub spec_version: String,
+    /// The type of the event, e.g. `com.example.object.deleted.v2`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    #[serde(rename = "datacontenttype")]
+    #[serde(default)]
+    pub data_content_type: Option<String>,
+    #[serde(rename = "dataschema")]
+    #[serde(default)]
+    pub data_schema: Option<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// The time at which the occurrence happened, as an RFC 3339 timestamp
+    #[serde(default)]
+    pub time: Option<String>,
+    /// Extension attributes, keyed by their (lowercase alphanumeric) names
+    #[serde(default)]
+    pub extensions: HashMap<String, String>,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub data: Vec<u8>,
+}
+
+impl fmt::Debug for CloudEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CloudEvent")
+            .field("id", &self.id)
+            .field("source", &self.source)
+            .field("spec_version", &self.spec_version)
+            .field("event_type", &self.event_type)
+            .field("data_content_type", &self.data_content_type)
+            .field("data_schema", &self.data_schema)
+            .field("subject", &self.subject)
+            .field("time", &self.time)
+            .field("extensions", &self.extensions)
+            .field("data", &BytesPreview(&self.data))
+            .finish()
+    }
+}
+
+impl CloudEvent {
+    /// Creates a new event with the required attributes and no data
+    pub fn new(id: &str, source: &str, event_type: &str) -> CloudEvent {
+        CloudEvent {
+            id: id.to_string(),
+            source: source.to_string(),
+            spec_version: SPEC_VERSION.to_string(),
+            event_type: event_type.to_string(),
+            data_content_type: None,
+            data_schema: None,
+            subject: None,
+            time: None,
+            extensions: HashMap::new(),
+            data: vec![],
+        }
+    }
+
+    /// Sets the event's data and its content type
+    pub fn with_data(self, content_type: &str, data: Vec<u8>) -> CloudEvent {
+        CloudEvent {
+            data_content_type: Some(content_type.to_string()),
+            data,
+            ..self
+        }
+    }
+
+    pub fn with_subject(self, subject: &str) -> CloudEvent {
+        CloudEvent {
+            subject: Some(subject.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_time(self, time: &str) -> CloudEvent {
+        CloudEvent {
+            time: Some(time.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_extension(mut self, name: &str, value: &str) -> CloudEvent {
+        self.extensions.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Encodes this event in binary mode, as a message published on the given broker subject
+    pub fn to_binary(&self, subject: &str) -> BrokerMessage {
+        let mut headers = HashMap::new();
+        let mut attr = |name: &str, value: &str| {
+            headers.insert(format!("{}{}", HEADER_PREFIX, name), value.to_string());
+        };
+        attr(ATTR_ID, &self.id);
+        attr(ATTR_SOURCE, &self.source);
+        attr(ATTR_SPEC_VERSION, &self.spec_version);
+        attr(ATTR_TYPE, &self.event_type);
+        if let Some(ref v) = self.data_schema {
+            attr(ATTR_DATA_SCHEMA, v);
+        }
+        if let Some(ref v) = self.subject {
+            attr(ATTR_SUBJECT, v);
+        }
+        if let Some(ref v) = self.time {
+            attr(ATTR_TIME, v);
+        }
+        for (k, v) in &self.extensions {
+            attr(k, v);
+        }
+        if let Some(ref ct) = self.data_content_type {
+            headers.insert(HEADER_CONTENT_TYPE.to_string(), ct.to_string());
+        }
+        BrokerMessage {
+            subject: subject.to_string(),
+            reply_to: String::new(),
+            body: self.data.clone(),
+            headers,
+            expiration: None,
+            delivery_tag: None,
+            priority: None,
+            group_id: None,
+            signature: None,
+            signer: None,
+        }
+    }
+
+    /// Encodes this event in structured mode, as a message published on the given broker subject. Data
+    /// with a JSON content type is embedded as JSON, all other data is embedded as base64
+    pub fn to_structured(&self, subject: &str) -> Result<BrokerMessage, WasccCodecError> {
+        let mut doc = Map::new();
+        let mut attr = |name: &str, value: &str| {
+            doc.insert(name.to_string(), Value::String(value.to_string()));
+        };
+        attr(ATTR_ID, &self.id);
+        attr(ATTR_SOURCE, &self.source);
+        attr(ATTR_SPEC_VERSION, &self.spec_version);
+        attr(ATTR_TYPE, &self.event_type);
+        if let Some(ref v) = self.data_content_type {
+            attr(ATTR_DATA_CONTENT_TYPE, v);
+        }
+        if let Some(ref v) = self.data_schema {
+            attr(ATTR_DATA_SCHEMA, v);
+        }
+        if let Some(ref v) = self.subject {
+            attr(ATTR_SUBJECT, v);
+        }
+        if let Some(ref v) = self.time {
+            attr(ATTR_TIME, v);
+        }
+        for (k, v) in &self.extensions {
+            attr(k, v);
+        }
+        if !self.data.is_empty() {
+            if is_json_content_type(self.data_content_type.as_deref()) {
+                let value: Value = serde_json::from_slice(&self.data).map_err(|e| {
+                    invalid(&format!(
+                        "data is declared as JSON but could not be parsed: {}",
+                        e
+                    ))
+                })?;
+                doc.insert(ATTR_DATA.to_string(), value);
+            } else {
+                doc.insert(
+                    ATTR_DATA_BASE64.to_string(),
+                    Value::String(base64::encode(&self.data)),
+                );
+            }
+        }
+        let body = serde_json::to_vec(&Value::Object(doc)).map_err(|e| invalid(&e.to_string()))?;
+        let mut headers = HashMap::new();
+        headers.insert(
+            HEADER_CONTENT_TYPE.to_string(),
+            CONTENT_TYPE_CLOUDEVENTS_JSON.to_string(),
+        );
+        Ok(BrokerMessage {
+            subject: subject.to_string(),
+            reply_to: String::new(),
+            body,
+            headers,
+            expiration: None,
+            delivery_tag: None,
+            priority: None,
+            group_id: None,
+            signature: None,
+            signer: None,
+        })
+    }
+
+    /// Decodes an event from a broker message, detecting whether it was sent in binary or structured mode
+    pub fn from_broker_message(msg: &BrokerMessage) -> Result<CloudEvent, WasccCodecError> {
+        let structured = msg
+            .header(HEADER_CONTENT_TYPE)
+            .is_some_and(|ct| ct.starts_with(CONTENT_TYPE_CLOUDEVENTS_JSON));
+        if structured {
+            from_structured(&msg.body)
+        } else {
+            from_binary(msg)
+        }
+    }
+}
+
+fn from_binary(msg: &BrokerMessage) -> Result<CloudEvent, WasccCodecError> {
+    let mut attrs: HashMap<String, String> = msg
+        .headers
+        .iter()
+        .filter_map(|(k, v)| {
+            // Header names may contain multi-byte characters, so the prefix is matched on bytes
+            let prefixed = k
+                .as_bytes()
+                .get(..HEADER_PREFIX.len())
+                .is_some_and(|p| p.eq_ignore_ascii_case(HEADER_PREFIX.as_bytes()));
+            match k.get(HEADER_PREFIX.len()..) {
+                Some(name) if prefixed && !name.is_empty() => {
+                    Some((name.to_ascii_lowercase(), v.to_string()))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+    let mut event = take_required(&mut attrs)?;
+    event.data_content_type = msg.header(HEADER_CONTENT_TYPE).map(|s| s.to_string());
+    event.data_schema = attrs.remove(ATTR_DATA_SCHEMA);
+    event.subject = attrs.remove(ATTR_SUBJECT);
+    event.time = attrs.remove(ATTR_TIME);
+    event.extensions = attrs;
+    event.data = msg.body.clone();
+    Ok(event)
+}
+
+fn from_structured(body: &[u8]) -> Result<CloudEvent, WasccCodecError> {
+    let doc: Map<String, Value> = serde_json::from_slice(body)
+        .map_err(|e| invalid(&format!("structured event is not a JSON object: {}", e)))?;
+    let mut attrs = HashMap::new();
+    let mut data = None;
+    let mut data_base64 = None;
+    for (k, v) in doc {
+        match (k.as_str(), v) {
+            (ATTR_DATA, v) => data = Some(v),
+            (ATTR_DATA_BASE64, Value::String(s)) => data_base64 = Some(s),
+            (_, Value::String(s)) => {
+                attrs.insert(k, s);
+            }
+            (_, Value::Null) => {}
+            (_, v) => {
+                attrs.insert(k, v.to_string());
+            }
+        }
+    }
+    let mut event = take_required(&mut attrs)?;
+    event.data_content_type = attrs.remove(ATTR_DATA_CONTENT_TYPE);
+    event.data_schema = attrs.remove(ATTR_DATA_SCHEMA);
+    event.subject = attrs.remove(ATTR_SUBJECT);
+    event.time = attrs.remove(ATTR_TIME);
+    event.extensions = attrs;
+    event.data = match (data, data_base64) {
+        (Some(_), Some(_)) => return Err(invalid("both data and data_base64 are present")),
+        (Some(Value::String(s)), None)
+            if !is_json_content_type(event.data_content_type.as_deref()) =>
+        {
+            s.into_bytes()
+        }
+        (Some(v), None) => serde_json::to_vec(&v).map_err(|e| invalid(&e.to_string()))?,
+        (None, Some(s)) => base64::decode(&s)
+            .map_err(|e| invalid(&format!("data_base64 is not valid base64: {}", e)))?,
+        (None, None) => vec![],
+    };
+    Ok(event)
+}
+
+fn take_required(attrs: &mut HashMap<String, String>) -> Result<CloudEvent, WasccCodecError> {
+    let mut required = |name: &str| {
+        attrs
+            .remove(name)
+            .ok_or_else(|| invalid(&format!("missing required attribute '{}'", name)))
+    };
+    let spec_version = required(ATTR_SPEC_VERSION)?;
+    if spec_version != SPEC_VERSION {
+        return Err(invalid(&format!(
+            "unsupported spec version '{}'",
+            spec_version
+        )));
+    }
+    let id = required(ATTR_ID)?;
+    let source = required(ATTR_SOURCE)?;
+    let event_type = required(ATTR_TYPE)?;
+    Ok(CloudEvent::new(&id, &source, &event_type))
+}
+
+/// Indicates whether data of the given content type is embedded as JSON in structured mode. Per the
+/// specification, an absent content type implies `application/json`
+fn is_json_content_type(content_type: Option<&str>) -> bool {
+    match content_type {
+        None => true,
+        Some(ct) => {
+            let media_type = ct.split(';').next().unwrap_or("").trim();
+            media_type.eq_ignore_ascii_case(CONTENT_TYPE_JSON)
+                || media_type.eq_ignore_ascii_case("text/json")
+                || media_type.to_ascii_lowercase().ends_with("+json")
+        }
+    }
+}
+
+fn invalid(reason: &str) -> WasccCodecError {
+    WasccCodecError::InvalidCloudEvent {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CloudEvent, CONTENT_TYPE_JSON};
+
+    #[test]
+    fn binary_and_structured_round_trip() {
+        let json = CloudEvent::new("evt-1", "/orders", "com.example.order.created")
+            .with_data(CONTENT_TYPE_JSON, br#"{"orderId":42}"#.to_vec())
+            .with_subject("orders/42")
+            .with_extension("traceparent", "00-abc-def-01");
+        let binary = CloudEvent::new("evt-2", "/sensors", "com.example.reading")
+            .with_data("application/octet-stream", vec![0, 159, 146, 150]);
+
+        for event in &[json, binary] {
+            let msg = event.to_binary("events");
+            assert_eq!(msg.header("ce-id"), Some(event.id.as_str()));
+            assert_eq!(&CloudEvent::from_broker_message(&msg).unwrap(), event);
+
+            let msg = event.to_structured("events").unwrap();
+            assert_eq!(&CloudEvent::from_broker_message(&msg).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn multi_byte_header_names_are_ignored() {
+        let event = CloudEvent::new("evt-3", "/orders", "com.example.order.created");
+        let msg = event
+            .to_binary("events")
+            .with_header("é€", "1")
+            .with_header("c€", "2");
+        assert_eq!(CloudEvent::from_broker_message(&msg).unwrap(), event);
+    }
+}
@@ -0,0 +1,366 @@
+//! # Usage Metering
+//!
+//! This module contains data types for the `wascc:metering` capability provider. Multi-tenant
+//! hosts emit a usage record for each invocation, and billing providers aggregate those records
+//! into reports that management actors can query
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+use std::collections::BTreeMap;
+
+/// The host sends a UsageRecord to the metering provider after an invocation completes, lack of error indicates success
+pub const OP_RECORD_USAGE: &str = "RecordUsage";
+/// Guest sends a UsageReportRequest to the provider, receives a UsageReport back
+pub const OP_QUERY_USAGE: &str = "QueryUsage";
+/// Guest sends an AggregateUsageRequest to the provider, receives AggregatedUsage back
+pub const OP_AGGREGATE_USAGE: &str = "AggregateUsage";
+
+/// A single metered invocation of a capability operation by an actor
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    /// The public key of the actor that was invoked or that invoked the capability
+    pub actor: String,
+    /// The capability ID that handled the invocation, e.g. `wascc:keyvalue`
+    pub capability: String,
+    /// The name of the operation that was invoked
+    pub operation: String,
+    /// Number of payload bytes sent into the invocation
+    pub bytes_in: u64,
+    /// Number of payload bytes returned from the invocation
+    pub bytes_out: u64,
+    /// Wall-clock duration of the invocation, in microseconds
+    pub duration_micros: u64,
+    /// Time at which the invocation completed
+    pub timestamp: Timestamp,
+}
+
+impl Sample for UsageRecord {
+    fn sample() -> Self {
+        UsageRecord {
+            actor: "MB4OLDIC3TCZ4Q4TGGOVAZC43VXFE2JQVRAXQMQFXUCREOOFEKOKZTY2".to_string(),
+            capability: "wascc:keyvalue".to_string(),
+            operation: "Get".to_string(),
+            bytes_in: 24,
+            bytes_out: 128,
+            duration_micros: 350,
+            timestamp: Timestamp::from_millis(1_594_000_000_000),
+        }
+    }
+}
+
+/// A request for an aggregated usage report. Empty filters match all values
+#[derive(Debug, PartialEq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportRequest {
+    /// Restrict the report to a single actor
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Restrict the report to a single capability ID
+    #[serde(default)]
+    pub capability: Option<String>,
+    /// Only include records at or after this time
+    pub start_time: Timestamp,
+    /// Only include records before this time, 0 - no upper bound
+    pub end_time: Timestamp,
+}
+
+/// Aggregated usage totals for a single actor, capability, and operation combination
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    pub actor: String,
+    pub capability: String,
+    pub operation: String,
+    /// The number of invocations included in this summary
+    pub invocation_count: u64,
+    pub total_bytes_in: u64,
+    pub total_bytes_out: u64,
+    pub total_duration_micros: u64,
+}
+
+/// The results of a usage report query
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    /// Start of the time window covered by this report
+    pub start_time: Timestamp,
+    /// End of the time window covered by this report
+    pub end_time: Timestamp,
+    #[serde(default)]
+    pub summaries: Vec<UsageSummary>,
+}
+
+impl UsageReport {
+    /// Aggregates the raw usage records that fall within a time window into a report with one summary
+    /// per actor, capability, and operation combination. As in `UsageReportRequest`, the window includes
+    /// its start but not its end, and an end time of 0 means no upper bound. Totals saturate rather than
+    /// overflow
+    pub fn from_records(
+        start_time: Timestamp,
+        end_time: Timestamp,
+        records: &[UsageRecord],
+    ) -> UsageReport {
+        let mut totals: BTreeMap<(&str, &str, &str), UsageSummary> = BTreeMap::new();
+        let in_window = |r: &&UsageRecord| {
+            r.timestamp >= start_time && (end_time.as_millis() == 0 || r.timestamp < end_time)
+        };
+        for r in records.iter().filter(in_window) {
+            let summary = totals
+                .entry((&r.actor, &r.capability, &r.operation))
+                .or_insert_with(|| UsageSummary {
+                    actor: r.actor.to_string(),
+                    capability: r.capability.to_string(),
+                    operation: r.operation.to_string(),
+                    ..Default::default()
+                });
+            summary.invocation_count = summary.invocation_count.saturating_add(1);
+            summary.total_bytes_in = summary.total_bytes_in.saturating_add(r.bytes_in);
+            summary.total_bytes_out = summary.total_bytes_out.saturating_add(r.bytes_out);
+            summary.total_duration_micros = summary
+                .total_duration_micros
+                .saturating_add(r.duration_micros);
+        }
+        UsageReport {
+            start_time,
+            end_time,
+            summaries: totals.into_values().collect(),
+        }
+    }
+}
+
+/// An attribute of usage records by which usage can be grouped or filtered
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageDimension {
+    Actor,
+    Capability,
+    Operation,
+}
+
+impl UsageDimension {
+    /// The value of this dimension in the given record
+    pub fn value_of<'a>(&self, record: &'a UsageRecord) -> &'a str {
+        match self {
+            UsageDimension::Actor => &record.actor,
+            UsageDimension::Capability => &record.capability,
+            UsageDimension::Operation => &record.operation,
+        }
+    }
+}
+
+/// Restricts aggregation to records whose value for a dimension is one of the given values
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionFilter {
+    pub dimension: UsageDimension,
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// The time range over which usage is aggregated, optionally divided into fixed-size buckets
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageWindow {
+    /// Only include records at or after this time
+    pub start_time: Timestamp,
+    /// Only include records before this time, 0 - no upper bound
+    pub end_time: Timestamp,
+    /// Divides the window into consecutive buckets of this length, starting at `start_time`, each
+    /// aggregated separately. If not supplied, the whole window is a single bucket
+    #[serde(default)]
+    pub bucket: Option<DurationMs>,
+}
+
+impl UsageWindow {
+    /// Indicates whether a record at the given time falls within the window
+    pub fn contains(&self, time: Timestamp) -> bool {
+        time >= self.start_time && (self.end_time.as_millis() == 0 || time < self.end_time)
+    }
+
+    /// The start of the bucket containing the given time
+    pub fn bucket_start(&self, time: Timestamp) -> Timestamp {
+        match self.bucket {
+            Some(bucket) if bucket.as_millis() > 0 => {
+                let offset = time.as_millis().saturating_sub(self.start_time.as_millis());
+                Timestamp(self.start_time.as_millis() + offset - offset % bucket.as_millis())
+            }
+            _ => self.start_time,
+        }
+    }
+}
+
+/// A request for usage totals grouped by one or more dimensions
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateUsageRequest {
+    /// The dimensions by which to group usage. If empty, all matching usage in each bucket is
+    /// aggregated into a single total
+    #[serde(default)]
+    pub group_by: Vec<UsageDimension>,
+    pub window: UsageWindow,
+    /// Every filter must match for a record to be included
+    #[serde(default)]
+    pub filters: Vec<DimensionFilter>,
+}
+
+impl Sample for AggregateUsageRequest {
+    fn sample() -> Self {
+        AggregateUsageRequest {
+            group_by: vec![UsageDimension::Actor, UsageDimension::Capability],
+            window: UsageWindow {
+                start_time: Timestamp::from_millis(1_594_000_000_000),
+                end_time: Timestamp::from_millis(1_594_086_400_000),
+                bucket: Some(DurationMs(3_600_000)),
+            },
+            filters: vec![DimensionFilter {
+                dimension: UsageDimension::Capability,
+                values: vec!["wascc:keyvalue".to_string()],
+            }],
+        }
+    }
+}
+
+impl AggregateUsageRequest {
+    /// Indicates whether a record falls within the window and matches every filter
+    pub fn includes(&self, record: &UsageRecord) -> bool {
+        self.window.contains(record.timestamp)
+            && self.filters.iter().all(|f| {
+                let value = f.dimension.value_of(record);
+                f.values.iter().any(|v| v == value)
+            })
+    }
+}
+
+/// Usage totals for one group within one bucket
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageAggregate {
+    /// The start of the bucket
+    pub bucket_start: Timestamp,
+    /// The values of the grouped dimensions, in the order of the request's `group_by`
+    #[serde(default)]
+    pub group: Vec<String>,
+    pub invocation_count: u64,
+    pub total_bytes_in: u64,
+    pub total_bytes_out: u64,
+    pub total_duration_micros: u64,
+}
+
+/// The results of a usage aggregation request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregatedUsage {
+    pub group_by: Vec<UsageDimension>,
+    /// One aggregate per bucket and group that had usage, ordered by bucket and then group
+    #[serde(default)]
+    pub aggregates: Vec<UsageAggregate>,
+}
+
+impl AggregatedUsage {
+    /// Aggregates a set of raw usage records according to a request. Totals saturate rather than overflow
+    pub fn from_records(
+        request: &AggregateUsageRequest,
+        records: &[UsageRecord],
+    ) -> AggregatedUsage {
+        let mut totals: BTreeMap<(Timestamp, Vec<&str>), UsageAggregate> = BTreeMap::new();
+        for r in records.iter().filter(|r| request.includes(r)) {
+            let bucket_start = request.window.bucket_start(r.timestamp);
+            let group: Vec<&str> = request.group_by.iter().map(|d| d.value_of(r)).collect();
+            let aggregate = totals
+                .entry((bucket_start, group.clone()))
+                .or_insert_with(|| UsageAggregate {
+                    bucket_start,
+                    group: group.iter().map(|v| v.to_string()).collect(),
+                    ..Default::default()
+                });
+            aggregate.invocation_count = aggregate.invocation_count.saturating_add(1);
+            aggregate.total_bytes_in = aggregate.total_bytes_in.saturating_add(r.bytes_in);
+            aggregate.total_bytes_out = aggregate.total_bytes_out.saturating_add(r.bytes_out);
+            aggregate.total_duration_micros = aggregate
+                .total_duration_micros
+                .saturating_add(r.duration_micros);
+        }
+        AggregatedUsage {
+            group_by: request.group_by.clone(),
+            aggregates: totals.into_values().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AggregateUsageRequest, AggregatedUsage, UsageRecord, UsageReport};
+    use crate::core::Timestamp;
+    use crate::Sample;
+
+    #[test]
+    fn usage_reports_only_include_records_in_their_window() {
+        let record = |at: u64, bytes_in: u64| UsageRecord {
+            timestamp: Timestamp::from_millis(at),
+            bytes_in,
+            ..UsageRecord::sample()
+        };
+        let records = vec![
+            record(999, 1),
+            record(1000, u64::MAX),
+            record(1500, 1),
+            record(2000, 1),
+        ];
+        let report = UsageReport::from_records(
+            Timestamp::from_millis(1000),
+            Timestamp::from_millis(2000),
+            &records,
+        );
+        assert_eq!(report.summaries.len(), 1);
+        assert_eq!(report.summaries[0].invocation_count, 2);
+        assert_eq!(report.summaries[0].total_bytes_in, u64::MAX);
+
+        let report = UsageReport::from_records(
+            Timestamp::from_millis(1000),
+            Timestamp::from_millis(0),
+            &records,
+        );
+        assert_eq!(report.summaries[0].invocation_count, 3);
+    }
+
+    #[test]
+    fn aggregates_usage_by_bucket_and_group() {
+        let request = AggregateUsageRequest::sample();
+        let start = request.window.start_time.as_millis();
+        let record = |actor: &str, capability: &str, offset: u64| UsageRecord {
+            actor: actor.to_string(),
+            capability: capability.to_string(),
+            timestamp: Timestamp::from_millis(start + offset),
+            ..UsageRecord::sample()
+        };
+        let records = vec![
+            record("a", "wascc:keyvalue", 10),
+            record("a", "wascc:keyvalue", 20),
+            record("b", "wascc:keyvalue", 30),
+            record("a", "wascc:keyvalue", 3_600_000),
+            record("a", "wascc:messaging", 40),
+        ];
+        let usage = AggregatedUsage::from_records(&request, &records);
+        let summary: Vec<(u64, Vec<String>, u64)> = usage
+            .aggregates
+            .iter()
+            .map(|a| {
+                (
+                    a.bucket_start.as_millis() - start,
+                    a.group.clone(),
+                    a.invocation_count,
+                )
+            })
+            .collect();
+        let group = |actor: &str| vec![actor.to_string(), "wascc:keyvalue".to_string()];
+        assert_eq!(
+            summary,
+            vec![
+                (0, group("a"), 2),
+                (0, group("b"), 1),
+                (3_600_000, group("a"), 1)
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! # MIME Message Parsing
+//!
+//! This module contains data types for the `wascc:emailparse` capability provider, which parses raw
+//! RFC 822/MIME messages and hands actors structured content. Attachments are stored in a blob store
+//! and referenced from the parsed message rather than carried inline
+
+use crate::blobstore::BlobReference;
+use crate::core::Timestamp;
+use crate::Sample;
+
+/// Guest sends a ParseMessageRequest to the provider, receives a ParsedMessage back
+pub const OP_PARSE_MESSAGE: &str = "ParseMessage";
+/// Provider delivers a ParsedMessage to an actor when an inbound message is ingested
+pub const OP_DELIVER_MESSAGE: &str = "DeliverParsedMessage";
+
+/// A request to parse a raw message. Exactly one of `raw` or `source` should be supplied
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseMessageRequest {
+    /// The raw RFC 822 bytes of the message
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub raw: Vec<u8>,
+    /// A blob containing the raw message, for messages too large to send inline
+    #[serde(default)]
+    pub source: Option<BlobReference>,
+    /// The blob store container in which the provider should store extracted attachments
+    pub attachment_container: String,
+}
+
+/// A single message header. Headers are kept as an ordered list because names may repeat
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// An attachment extracted from a message and stored in a blob store
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// The file name supplied by the sender, if any
+    #[serde(default)]
+    pub filename: String,
+    /// The MIME content type, e.g. `application/pdf`
+    pub content_type: String,
+    /// The decoded size of the attachment in bytes
+    pub byte_size: u64,
+    /// The `Content-ID` of inline attachments referenced from the HTML body
+    #[serde(default)]
+    pub content_id: Option<String>,
+    /// Where the attachment's decoded bytes were stored
+    pub blob: BlobReference,
+}
+
+/// A parsed message. Address fields contain the addresses as they appeared in the message headers
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedMessage {
+    #[serde(default)]
+    pub message_id: String,
+    pub from: String,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    #[serde(default)]
+    pub subject: String,
+    /// The time from the message's `Date` header, if present and valid
+    #[serde(default)]
+    pub date: Option<Timestamp>,
+    /// All headers of the message, in the order they appeared
+    #[serde(default)]
+    pub headers: Vec<MessageHeader>,
+    /// The decoded plain text body, if the message has one
+    #[serde(default)]
+    pub text_body: Option<String>,
+    /// The decoded HTML body, if the message has one
+    #[serde(default)]
+    pub html_body: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+impl Sample for ParsedMessage {
+    fn sample() -> Self {
+        ParsedMessage {
+            message_id: "<1234@example.com>".to_string(),
+            from: "Alice <alice@example.com>".to_string(),
+            to: vec!["bob@example.com".to_string()],
+            cc: vec![],
+            reply_to: None,
+            subject: "Quarterly report".to_string(),
+            date: Some(Timestamp::from_secs(1_594_000_000)),
+            headers: vec![MessageHeader {
+                name: "Subject".to_string(),
+                value: "Quarterly report".to_string(),
+            }],
+            text_body: Some("Report attached".to_string()),
+            html_body: None,
+            attachments: vec![Attachment {
+                filename: "report.pdf".to_string(),
+                content_type: "application/pdf".to_string(),
+                byte_size: 53400,
+                content_id: None,
+                blob: BlobReference {
+                    container: "attachments".to_string(),
+                    id: "report-1234.pdf".to_string(),
+                },
+            }],
+        }
+    }
+}
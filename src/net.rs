@@ -0,0 +1,126 @@
+//! # Raw Sockets
+//!
+//! This module contains data types for the `wascc:socket` capability provider. The provider manages
+//! TCP and UDP sockets on behalf of actors that speak custom protocols. Bytes flow in both directions
+//! as `ConnectionData` frames, which follow the same sequence numbering conventions as streamed blobs
+//! and HTTP bodies
+
+use crate::core::DurationMs;
+use crate::Sample;
+
+/// Guest sends an OpenConnection to the capability provider, receives a ConnectionOpened back
+pub const OP_OPEN_CONNECTION: &str = "OpenConnection";
+/// Guest sends a ConnectionData frame to the capability provider to write bytes to the socket
+pub const OP_SEND_DATA: &str = "SendData";
+/// Provider sends a ConnectionData frame to a guest when bytes are read from the socket
+pub const OP_RECEIVE_DATA: &str = "ReceiveData";
+/// Guest sends a CloseConnection to the capability provider, lack of error indicates success
+pub const OP_CLOSE_CONNECTION: &str = "CloseConnection";
+/// Provider sends ConnectionClosed to a guest when a connection ends for any reason
+pub const OP_CONNECTION_CLOSED: &str = "ConnectionClosed";
+
+/// The transport protocol of a connection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    Tcp,
+    /// Each `ConnectionData` frame is sent and received as a single datagram
+    Udp,
+}
+
+/// Requests that the provider wrap a TCP connection in TLS
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsOptions {
+    /// The name used for SNI and certificate verification. Defaults to the connection's host
+    #[serde(default)]
+    pub server_name: Option<String>,
+    /// PEM-encoded certificates to trust in addition to the provider's trust store
+    #[serde(default)]
+    pub ca_certs: Option<String>,
+    /// Disables certificate verification. Providers may refuse this option
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+}
+
+/// Opens a connection to a remote host
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenConnection {
+    /// An actor-chosen ID that identifies the connection in all subsequent frames
+    pub connection_id: String,
+    pub host: String,
+    pub port: u16,
+    pub protocol: Protocol,
+    /// TLS options, if the connection should use TLS. Not supported for UDP
+    #[serde(default)]
+    pub tls: Option<TlsOptions>,
+    /// How long to wait for the connection to be established before giving up
+    #[serde(default)]
+    pub connect_timeout: Option<DurationMs>,
+}
+
+impl Sample for OpenConnection {
+    fn sample() -> Self {
+        OpenConnection {
+            connection_id: "conn-1".to_string(),
+            host: "mail.example.com".to_string(),
+            port: 465,
+            protocol: Protocol::Tcp,
+            tls: Some(TlsOptions::default()),
+            connect_timeout: Some(DurationMs(5_000)),
+        }
+    }
+}
+
+/// The provider's response to an OpenConnection request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionOpened {
+    pub connection_id: String,
+    /// The local address of the socket, e.g. `10.0.0.5:49152`
+    pub local_addr: String,
+    /// The resolved remote address of the socket
+    pub remote_addr: String,
+}
+
+/// A frame of bytes written to or read from a connection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionData {
+    pub connection_id: String,
+    /// A sequence number that can be used for retry and ordering logic, starting at 0 for each direction
+    pub sequence_no: u64,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+}
+
+/// Closes a connection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseConnection {
+    pub connection_id: String,
+}
+
+/// The reason a connection ended
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    /// The actor closed the connection
+    Closed,
+    /// The remote end closed the connection
+    RemoteClosed,
+    /// The connection was idle for longer than the provider allows
+    IdleTimeout,
+    /// The connection failed with the given error
+    Error(String),
+}
+
+/// Notifies a guest that a connection has ended. No further frames will be delivered for the connection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionClosed {
+    pub connection_id: String,
+    pub reason: CloseReason,
+}
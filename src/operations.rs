@@ -0,0 +1,401 @@
+//! # Operation lookup
+//!
+//! This module contains a compile-time perfect hash table of every operation defined by this crate, keyed
+//! by capability ID and operation name. Hosts can use it to route invocations and to produce better
+//! diagnostics (e.g. naming the payload type an operation expects) without allocating
+
+use crate::capabilities::OperationDirection;
+use phf::phf_map;
+
+/// Static information about a single operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationInfo {
+    /// The operation name, e.g. `Get`
+    pub name: &'static str,
+    /// The direction in which the operation is invoked
+    pub direction: OperationDirection,
+    /// The type name of the request payload (e.g. `keyvalue::GetRequest`), or `None` if the payload is empty
+    pub request_type: Option<&'static str>,
+    /// The type name of the response payload, or `None` if the response is empty
+    pub response_type: Option<&'static str>,
+}
+
+macro_rules! op {
+    ($name:expr, $dir:ident, $req:expr, $resp:expr) => {
+        OperationInfo {
+            name: $name,
+            direction: OperationDirection::$dir,
+            request_type: $req,
+            response_type: $resp,
+        }
+    };
+}
+
+/// Looks up an operation supported by a capability. Operations common to all capabilities (such as
+/// `BindActor`) are found regardless of the capability ID
+pub fn lookup_operation(capability_id: &str, op: &str) -> Option<&'static OperationInfo> {
+    CAPABILITIES
+        .get(capability_id)
+        .and_then(|ops| ops.get(op))
+        .or_else(|| CORE_OPS.get(op))
+}
+
+/// Returns the operations supported by a capability, not including the operations common to all capabilities
+pub fn capability_operations(
+    capability_id: &str,
+) -> Option<&'static phf::Map<&'static str, OperationInfo>> {
+    CAPABILITIES.get(capability_id).copied()
+}
+
+static CAPABILITIES: phf::Map<&'static str, &'static phf::Map<&'static str, OperationInfo>> = phf_map! {
+    "wascc:messaging" => &MESSAGING_OPS,
+    "wascc:http_server" => &HTTP_SERVER_OPS,
+    "wascc:http_client" => &HTTP_CLIENT_OPS,
+    "wascc:keyvalue" => &KEYVALUE_OPS,
+    "wascc:blobstore" => &BLOBSTORE_OPS,
+    "wascc:eventstreams" => &EVENTSTREAMS_OPS,
+    "wascc:logging" => &LOGGING_OPS,
+    "wascc:extras" => &EXTRAS_OPS,
+    "wascc:metering" => &METERING_OPS,
+    "wascc:device" => &DEVICE_OPS,
+    "wascc:calendar" => &CALENDAR_OPS,
+    "wascc:emailparse" => &EMAIL_PARSE_OPS,
+    "wascc:docextract" => &DOC_EXTRACT_OPS,
+    "wascc:graphql" => &GRAPHQL_OPS,
+    "wascc:servicediscovery" => &DISCOVERY_OPS,
+    "wascc:outbox" => &OUTBOX_OPS,
+    "wascc:push-webhook" => &WEBHOOKS_OPS,
+    "wascc:textgen" => &TEXTGEN_OPS,
+    "wascc:coordination" => &COORDINATION_OPS,
+    "wascc:statemachine" => &STATE_OPS,
+    "wascc:socket" => &NET_OPS,
+    "wascc:session" => &SESSION_OPS,
+    "wascc:attestation" => &ATTESTATION_OPS,
+    "wascc:healthprobe" => &PROBES_OPS,
+    "wascc:docgen" => &DOC_GEN_OPS,
+    "wascc:vectorstore" => &VECTOR_STORE_OPS,
+    "wascc:leaderboard" => &LEADERBOARD_OPS,
+    "wascc:barcodes" => &BARCODE_OPS,
+    "wascc:datafeed" => &DATA_FEED_OPS,
+    "wascc:telephony" => &TELEPHONY_OPS,
+    "wascc:crdt" => &CRDT_OPS,
+    "wascc:batch" => &BATCH_OPS,
+    "wascc:anomaly" => &ANOMALY_OPS,
+    "wascc:sqldb" => &SQL_DB_OPS,
+    "wascc:terminal" => &TERMINAL_OPS,
+};
+
+static CORE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "PerformLiveUpdate" => op!("PerformLiveUpdate", ToActor, Some("core::LiveUpdate"), None),
+    "HealthRequest" => op!("HealthRequest", ToActor, Some("core::HealthRequest"), None),
+    "Initialize" => op!("Initialize", ToProvider, Some("core::InitializePayload"), Some("core::InitializeAck")),
+    "BindActor" => op!("BindActor", ToProvider, Some("core::CapabilityConfiguration"), None),
+    "RemoveActor" => op!("RemoveActor", ToProvider, Some("core::CapabilityConfiguration"), None),
+    "UpdateConfiguration" => op!("UpdateConfiguration", ToProvider, Some("core::ConfigurationDelta"), Some("core::ConfigurationUpdateResult")),
+    "ClaimsExpiring" => op!("ClaimsExpiring", ToActor, Some("core::ClaimsExpiring"), None),
+    "ClaimsRenewed" => op!("ClaimsRenewed", ToActor, Some("core::ClaimsRenewed"), None),
+    "ReadyCheck" => op!("ReadyCheck", ToProvider, None, Some("core::ReadinessReport")),
+    "QueryBindings" => op!("QueryBindings", ToProvider, Some("core::QueryBindings"), Some("core::BindingsSnapshot")),
+    "GetCapabilityDescriptor" => op!("GetCapabilityDescriptor", ToProvider, None, Some("capabilities::CapabilityDescriptor")),
+    "DrainStart" => op!("DrainStart", ToProvider, Some("capabilities::DrainStart"), Some("capabilities::DrainProgress")),
+    "DrainProgress" => op!("DrainProgress", ToActor, Some("capabilities::DrainProgress"), None),
+    "DrainComplete" => op!("DrainComplete", ToActor, Some("capabilities::DrainComplete"), None),
+};
+
+static MESSAGING_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "Publish" => op!("Publish", ToProvider, Some("messaging::BrokerMessage"), None),
+    "DeliverMessage" => op!("DeliverMessage", ToActor, Some("messaging::BrokerMessage"), None),
+    "Request" => op!("Request", ToProvider, Some("messaging::RequestMessage"), Some("messaging::BrokerMessage")),
+    "Subscribe" => op!("Subscribe", ToProvider, Some("messaging::SubscribeRequest"), None),
+    "Unsubscribe" => op!("Unsubscribe", ToProvider, Some("messaging::UnsubscribeRequest"), None),
+    "AckDelivery" => op!("AckDelivery", ToProvider, Some("messaging::AckDelivery"), None),
+    "DeclareTopology" => op!("DeclareTopology", ToProvider, Some("messaging::DeclareTopologyRequest"), None),
+};
+
+static HTTP_SERVER_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "HandleRequest" => op!("HandleRequest", ToActor, Some("http::Request"), Some("http::Response")),
+    "ReceiveBodyChunk" => op!("ReceiveBodyChunk", ToActor, Some("http::BodyChunk"), None),
+    "SendBodyChunk" => op!("SendBodyChunk", ToProvider, Some("http::BodyChunk"), None),
+};
+
+static HTTP_CLIENT_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "PerformRequest" => op!("PerformRequest", ToProvider, Some("http::Request"), Some("http::Response")),
+};
+
+static KEYVALUE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "Add" => op!("Add", ToProvider, Some("keyvalue::AddRequest"), Some("keyvalue::AddResponse")),
+    "Get" => op!("Get", ToProvider, Some("keyvalue::GetRequest"), Some("keyvalue::GetResponse")),
+    "Set" => op!("Set", ToProvider, Some("keyvalue::SetRequest"), Some("keyvalue::SetResponse")),
+    "SetWithExpiration" => op!("SetWithExpiration", ToProvider, Some("keyvalue::SetWithExpirationRequest"), Some("keyvalue::SetResponse")),
+    "CompareAndSet" => op!("CompareAndSet", ToProvider, Some("keyvalue::CompareAndSetRequest"), Some("keyvalue::CompareAndSetResponse")),
+    "Del" => op!("Del", ToProvider, Some("keyvalue::DelRequest"), Some("keyvalue::DelResponse")),
+    "Clear" => op!("Clear", ToProvider, Some("keyvalue::ListClearRequest"), Some("keyvalue::DelResponse")),
+    "Range" => op!("Range", ToProvider, Some("keyvalue::ListRangeRequest"), Some("keyvalue::ListRangeResponse")),
+    "Push" => op!("Push", ToProvider, Some("keyvalue::ListPushRequest"), Some("keyvalue::ListResponse")),
+    "ListItemDelete" => op!("ListItemDelete", ToProvider, Some("keyvalue::ListDelItemRequest"), Some("keyvalue::ListResponse")),
+    "SetAdd" => op!("SetAdd", ToProvider, Some("keyvalue::SetAddRequest"), Some("keyvalue::SetOperationResponse")),
+    "SetRemove" => op!("SetRemove", ToProvider, Some("keyvalue::SetRemoveRequest"), Some("keyvalue::SetOperationResponse")),
+    "SetUnion" => op!("SetUnion", ToProvider, Some("keyvalue::SetUnionRequest"), Some("keyvalue::SetQueryResponse")),
+    "SetIntersection" => op!("SetIntersection", ToProvider, Some("keyvalue::SetIntersectionRequest"), Some("keyvalue::SetQueryResponse")),
+    "SetQuery" => op!("SetQuery", ToProvider, Some("keyvalue::SetQueryRequest"), Some("keyvalue::SetQueryResponse")),
+    "KeyExists" => op!("KeyExists", ToProvider, Some("keyvalue::KeyExistsQuery"), Some("keyvalue::GetResponse")),
+    "Append" => op!("Append", ToProvider, Some("keyvalue::AppendRequest"), Some("keyvalue::AppendResponse")),
+    "GetRange" => op!("GetRange", ToProvider, Some("keyvalue::GetRangeRequest"), Some("keyvalue::GetRangeResponse")),
+    "JsonGet" => op!("JsonGet", ToProvider, Some("keyvalue::JsonGetRequest"), Some("keyvalue::JsonGetResponse")),
+    "JsonSet" => op!("JsonSet", ToProvider, Some("keyvalue::JsonSetRequest"), Some("keyvalue::JsonSetResponse")),
+    "PfAdd" => op!("PfAdd", ToProvider, Some("keyvalue::PfAddRequest"), Some("keyvalue::PfAddResponse")),
+    "PfCount" => op!("PfCount", ToProvider, Some("keyvalue::PfCountRequest"), Some("keyvalue::PfCountResponse")),
+    "PfMerge" => op!("PfMerge", ToProvider, Some("keyvalue::PfMergeRequest"), None),
+    "BloomReserve" => op!("BloomReserve", ToProvider, Some("keyvalue::BloomReserveRequest"), None),
+    "BloomAdd" => op!("BloomAdd", ToProvider, Some("keyvalue::BloomAddRequest"), Some("keyvalue::BloomAddResponse")),
+    "BloomCheck" => op!("BloomCheck", ToProvider, Some("keyvalue::BloomCheckRequest"), Some("keyvalue::BloomCheckResponse")),
+    "StartExport" => op!("StartExport", ToProvider, Some("keyvalue::ExportRequest"), Some("keyvalue::ExportStarted")),
+    "ReceiveExportChunk" => op!("ReceiveExportChunk", ToActor, Some("keyvalue::ExportChunk"), None),
+    "ImportChunk" => op!("ImportChunk", ToProvider, Some("keyvalue::ImportChunk"), Some("keyvalue::ImportResult")),
+    "SubscribeKeyspace" => op!("SubscribeKeyspace", ToProvider, Some("keyvalue::SubscribeKeyspace"), None),
+    "UnsubscribeKeyspace" => op!("UnsubscribeKeyspace", ToProvider, Some("keyvalue::UnsubscribeKeyspace"), None),
+    "DeliverCacheFill" => op!("DeliverCacheFill", ToActor, Some("keyvalue::CacheFillEvent"), None),
+    "DeliverCacheInvalidate" => op!("DeliverCacheInvalidate", ToActor, Some("keyvalue::CacheInvalidateEvent"), None),
+};
+
+static BLOBSTORE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "CreateContainer" => op!("CreateContainer", ToProvider, Some("blobstore::Container"), Some("blobstore::Container")),
+    "RemoveContainer" => op!("RemoveContainer", ToProvider, Some("blobstore::Container"), None),
+    "RemoveObject" => op!("RemoveObject", ToProvider, Some("blobstore::Blob"), None),
+    "ListObjects" => op!("ListObjects", ToProvider, Some("blobstore::Container"), Some("blobstore::BlobList")),
+    "UploadChunk" => op!("UploadChunk", ToProvider, Some("blobstore::FileChunk"), None),
+    "StartDownload" => op!("StartDownload", ToProvider, Some("blobstore::StreamRequest"), Some("blobstore::Transfer")),
+    "ResumeDownload" => op!("ResumeDownload", ToProvider, Some("blobstore::ResumeDownloadRequest"), None),
+    "GetTransferStatus" => op!("GetTransferStatus", ToProvider, Some("blobstore::TransferStatusRequest"), Some("blobstore::TransferStatus")),
+    "StartUpload" => op!("StartUpload", ToProvider, Some("blobstore::FileChunk"), None),
+    "ReceiveChunk" => op!("ReceiveChunk", ToActor, Some("blobstore::FileChunk"), None),
+    "GetObjectInfo" => op!("GetObjectInfo", ToProvider, Some("blobstore::ObjectInfoRequest"), Some("blobstore::Blob")),
+    "DeleteObjects" => op!("DeleteObjects", ToProvider, Some("blobstore::DeleteObjectsRequest"), Some("blobstore::DeleteResult")),
+    "GetContainerStats" => op!("GetContainerStats", ToProvider, Some("blobstore::ContainerStatsRequest"), Some("blobstore::ContainerStats")),
+};
+
+static EVENTSTREAMS_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "DeliverEvent" => op!("DeliverEvent", ToActor, Some("eventstreams::Event"), None),
+    "WriteEvent" => op!("WriteEvent", ToProvider, Some("eventstreams::Event"), Some("eventstreams::WriteResponse")),
+    "QueryStream" => op!("QueryStream", ToProvider, Some("eventstreams::StreamQuery"), Some("eventstreams::StreamResults")),
+    "SubscribeStreams" => op!("SubscribeStreams", ToProvider, Some("eventstreams::MultiStreamSubscribe"), Some("eventstreams::MultiStreamSubscription")),
+    "DeliverMergedEvent" => op!("DeliverMergedEvent", ToActor, Some("eventstreams::MergedEvent"), None),
+    "UnsubscribeStreams" => op!("UnsubscribeStreams", ToProvider, Some("eventstreams::MultiStreamUnsubscribe"), None),
+    "CompactStream" => op!("CompactStream", ToProvider, Some("eventstreams::CompactStreamRequest"), Some("eventstreams::CompactStreamResult")),
+};
+
+static LOGGING_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "WriteLog" => op!("WriteLog", ToProvider, Some("logging::WriteLogRequest"), None),
+    "QueryLog" => op!("QueryLog", ToProvider, Some("logging::LogQueryRequest"), Some("logging::LogQueryResults")),
+};
+
+static EXTRAS_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "RequestGuid" => op!("RequestGuid", ToProvider, Some("extras::GeneratorRequest"), Some("extras::GeneratorResult")),
+    "RequestSequence" => op!("RequestSequence", ToProvider, Some("extras::GeneratorRequest"), Some("extras::GeneratorResult")),
+    "RequestRandom" => op!("RequestRandom", ToProvider, Some("extras::GeneratorRequest"), Some("extras::GeneratorResult")),
+    "RequestVrf" => op!("RequestVrf", ToProvider, Some("extras::VrfRequest"), Some("extras::VrfResult")),
+    "RequestSeededRandom" => op!("RequestSeededRandom", ToProvider, Some("extras::SeededRandomRequest"), Some("extras::SeededRandomResult")),
+};
+
+static METERING_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "RecordUsage" => op!("RecordUsage", ToProvider, Some("metering::UsageRecord"), None),
+    "QueryUsage" => op!("QueryUsage", ToProvider, Some("metering::UsageReportRequest"), Some("metering::UsageReport")),
+    "AggregateUsage" => op!("AggregateUsage", ToProvider, Some("metering::AggregateUsageRequest"), Some("metering::AggregatedUsage")),
+};
+
+static DEVICE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "ReadPin" => op!("ReadPin", ToProvider, Some("device::ReadPinRequest"), Some("device::PinState")),
+    "WritePin" => op!("WritePin", ToProvider, Some("device::WritePinRequest"), None),
+    "I2cTransfer" => op!("I2cTransfer", ToProvider, Some("device::I2cTransfer"), Some("device::TransferResponse")),
+    "SpiTransfer" => op!("SpiTransfer", ToProvider, Some("device::SpiTransfer"), Some("device::TransferResponse")),
+    "ListDevices" => op!("ListDevices", ToProvider, None, Some("device::DeviceList")),
+};
+
+static CALENDAR_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "CreateEvent" => op!("CreateEvent", ToProvider, Some("calendar::CalendarEvent"), Some("calendar::CalendarEvent")),
+    "UpdateEvent" => op!("UpdateEvent", ToProvider, Some("calendar::CalendarEvent"), None),
+    "DeleteEvent" => op!("DeleteEvent", ToProvider, Some("calendar::DeleteEventRequest"), None),
+    "ListEvents" => op!("ListEvents", ToProvider, Some("calendar::EventQuery"), Some("calendar::EventList")),
+    "QueryAvailability" => op!("QueryAvailability", ToProvider, Some("calendar::AvailabilityQuery"), Some("calendar::FreeBusyResponse")),
+};
+
+static EMAIL_PARSE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "ParseMessage" => op!("ParseMessage", ToProvider, Some("mime::ParseMessageRequest"), Some("mime::ParsedMessage")),
+    "DeliverParsedMessage" => op!("DeliverParsedMessage", ToActor, Some("mime::ParsedMessage"), None),
+};
+
+static DOC_EXTRACT_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "ExtractText" => op!("ExtractText", ToProvider, Some("docextract::ExtractTextRequest"), Some("docextract::ExtractedDocument")),
+};
+
+static GRAPHQL_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "HandleGraphQLRequest" => op!("HandleGraphQLRequest", ToActor, Some("graphql::GraphQLRequest"), Some("graphql::GraphQLResponse")),
+    "StartSubscription" => op!("StartSubscription", ToActor, Some("graphql::SubscriptionStart"), None),
+    "StopSubscription" => op!("StopSubscription", ToActor, Some("graphql::SubscriptionStop"), None),
+    "PublishSubscriptionEvent" => op!("PublishSubscriptionEvent", ToProvider, Some("graphql::SubscriptionEvent"), None),
+    "CompleteSubscription" => op!("CompleteSubscription", ToProvider, Some("graphql::SubscriptionStop"), None),
+};
+
+static DISCOVERY_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "RegisterService" => op!("RegisterService", ToProvider, Some("discovery::RegisterService"), None),
+    "DeregisterService" => op!("DeregisterService", ToProvider, Some("discovery::DeregisterService"), None),
+    "QueryService" => op!("QueryService", ToProvider, Some("discovery::QueryService"), Some("discovery::ServiceInstances")),
+};
+
+static OUTBOX_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "EnqueueOutbox" => op!("EnqueueOutbox", ToProvider, Some("outbox::EnqueueOutbox"), Some("outbox::EnqueueOutboxResponse")),
+    "DrainOutbox" => op!("DrainOutbox", ToProvider, Some("outbox::DrainOutbox"), Some("outbox::OutboxBatch")),
+    "AckOutbox" => op!("AckOutbox", ToProvider, Some("outbox::AckOutbox"), None),
+};
+
+static WEBHOOKS_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "RegisterWebhook" => op!("RegisterWebhook", ToProvider, Some("webhooks::WebhookRegistration"), Some("webhooks::RegistrationResult")),
+    "UnregisterWebhook" => op!("UnregisterWebhook", ToProvider, Some("webhooks::UnregisterWebhook"), None),
+    "PublishWebhookEvent" => op!("PublishWebhookEvent", ToProvider, Some("webhooks::WebhookEvent"), Some("webhooks::PublishResult")),
+    "GetDeliveryStatus" => op!("GetDeliveryStatus", ToProvider, Some("webhooks::DeliveryStatusRequest"), Some("webhooks::DeliveryReport")),
+    "DeliveryStatusChanged" => op!("DeliveryStatusChanged", ToActor, Some("webhooks::DeliveryReport"), None),
+};
+
+static TEXTGEN_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "Complete" => op!("Complete", ToProvider, Some("textgen::CompletionRequest"), Some("textgen::CompletionResponse")),
+    "StartCompletionStream" => op!("StartCompletionStream", ToProvider, Some("textgen::CompletionRequest"), None),
+    "DeliverCompletionChunk" => op!("DeliverCompletionChunk", ToActor, Some("textgen::CompletionChunk"), None),
+    "CreateEmbeddings" => op!("CreateEmbeddings", ToProvider, Some("textgen::EmbeddingRequest"), Some("textgen::EmbeddingResult")),
+};
+
+static COORDINATION_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "Campaign" => op!("Campaign", ToProvider, Some("coordination::CampaignRequest"), None),
+    "LeadershipGranted" => op!("LeadershipGranted", ToActor, Some("coordination::LeadershipGranted"), None),
+    "LeadershipRevoked" => op!("LeadershipRevoked", ToActor, Some("coordination::LeadershipRevoked"), None),
+    "Resign" => op!("Resign", ToProvider, Some("coordination::ResignRequest"), None),
+    "QueryLeader" => op!("QueryLeader", ToProvider, Some("coordination::LeaderQuery"), Some("coordination::LeaderInfo")),
+};
+
+static STATE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "SaveSnapshot" => op!("SaveSnapshot", ToProvider, Some("state::SaveSnapshot"), Some("state::SaveSnapshotResponse")),
+    "LoadSnapshot" => op!("LoadSnapshot", ToProvider, Some("state::LoadSnapshotRequest"), Some("state::Snapshot")),
+    "PurgeSnapshots" => op!("PurgeSnapshots", ToProvider, Some("state::PurgeSnapshots"), Some("state::PurgeSnapshotsResponse")),
+};
+
+static NET_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "OpenConnection" => op!("OpenConnection", ToProvider, Some("net::OpenConnection"), Some("net::ConnectionOpened")),
+    "SendData" => op!("SendData", ToProvider, Some("net::ConnectionData"), None),
+    "ReceiveData" => op!("ReceiveData", ToActor, Some("net::ConnectionData"), None),
+    "CloseConnection" => op!("CloseConnection", ToProvider, Some("net::CloseConnection"), None),
+    "ConnectionClosed" => op!("ConnectionClosed", ToActor, Some("net::ConnectionClosed"), None),
+};
+
+static SESSION_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "CreateSession" => op!("CreateSession", ToProvider, Some("session::CreateSession"), Some("session::SessionData")),
+    "GetSession" => op!("GetSession", ToProvider, Some("session::GetSession"), Some("session::SessionData")),
+    "TouchSession" => op!("TouchSession", ToProvider, Some("session::TouchSession"), Some("session::SessionData")),
+    "DestroySession" => op!("DestroySession", ToProvider, Some("session::DestroySession"), None),
+};
+
+static ATTESTATION_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "RequestAttestation" => op!("RequestAttestation", ToProvider, Some("attestation::RemoteAttestationRequest"), Some("attestation::AttestationReport")),
+    "VerifyAttestation" => op!("VerifyAttestation", ToProvider, Some("attestation::VerifyAttestation"), Some("attestation::VerifyAttestationResponse")),
+};
+
+static PROBES_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "ScheduleProbe" => op!("ScheduleProbe", ToProvider, Some("probes::ProbeSpec"), None),
+    "CancelProbe" => op!("CancelProbe", ToProvider, Some("probes::CancelProbe"), None),
+    "RunProbe" => op!("RunProbe", ToProvider, Some("probes::ProbeSpec"), Some("probes::ProbeResult")),
+    "DeliverProbeResult" => op!("DeliverProbeResult", ToActor, Some("probes::ProbeResult"), None),
+};
+
+static DOC_GEN_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "GenerateDocument" => op!("GenerateDocument", ToProvider, Some("docgen::GenerateDocumentRequest"), Some("docgen::GeneratedDocument")),
+};
+
+static VECTOR_STORE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "CreateCollection" => op!("CreateCollection", ToProvider, Some("vectorstore::CollectionSpec"), None),
+    "DeleteCollection" => op!("DeleteCollection", ToProvider, Some("vectorstore::Collection"), None),
+    "ListCollections" => op!("ListCollections", ToProvider, None, Some("vectorstore::CollectionList")),
+    "UpsertVectors" => op!("UpsertVectors", ToProvider, Some("vectorstore::UpsertVectors"), Some("vectorstore::UpsertResult")),
+    "DeleteVectors" => op!("DeleteVectors", ToProvider, Some("vectorstore::DeleteVectors"), None),
+    "Query" => op!("Query", ToProvider, Some("vectorstore::SimilarityQuery"), Some("vectorstore::QueryMatches")),
+};
+
+static LEADERBOARD_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "SubmitScore" => op!("SubmitScore", ToProvider, Some("leaderboard::SubmitScore"), Some("leaderboard::MemberRank")),
+    "TopN" => op!("TopN", ToProvider, Some("leaderboard::TopNQuery"), Some("leaderboard::RankedEntries")),
+    "RankOfMember" => op!("RankOfMember", ToProvider, Some("leaderboard::RankOfMemberQuery"), Some("leaderboard::MemberRank")),
+    "RemoveMember" => op!("RemoveMember", ToProvider, Some("leaderboard::RemoveMember"), None),
+};
+
+static BARCODE_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "GenerateCode" => op!("GenerateCode", ToProvider, Some("barcode::GenerateCodeRequest"), Some("barcode::GeneratedCode")),
+    "DecodeImage" => op!("DecodeImage", ToProvider, Some("barcode::DecodeImageRequest"), Some("barcode::DecodedContent")),
+};
+
+static DATA_FEED_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "QueryFeed" => op!("QueryFeed", ToProvider, Some("datafeed::FeedQuery"), Some("datafeed::FeedResult")),
+    "SubscribeFeed" => op!("SubscribeFeed", ToProvider, Some("datafeed::FeedSubscription"), None),
+    "UnsubscribeFeed" => op!("UnsubscribeFeed", ToProvider, Some("datafeed::FeedUnsubscribe"), None),
+    "DeliverFeedUpdate" => op!("DeliverFeedUpdate", ToActor, Some("datafeed::FeedUpdate"), None),
+};
+
+static TELEPHONY_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "InitiateCall" => op!("InitiateCall", ToProvider, Some("telephony::InitiateCall"), Some("telephony::CallHandle")),
+    "DeliverCallEvent" => op!("DeliverCallEvent", ToActor, Some("telephony::CallEvent"), None),
+    "PlayAudio" => op!("PlayAudio", ToProvider, Some("telephony::PlayAudio"), None),
+    "HangupCall" => op!("HangupCall", ToProvider, Some("telephony::HangupCall"), None),
+};
+
+static CRDT_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "UpdateGCounter" => op!("UpdateGCounter", ToProvider, Some("crdt::GCounterUpdate"), Some("crdt::CrdtValue")),
+    "UpdatePnCounter" => op!("UpdatePnCounter", ToProvider, Some("crdt::PnCounterUpdate"), Some("crdt::CrdtValue")),
+    "UpdateOrSet" => op!("UpdateOrSet", ToProvider, Some("crdt::OrSetOp"), Some("crdt::CrdtValue")),
+    "GetCrdtState" => op!("GetCrdtState", ToProvider, Some("crdt::GetCrdtState"), Some("crdt::StateSync")),
+    "MergeCrdtState" => op!("MergeCrdtState", ToProvider, Some("crdt::StateSync"), Some("crdt::StateSync")),
+};
+
+static BATCH_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "SubmitJob" => op!("SubmitJob", ToProvider, Some("batchjobs::SubmitJob"), Some("batchjobs::JobHandle")),
+    "QueryJobStatus" => op!("QueryJobStatus", ToProvider, Some("batchjobs::JobStatusQuery"), Some("batchjobs::JobStatus")),
+    "CancelJob" => op!("CancelJob", ToProvider, Some("batchjobs::CancelJob"), None),
+    "DeliverJobStatus" => op!("DeliverJobStatus", ToActor, Some("batchjobs::JobStatus"), None),
+};
+
+static ANOMALY_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "IngestDataPoints" => op!("IngestDataPoints", ToProvider, Some("analytics::IngestDataPoints"), None),
+    "QueryAnomalies" => op!("QueryAnomalies", ToProvider, Some("analytics::AnomalyQuery"), Some("analytics::AnomalyReport")),
+    "Forecast" => op!("Forecast", ToProvider, Some("analytics::ForecastRequest"), Some("analytics::Forecast")),
+};
+
+static SQL_DB_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "ExecuteQuery" => op!("ExecuteQuery", ToProvider, Some("sqldb::QueryRequest"), Some("sqldb::ResultSet")),
+    "ExecuteStatement" => op!("ExecuteStatement", ToProvider, Some("sqldb::QueryRequest"), Some("sqldb::ExecuteResult")),
+    "BeginTransaction" => op!("BeginTransaction", ToProvider, Some("sqldb::BeginTransaction"), Some("sqldb::TransactionHandle")),
+    "CommitTransaction" => op!("CommitTransaction", ToProvider, Some("sqldb::EndTransaction"), None),
+    "RollbackTransaction" => op!("RollbackTransaction", ToProvider, Some("sqldb::EndTransaction"), None),
+};
+
+static TERMINAL_OPS: phf::Map<&'static str, OperationInfo> = phf_map! {
+    "OpenSession" => op!("OpenSession", ToActor, Some("exec::OpenSession"), None),
+    "DeliverSessionInput" => op!("DeliverSessionInput", ToActor, Some("exec::SessionInput"), None),
+    "SendSessionOutput" => op!("SendSessionOutput", ToProvider, Some("exec::SessionOutput"), None),
+    "CloseSession" => op!("CloseSession", Both, Some("exec::CloseSession"), None),
+};
+
+#[cfg(test)]
+mod test {
+    use super::{lookup_operation, CAPABILITIES};
+    use crate::capabilities::OperationDirection;
+
+    #[test]
+    fn lookup_finds_capability_and_core_operations() {
+        let op = lookup_operation("wascc:keyvalue", crate::keyvalue::OP_GET).unwrap();
+        assert_eq!(op.direction, OperationDirection::ToProvider);
+        assert_eq!(op.response_type, Some("keyvalue::GetResponse"));
+        assert!(lookup_operation("wascc:keyvalue", crate::core::OP_BIND_ACTOR).is_some());
+        assert!(lookup_operation("wascc:keyvalue", crate::messaging::OP_PUBLISH_MESSAGE).is_none());
+
+        for (_, ops) in CAPABILITIES.entries() {
+            for (key, info) in ops.entries() {
+                assert_eq!(key, &info.name);
+            }
+        }
+    }
+}
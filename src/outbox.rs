@@ -0,0 +1,140 @@
+//! # Transactional Outbox
+//!
+//! This module contains data types for the `wascc:outbox` capability. A provider (or host plugin)
+//! implementing this capability stores key-value state changes together with the messages that
+//! announce them in a single transaction, then relays the messages to the message broker. This
+//! avoids the inconsistency of an actor saving state and publishing as two separate operations
+
+use crate::core::{DurationMs, Timestamp};
+use crate::messaging::BrokerMessage;
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends an EnqueueOutbox to the capability provider, receives an EnqueueOutboxResponse back
+pub const OP_ENQUEUE_OUTBOX: &str = "EnqueueOutbox";
+/// Relay sends a DrainOutbox to the capability provider, receives an OutboxBatch back
+pub const OP_DRAIN_OUTBOX: &str = "DrainOutbox";
+/// Relay sends an AckOutbox to the capability provider once entries have been published, lack of error indicates success
+pub const OP_ACK_OUTBOX: &str = "AckOutbox";
+
+/// A message waiting in the outbox to be published to the broker
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    /// Uniquely identifies the entry. Assigned by the provider when the entry is enqueued, consumers
+    /// can use this to de-duplicate messages that are relayed more than once
+    #[serde(default)]
+    pub entry_id: String,
+    /// The subject on which the message will be published
+    pub subject: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub body: Vec<u8>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The time at which the entry was enqueued, assigned by the provider
+    #[serde(default)]
+    pub enqueued_at: Timestamp,
+    /// The number of times the relay has drained this entry without acknowledging it
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+impl OutboxEntry {
+    /// Creates a new entry to be enqueued
+    pub fn new(subject: &str, body: Vec<u8>) -> OutboxEntry {
+        OutboxEntry {
+            entry_id: String::new(),
+            subject: subject.to_string(),
+            body,
+            headers: HashMap::new(),
+            enqueued_at: Timestamp::default(),
+            attempts: 0,
+        }
+    }
+
+    /// Produces the message to publish (with `messaging::OP_PUBLISH_MESSAGE`) for this entry
+    pub fn to_broker_message(&self) -> BrokerMessage {
+        BrokerMessage {
+            subject: self.subject.to_string(),
+            reply_to: String::new(),
+            body: self.body.clone(),
+            headers: self.headers.clone(),
+            expiration: None,
+            delivery_tag: None,
+            priority: None,
+            group_id: None,
+            signature: None,
+            signer: None,
+        }
+    }
+}
+
+/// A change to a single key, applied in the same transaction as the enqueued entries
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StateWrite {
+    pub key: String,
+    /// The new value of the key, or `None` to delete it
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// Atomically applies a set of state changes and enqueues the messages that announce them. Either all
+/// writes are applied and all entries are enqueued, or nothing is
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueOutbox {
+    #[serde(default)]
+    pub writes: Vec<StateWrite>,
+    pub entries: Vec<OutboxEntry>,
+}
+
+impl Sample for EnqueueOutbox {
+    fn sample() -> Self {
+        EnqueueOutbox {
+            writes: vec![StateWrite {
+                key: "order:42".to_string(),
+                value: Some("shipped".to_string()),
+            }],
+            entries: vec![OutboxEntry::new(
+                "orders.shipped",
+                br#"{"orderId":42}"#.to_vec(),
+            )],
+        }
+    }
+}
+
+/// The provider's response to an enqueue request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueOutboxResponse {
+    /// The IDs assigned to the enqueued entries, in the order they were supplied
+    pub entry_ids: Vec<String>,
+}
+
+/// Requests the oldest unacknowledged entries in the outbox. Drained entries remain in the outbox
+/// until they are acknowledged, and are drained again once the visibility timeout elapses
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainOutbox {
+    /// The maximum number of entries to return
+    pub max_entries: u32,
+    /// Time for which drained entries are hidden from subsequent drain requests
+    pub visibility_timeout: DurationMs,
+}
+
+/// A batch of entries drained from the outbox, in the order they were enqueued
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxBatch {
+    #[serde(default)]
+    pub entries: Vec<OutboxEntry>,
+}
+
+/// Removes entries from the outbox after they have been published
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AckOutbox {
+    pub entry_ids: Vec<String>,
+}
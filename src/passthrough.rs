@@ -0,0 +1,110 @@
+// Conversion between message pack payloads and JSON value trees, used by `serialize_value` and
+// `deserialize_to_value`. JSON has no binary type, so message pack binary values are represented as an
+// object with the single member `BINARY_TAG`, holding the base64-encoded bytes, and converted back when
+// the value is serialized. Payloads that JSON cannot represent without loss are rejected rather than
+// altered: maps with keys that are not strings, maps that would read back as binary because their only
+// member is a `BINARY_TAG` string, and non-finite floats.
+
+use crate::value::Value as MsgpackValue;
+use crate::BINARY_TAG;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{Map, Number, Value};
+
+pub(crate) struct ValueOut(pub Value);
+
+impl<'de> Deserialize<'de> for ValueOut {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ValueOut, D::Error> {
+        let value = MsgpackValue::deserialize(deserializer)?;
+        to_json(value).map(ValueOut).map_err(de::Error::custom)
+    }
+}
+
+fn to_json(value: MsgpackValue) -> Result<Value, String> {
+    Ok(match value {
+        MsgpackValue::Nil => Value::Null,
+        MsgpackValue::Bool(v) => Value::Bool(v),
+        MsgpackValue::Int(v) => Value::Number(v.into()),
+        MsgpackValue::UInt(v) => Value::Number(v.into()),
+        MsgpackValue::Float(v) => Number::from_f64(v)
+            .map(Value::Number)
+            .ok_or_else(|| format!("{} cannot be represented in JSON", v))?,
+        MsgpackValue::Str(v) => Value::String(v),
+        MsgpackValue::Bytes(v) => {
+            let mut tagged = Map::new();
+            tagged.insert(BINARY_TAG.to_string(), Value::String(base64::encode(v)));
+            Value::Object(tagged)
+        }
+        MsgpackValue::Seq(items) => {
+            Value::Array(items.into_iter().map(to_json).collect::<Result<_, _>>()?)
+        }
+        MsgpackValue::Map(entries) => {
+            let mut members = Map::new();
+            for (k, v) in entries {
+                let key = match k {
+                    MsgpackValue::Str(s) => s,
+                    other => return Err(format!("map key {:?} is not a string", other)),
+                };
+                members.insert(key, to_json(v)?);
+            }
+            if binary(&members).is_some() {
+                return Err(format!(
+                    "a map whose only member is a {} string would read back as binary",
+                    BINARY_TAG
+                ));
+            }
+            Value::Object(members)
+        }
+    })
+}
+
+pub(crate) struct ValueIn<'a>(pub &'a Value);
+
+impl Serialize for ValueIn<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    serializer.serialize_u64(u)
+                } else if let Some(i) = n.as_i64() {
+                    serializer.serialize_i64(i)
+                } else {
+                    serializer.serialize_f64(n.as_f64().unwrap_or_default())
+                }
+            }
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&ValueIn(item))?;
+                }
+                seq.end()
+            }
+            Value::Object(members) => match binary(members) {
+                Some(encoded) => {
+                    let bytes = base64::decode(encoded).map_err(|e| {
+                        ser::Error::custom(format!("invalid {} value: {}", BINARY_TAG, e))
+                    })?;
+                    serializer.serialize_bytes(&bytes)
+                }
+                None => {
+                    let mut map = serializer.serialize_map(Some(members.len()))?;
+                    for (k, v) in members {
+                        map.serialize_entry(k, &ValueIn(v))?;
+                    }
+                    map.end()
+                }
+            },
+        }
+    }
+}
+
+fn binary(members: &Map<String, Value>) -> Option<&str> {
+    if members.len() == 1 {
+        members.get(BINARY_TAG).and_then(Value::as_str)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,215 @@
+//! # Pipelines
+//!
+//! A small builder for chains of capability calls, e.g. receiving an HTTP request, transforming it,
+//! storing the result in a key-value store and announcing it on a message broker. Each step receives the
+//! value produced by the previous one. The first failing step short-circuits the rest of the chain, and
+//! the error it produced is reported along with the step's position and description
+//!
+//! ```
+//! # use wascc_codec::capabilities::Dispatcher;
+//! # use wascc_codec::keyvalue::{SetRequest, SetResponse, OP_SET};
+//! # use wascc_codec::pipeline::Pipeline;
+//! # use std::error::Error;
+//! # struct Echo;
+//! # impl Dispatcher for Echo {
+//! #     fn dispatch(&self, _: &str, _: &str, _: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+//! #         wascc_codec::serialize(SetResponse::new("42"))
+//! #     }
+//! # }
+//! # let dispatcher = Echo;
+//! let stored = Pipeline::start(&dispatcher, 42)
+//!     .map(|n| SetRequest::new("answer", &n.to_string(), 0))
+//!     .call::<SetResponse>("wascc:keyvalue", OP_SET)
+//!     .map(|resp| resp.value)
+//!     .finish();
+//! assert_eq!(stored.unwrap(), "42");
+//! ```
+
+use crate::capabilities::Dispatcher;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// A chain of steps over a dispatcher, holding the value produced by the last step or the error of the
+/// step that failed
+pub struct Pipeline<'a, T> {
+    dispatcher: &'a dyn Dispatcher,
+    steps: usize,
+    state: Result<T, PipelineError>,
+}
+
+impl<'a, T> Pipeline<'a, T> {
+    /// Starts a pipeline with an initial value
+    pub fn start(dispatcher: &'a dyn Dispatcher, input: T) -> Pipeline<'a, T> {
+        Pipeline {
+            dispatcher,
+            steps: 0,
+            state: Ok(input),
+        }
+    }
+
+    /// Invokes an operation with the current value as its payload, continuing with the decoded response
+    pub fn call<R: DeserializeOwned>(self, target: &str, op: &str) -> Pipeline<'a, R>
+    where
+        T: Serialize,
+    {
+        let dispatcher = self.dispatcher;
+        self.step(format!("{} on {}", op, target), |value| {
+            let payload = crate::serialize(value)?;
+            let response = dispatcher.dispatch(target, op, &payload)?;
+            crate::deserialize(&response)
+        })
+    }
+
+    /// Invokes an operation whose response carries nothing, i.e. lack of error indicates success,
+    /// continuing with the current value
+    pub fn send(self, target: &str, op: &str) -> Pipeline<'a, T>
+    where
+        T: Serialize,
+    {
+        let dispatcher = self.dispatcher;
+        self.step(format!("{} on {}", op, target), |value| {
+            let payload = crate::serialize(&value)?;
+            dispatcher.dispatch(target, op, &payload)?;
+            Ok(value)
+        })
+    }
+
+    /// Transforms the current value
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Pipeline<'a, U> {
+        self.step("map".to_string(), |value| Ok(f(value)))
+    }
+
+    /// Transforms the current value with a function that may fail, e.g. to validate it
+    pub fn and_then<U>(
+        self,
+        description: &str,
+        f: impl FnOnce(T) -> Result<U, Box<dyn Error>>,
+    ) -> Pipeline<'a, U> {
+        self.step(description.to_string(), f)
+    }
+
+    /// Replaces the error of the previous step, if that step failed, e.g. to turn a transport error
+    /// into a domain error. Errors of earlier steps are passed through untouched
+    pub fn map_err(self, f: impl FnOnce(Box<dyn Error>) -> Box<dyn Error>) -> Pipeline<'a, T> {
+        let steps = self.steps;
+        let state = match self.state {
+            Err(e) if e.step == steps => Err(PipelineError {
+                source: f(e.source),
+                ..e
+            }),
+            other => other,
+        };
+        Pipeline { state, ..self }
+    }
+
+    /// Ends the pipeline, returning the value produced by the last step
+    pub fn finish(self) -> Result<T, PipelineError> {
+        self.state
+    }
+
+    fn step<U>(
+        self,
+        description: String,
+        f: impl FnOnce(T) -> Result<U, Box<dyn Error>>,
+    ) -> Pipeline<'a, U> {
+        let step = self.steps + 1;
+        let state = match self.state {
+            Ok(value) => f(value).map_err(|source| PipelineError {
+                step,
+                description,
+                source,
+            }),
+            Err(e) => Err(e),
+        };
+        Pipeline {
+            dispatcher: self.dispatcher,
+            steps: step,
+            state,
+        }
+    }
+}
+
+/// The failure of a pipeline step
+#[derive(Debug)]
+pub struct PipelineError {
+    /// The position of the failed step, starting at 1
+    pub step: usize,
+    /// The operation and target invoked by the step, or the description of a transformation
+    pub description: String,
+    pub source: Box<dyn Error>,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Pipeline step {} ({}) failed: {}",
+            self.step, self.description, self.source
+        )
+    }
+}
+
+impl Error for PipelineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Pipeline;
+    use crate::capabilities::Dispatcher;
+    use crate::errors::{as_wascc_error, WasccCodecError};
+    use crate::keyvalue::{AddRequest, AddResponse};
+    use std::error::Error;
+    use std::sync::Mutex;
+
+    struct Counter(Mutex<Vec<String>>);
+
+    impl Dispatcher for Counter {
+        fn dispatch(&self, _actor: &str, op: &str, msg: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+            self.0.lock().unwrap().push(op.to_string());
+            match op {
+                "Add" => {
+                    let req: AddRequest = crate::deserialize(msg)?;
+                    crate::serialize(AddResponse::new(req.value + 1))
+                }
+                _ => Err(Box::new(WasccCodecError::UnknownOperation {
+                    capability: "wascc:keyvalue".to_string(),
+                    operation: op.to_string(),
+                })),
+            }
+        }
+    }
+
+    #[test]
+    fn failing_step_short_circuits_the_chain() {
+        let counter = Counter(Mutex::new(Vec::new()));
+        let result = Pipeline::start(&counter, 1)
+            .map(|n| AddRequest::new("hits", n))
+            .call::<AddResponse>("wascc:keyvalue", "Add")
+            .send("wascc:keyvalue", "Publish")
+            .call::<AddResponse>("wascc:keyvalue", "Add")
+            .finish();
+        let err = result.unwrap_err();
+        assert_eq!(err.step, 3);
+        assert_eq!(err.description, "Publish on wascc:keyvalue");
+        assert!(matches!(
+            as_wascc_error(&err),
+            Some(WasccCodecError::UnknownOperation { .. })
+        ));
+        assert_eq!(*counter.0.lock().unwrap(), vec!["Add", "Publish"]);
+
+        let mapped = Pipeline::start(&counter, AddRequest::new("hits", 1))
+            .send("wascc:keyvalue", "Publish")
+            .map_err(|_| "broker unavailable".into())
+            .map(|_| ())
+            .map_err(|_| "not the failed step".into())
+            .finish()
+            .unwrap_err();
+        assert_eq!(mapped.step, 1);
+        assert_eq!(mapped.source.to_string(), "broker unavailable");
+    }
+}
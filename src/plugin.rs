@@ -0,0 +1,112 @@
+//! # Host Plugins
+//!
+//! This module contains the contract between a waSCC host and host plugins: extensions that are not
+//! capability providers, but observe or intercept what happens inside the host, e.g. for auditing,
+//! rate limiting or custom metrics. A plugin describes itself with a `HostPluginDescriptor` listing
+//! the hook points it wants to be invoked at. The host initializes the plugin with `OP_PLUGIN_INIT`,
+//! invokes it with `OP_INVOKE_HOOK` at each declared hook point, and calls `OP_PLUGIN_SHUTDOWN` before
+//! unloading it
+
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Host sends a PluginInit to a plugin once before any hooks are invoked, lack of error indicates success
+pub const OP_PLUGIN_INIT: &str = "PluginInit";
+/// Host sends a HookInvocation to a plugin at each hook point it declared, receives a HookDecision back
+pub const OP_INVOKE_HOOK: &str = "InvokeHook";
+/// Host sends a PluginShutdown to a plugin before unloading it, lack of error indicates success
+pub const OP_PLUGIN_SHUTDOWN: &str = "PluginShutdown";
+/// All host plugins must respond to this operation, returning a HostPluginDescriptor
+pub const OP_GET_PLUGIN_DESCRIPTOR: &str = "GetPluginDescriptor";
+
+/// The points in the host's processing at which plugins can be invoked
+#[derive(Debug, PartialEq, Eq, Hash, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPoint {
+    /// Before an invocation is dispatched to its target. Plugins may reject the invocation
+    PreInvoke,
+    /// After an invocation has completed, successfully or not
+    PostInvoke,
+    /// After an actor has been started in the host
+    OnActorStart,
+    /// After an actor has been stopped
+    OnActorStop,
+}
+
+/// Describes a host plugin and the hook points at which it should be invoked
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HostPluginDescriptor {
+    /// A unique identifier for the plugin, e.g. `acme:audit`
+    pub id: String,
+    /// The human-friendly name of the plugin, displayed in short messages and log entries
+    pub name: String,
+    /// A semver string representing the version of the plugin
+    pub version: String,
+    #[serde(default)]
+    pub hooks: Vec<HookPoint>,
+}
+
+impl Sample for HostPluginDescriptor {
+    fn sample() -> Self {
+        HostPluginDescriptor {
+            id: "acme:audit".to_string(),
+            name: "Audit Log".to_string(),
+            version: "0.1.0".to_string(),
+            hooks: vec![HookPoint::PreInvoke, HookPoint::PostInvoke],
+        }
+    }
+}
+
+impl HostPluginDescriptor {
+    /// Indicates whether the plugin should be invoked at the given hook point
+    pub fn hooks_into(&self, hook: HookPoint) -> bool {
+        self.hooks.contains(&hook)
+    }
+}
+
+/// Initializes a plugin with its configuration
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInit {
+    /// The ID of the host loading the plugin
+    pub host_id: String,
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
+
+/// Asks a plugin to release its resources before it is unloaded
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginShutdown {
+    pub host_id: String,
+}
+
+/// Invokes a plugin at a hook point
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HookInvocation {
+    pub hook: HookPoint,
+    /// The actor the hook concerns: the target (or origin, for invocations from actors to providers)
+    /// of an invocation, or the actor being started or stopped
+    pub actor: String,
+    /// The operation being invoked. Empty for actor lifecycle hooks
+    #[serde(default)]
+    pub operation: String,
+    /// The origin of the invocation. Empty for actor lifecycle hooks
+    #[serde(default)]
+    pub origin: String,
+    /// For post-invoke hooks, the error with which the invocation failed, if it did
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A plugin's response to a hook invocation. Only pre-invoke hooks may reject; the host ignores
+/// rejections from other hook points
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum HookDecision {
+    Continue,
+    /// The invocation must not be dispatched, for the given reason
+    Reject(String),
+}
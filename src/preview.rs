@@ -0,0 +1,45 @@
+// Concise rendering of byte payloads for `Debug` output. Types carrying message bodies or file
+// chunks implement `Debug` by hand and render those fields through `BytesPreview`, so that tracing
+// a payload logs its length and first few bytes rather than its full contents.
+
+use std::fmt;
+
+/// The number of leading bytes shown in a preview
+const PREVIEW_LEN: usize = 16;
+
+pub(crate) struct BytesPreview<'a>(pub &'a [u8]);
+
+impl fmt::Debug for BytesPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes", self.0.len())?;
+        if !self.0.is_empty() {
+            f.write_str(": ")?;
+            for b in self.0.iter().take(PREVIEW_LEN) {
+                write!(f, "{:02x}", b)?;
+            }
+            if self.0.len() > PREVIEW_LEN {
+                f.write_str("...")?;
+            }
+        }
+        f.write_str(">")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BytesPreview;
+
+    #[test]
+    fn preview_truncates_long_payloads() {
+        assert_eq!(format!("{:?}", BytesPreview(&[])), "<0 bytes>");
+        assert_eq!(
+            format!("{:?}", BytesPreview(&[0x01, 0xab, 0xff])),
+            "<3 bytes: 01abff>"
+        );
+        let chunk =
+            crate::blobstore::FileChunk::new(0, "c", "id", 1 << 20, 1 << 20, vec![7; 1 << 20]);
+        let debug = format!("{:?}", chunk);
+        assert!(debug.contains("chunk_bytes: <1048576 bytes: 07070707070707070707070707070707...>"));
+        assert!(debug.len() < 256);
+    }
+}
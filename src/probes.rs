@@ -0,0 +1,129 @@
+//! # Synthetic Probes
+//!
+//! This module contains data types for the `wascc:healthprobe` capability provider. Monitoring
+//! actors schedule probes that the provider runs at a fixed interval against external targets,
+//! and receive a result after each run. The provider tracks consecutive successes and failures
+//! against the probe's thresholds, so each result also carries the target's overall health
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+
+/// Guest sends a ProbeSpec to the capability provider to schedule a recurring probe, lack of error
+/// indicates success. Scheduling a probe with an existing ID replaces it
+pub const OP_SCHEDULE_PROBE: &str = "ScheduleProbe";
+/// Guest sends a CancelProbe to the capability provider, lack of error indicates success
+pub const OP_CANCEL_PROBE: &str = "CancelProbe";
+/// Guest sends a ProbeSpec to the capability provider to run it once immediately, receives a ProbeResult back
+pub const OP_RUN_PROBE: &str = "RunProbe";
+/// Provider sends a ProbeResult to the guest that scheduled a probe after each run
+pub const OP_DELIVER_PROBE_RESULT: &str = "DeliverProbeResult";
+
+/// The check a probe performs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeKind {
+    /// Sends an HTTP GET to the target URL and checks the response status
+    HttpPing,
+    /// Opens a TCP connection to the target `host:port` and closes it
+    TcpConnect,
+    /// Resolves the target host name
+    DnsResolve,
+}
+
+/// The limits a probe is judged against
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeThresholds {
+    /// A run that takes longer than this fails
+    pub timeout: DurationMs,
+    /// A run that succeeds but takes longer than this marks the target as degraded
+    #[serde(default)]
+    pub max_latency: Option<DurationMs>,
+    /// The number of consecutive failed runs after which the target is unhealthy
+    pub failure_threshold: u32,
+    /// The number of consecutive successful runs after which an unhealthy target is healthy again
+    pub success_threshold: u32,
+}
+
+impl Default for ProbeThresholds {
+    fn default() -> Self {
+        ProbeThresholds {
+            timeout: DurationMs(5_000),
+            max_latency: None,
+            failure_threshold: 3,
+            success_threshold: 1,
+        }
+    }
+}
+
+/// Describes a probe
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeSpec {
+    /// An actor-chosen ID that identifies the probe in results
+    pub probe_id: String,
+    pub kind: ProbeKind,
+    /// A URL for HTTP probes, `host:port` for TCP probes, or a host name for DNS probes
+    pub target: String,
+    /// How often the probe runs. Ignored by `OP_RUN_PROBE`
+    pub interval: DurationMs,
+    /// For HTTP probes, the status codes that count as success. If empty, any 2xx status succeeds
+    #[serde(default)]
+    pub expected_status: Vec<u32>,
+    #[serde(default)]
+    pub thresholds: ProbeThresholds,
+}
+
+impl Sample for ProbeSpec {
+    fn sample() -> Self {
+        ProbeSpec {
+            probe_id: "api-health".to_string(),
+            kind: ProbeKind::HttpPing,
+            target: "https://api.example.com/health".to_string(),
+            interval: DurationMs(30_000),
+            expected_status: vec![200],
+            thresholds: ProbeThresholds {
+                max_latency: Some(DurationMs(500)),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Cancels a scheduled probe
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelProbe {
+    pub probe_id: String,
+}
+
+/// The health of a probe's target, taking the probe's thresholds into account
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetHealth {
+    Healthy,
+    /// The target is responding, but more slowly than the probe's maximum latency
+    Degraded,
+    Unhealthy,
+}
+
+/// The outcome of a single probe run
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeResult {
+    pub probe_id: String,
+    /// Indicates whether this run succeeded
+    pub success: bool,
+    /// How long the run took
+    pub latency: DurationMs,
+    pub checked_at: Timestamp,
+    pub health: TargetHealth,
+    /// The number of consecutive failed runs, including this one. 0 if this run succeeded
+    pub consecutive_failures: u32,
+    /// Additional information about the run, e.g. the HTTP status or resolved addresses
+    #[serde(default)]
+    pub detail: Option<String>,
+    /// The reason the run failed, if it did
+    #[serde(default)]
+    pub error: Option<String>,
+}
@@ -0,0 +1,242 @@
+//! # Out-of-Process Providers
+//!
+//! This module defines the wire protocol between a host and a native capability provider running in a
+//! separate process, connected by a pipe or socket. Every message is a `Frame`, encoded with the
+//! codec's standard message pack serialization and preceded by its length as a 4-byte big-endian
+//! integer.
+//!
+//! A session begins with the host sending `Frame::Handshake`. The provider replies with
+//! `Frame::HandshakeAck` carrying its capability descriptor, after which either side may send
+//! `Frame::Invoke` and must answer each with a `Frame::Reply` bearing the same ID. Hosts send
+//! `Frame::Keepalive` periodically and providers echo it, so either side can detect a hung peer. A
+//! session ends with `Frame::Shutdown`, after which the sender closes the connection
+
+use crate::capabilities::CapabilityDescriptor;
+use crate::errors::WasccCodecError;
+use std::io::{self, Read, Write};
+
+/// The version of the frame protocol. Peers must refuse a handshake with a different version
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The largest frame body, in bytes, that peers are required to accept
+pub const MAX_FRAME_LENGTH: usize = 16 * 1024 * 1024;
+
+const LENGTH_PREFIX: usize = 4;
+
+/// A single message exchanged between a host and an out-of-process provider
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Frame {
+    /// Sent by the host to open a session
+    #[serde(rename_all = "camelCase")]
+    Handshake {
+        protocol_version: u32,
+        /// The codec version the host was built with
+        codec_version: String,
+        host_id: String,
+    },
+    /// Sent by the provider to accept a handshake
+    #[serde(rename_all = "camelCase")]
+    HandshakeAck {
+        protocol_version: u32,
+        descriptor: CapabilityDescriptor,
+    },
+    /// An invocation, from the host on behalf of an actor or from the provider to an actor
+    Invoke {
+        /// Chosen by the sender, unique among its unanswered invocations
+        id: u64,
+        /// The actor making the invocation (for host-sent frames) or being invoked (for provider-sent frames)
+        origin: String,
+        op: String,
+        #[serde(with = "serde_bytes")]
+        #[serde(default)]
+        payload: Vec<u8>,
+    },
+    /// The result of an invocation
+    Reply {
+        /// The ID of the invocation being answered
+        id: u64,
+        #[serde(with = "serde_bytes")]
+        #[serde(default)]
+        payload: Vec<u8>,
+        /// The error with which the invocation failed, in which case the payload is empty
+        #[serde(default)]
+        error: Option<String>,
+    },
+    /// Sent by the host to check the provider is responsive, and echoed back by the provider
+    Keepalive { seq: u64 },
+    /// Ends the session
+    Shutdown { reason: String },
+}
+
+impl Frame {
+    /// Encodes the frame, including its length prefix
+    pub fn encode(&self) -> Result<Vec<u8>, WasccCodecError> {
+        let body = crate::serialize(self).map_err(|e| WasccCodecError::MalformedPayload {
+            reason: e.to_string(),
+        })?;
+        if body.len() > MAX_FRAME_LENGTH {
+            return Err(WasccCodecError::PayloadTooLarge {
+                actual: body.len() as u64,
+                allowed: MAX_FRAME_LENGTH as u64,
+            });
+        }
+        let mut buf = Vec::with_capacity(LENGTH_PREFIX + body.len());
+        buf.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&body);
+        Ok(buf)
+    }
+
+    /// Decodes a frame from the front of a buffer, for transports that receive data in arbitrary
+    /// pieces. Returns the frame and the number of bytes it occupied, or `None` if the buffer does not
+    /// yet hold a complete frame
+    pub fn decode(buf: &[u8]) -> Result<Option<(Frame, usize)>, WasccCodecError> {
+        if buf.len() < LENGTH_PREFIX {
+            return Ok(None);
+        }
+        let mut prefix = [0u8; LENGTH_PREFIX];
+        prefix.copy_from_slice(&buf[..LENGTH_PREFIX]);
+        let length = u32::from_be_bytes(prefix) as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(WasccCodecError::PayloadTooLarge {
+                actual: length as u64,
+                allowed: MAX_FRAME_LENGTH as u64,
+            });
+        }
+        if buf.len() < LENGTH_PREFIX + length {
+            return Ok(None);
+        }
+        let frame =
+            crate::deserialize(&buf[LENGTH_PREFIX..LENGTH_PREFIX + length]).map_err(|e| {
+                WasccCodecError::MalformedPayload {
+                    reason: e.to_string(),
+                }
+            })?;
+        Ok(Some((frame, LENGTH_PREFIX + length)))
+    }
+
+    /// Writes the frame to a blocking stream
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let buf = self.encode().map_err(invalid_data)?;
+        writer.write_all(&buf)?;
+        writer.flush()
+    }
+
+    /// Reads the next frame from a blocking stream. Returns `None` if the stream ended cleanly before
+    /// the start of a frame, and fails with `UnexpectedEof` if it ended partway through one
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Frame>> {
+        let mut prefix = [0u8; LENGTH_PREFIX];
+        // Only the first byte may be missing at a clean end of stream
+        loop {
+            match reader.read(&mut prefix[..1]) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        reader.read_exact(&mut prefix[1..])?;
+        let length = u32::from_be_bytes(prefix) as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(invalid_data(WasccCodecError::PayloadTooLarge {
+                actual: length as u64,
+                allowed: MAX_FRAME_LENGTH as u64,
+            }));
+        }
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body)?;
+        crate::deserialize(&body)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Creates the handshake a host sends to open a session
+    pub fn handshake(host_id: &str) -> Frame {
+        Frame::Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            codec_version: crate::VERSION.to_string(),
+            host_id: host_id.to_string(),
+        }
+    }
+
+    /// Creates the reply to an invocation from the outcome of handling it
+    pub fn reply(id: u64, result: Result<Vec<u8>, String>) -> Frame {
+        match result {
+            Ok(payload) => Frame::Reply {
+                id,
+                payload,
+                error: None,
+            },
+            Err(error) => Frame::Reply {
+                id,
+                payload: Vec::new(),
+                error: Some(error),
+            },
+        }
+    }
+}
+
+fn invalid_data(e: WasccCodecError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Frame, PROTOCOL_VERSION};
+    use crate::capabilities::CapabilityDescriptor;
+    use std::io::Cursor;
+
+    #[test]
+    fn frames_round_trip_over_streams_and_buffers() {
+        let frames = vec![
+            Frame::handshake("host-1"),
+            Frame::HandshakeAck {
+                protocol_version: PROTOCOL_VERSION,
+                descriptor: CapabilityDescriptor::builder()
+                    .id("wascc:keyvalue")
+                    .name("kv")
+                    .build(),
+            },
+            Frame::Invoke {
+                id: 7,
+                origin: "Mxxx".to_string(),
+                op: "Get".to_string(),
+                payload: b"payload".to_vec(),
+            },
+            Frame::reply(7, Err("no such key".to_string())),
+            Frame::Keepalive { seq: 3 },
+            Frame::Shutdown {
+                reason: "host stopping".to_string(),
+            },
+        ];
+        let mut stream = Vec::new();
+        for f in &frames {
+            f.write_to(&mut stream).unwrap();
+        }
+
+        let mut reader = Cursor::new(stream.clone());
+        for f in &frames {
+            assert_eq!(Frame::read_from(&mut reader).unwrap().as_ref(), Some(f));
+        }
+        assert_eq!(Frame::read_from(&mut reader).unwrap(), None);
+
+        for len in 1..super::LENGTH_PREFIX {
+            let mut truncated = Cursor::new(stream[..len].to_vec());
+            assert_eq!(
+                Frame::read_from(&mut truncated).unwrap_err().kind(),
+                std::io::ErrorKind::UnexpectedEof
+            );
+        }
+
+        assert_eq!(Frame::decode(&stream[..3]).unwrap(), None);
+        let (first, used) = Frame::decode(&stream).unwrap().unwrap();
+        assert_eq!(first, frames[0]);
+        let contains = |name: &[u8]| stream[..used].windows(name.len()).any(|w| w == name);
+        assert!(contains(b"protocolVersion") && contains(b"hostId"));
+        assert!(!contains(b"protocol_version"));
+        assert_eq!(
+            Frame::decode(&stream[used..]).unwrap().unwrap().0,
+            frames[1]
+        );
+    }
+}
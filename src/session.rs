@@ -0,0 +1,109 @@
+//! # User Sessions
+//!
+//! This module contains data types for the `wascc:session` capability provider. Session store
+//! providers create sessions with provider-generated IDs, suitable for use in cookies, and expire
+//! them after a period of inactivity or at a fixed time, depending on the session's expiry mode
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a CreateSession to the capability provider, receives SessionData back
+pub const OP_CREATE_SESSION: &str = "CreateSession";
+/// Guest sends a GetSession to the capability provider, receives SessionData back
+pub const OP_GET_SESSION: &str = "GetSession";
+/// Guest sends a TouchSession to the capability provider, receives SessionData back
+pub const OP_TOUCH_SESSION: &str = "TouchSession";
+/// Guest sends a DestroySession to the capability provider, lack of error indicates success
+pub const OP_DESTROY_SESSION: &str = "DestroySession";
+
+/// Determines when a session expires
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryMode {
+    /// The session expires once it has gone unused for the TTL. Reading or touching the session
+    /// extends its expiry
+    #[default]
+    Sliding,
+    /// The session expires when the TTL has elapsed since it was created, regardless of use
+    Absolute,
+}
+
+/// Creates a new session
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSession {
+    pub ttl: DurationMs,
+    #[serde(default)]
+    pub expiry: ExpiryMode,
+    /// The initial attributes of the session, e.g. the signed-in user's ID
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+impl Sample for CreateSession {
+    fn sample() -> Self {
+        let mut attributes = HashMap::new();
+        attributes.insert("userId".to_string(), "alice".to_string());
+        CreateSession {
+            ttl: DurationMs(30 * 60 * 1000),
+            expiry: ExpiryMode::Sliding,
+            attributes,
+        }
+    }
+}
+
+/// Retrieves a session. For sliding sessions, this extends the session's expiry
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSession {
+    pub session_id: String,
+}
+
+/// Extends the expiry of a sliding session without retrieving it, optionally replacing attributes.
+/// Touching an absolute session only updates its attributes
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TouchSession {
+    pub session_id: String,
+    /// Attributes to add to the session or replace
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+/// Ends a session
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DestroySession {
+    pub session_id: String,
+}
+
+/// The state of a session. If the session does not exist or has expired, `exists` will be false and
+/// the remaining fields will hold default values
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionData {
+    pub session_id: String,
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    pub created_at: Timestamp,
+    /// The time at which the session will expire unless it is extended
+    pub expires_at: Timestamp,
+    pub expiry: ExpiryMode,
+    pub exists: bool,
+}
+
+impl SessionData {
+    /// The response a provider returns for a session that does not exist
+    pub fn not_found(session_id: &str) -> SessionData {
+        SessionData {
+            session_id: session_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Indicates whether the session exists and has not expired as of the given time
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.exists && now < self.expires_at
+    }
+}
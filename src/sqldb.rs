@@ -0,0 +1,243 @@
+//! # SQL Databases
+//!
+//! This module contains data types for the `wascc:sqldb` capability provider, which gives actors access
+//! to a relational database. Statements are parameterized, with parameters and result values carried
+//! as typed `SqlValue`s, so actors never build SQL text from untrusted input
+
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+
+/// Guest sends a QueryRequest to the capability provider, receives a ResultSet back
+pub const OP_EXECUTE_QUERY: &str = "ExecuteQuery";
+/// Guest sends a QueryRequest for a statement that returns no rows (e.g. INSERT or UPDATE) to the
+/// capability provider, receives an ExecuteResult back
+pub const OP_EXECUTE_STATEMENT: &str = "ExecuteStatement";
+/// Guest sends a BeginTransaction to the capability provider, receives a TransactionHandle back
+pub const OP_BEGIN_TRANSACTION: &str = "BeginTransaction";
+/// Guest sends an EndTransaction to the capability provider, lack of error indicates success
+pub const OP_COMMIT_TRANSACTION: &str = "CommitTransaction";
+/// Guest sends an EndTransaction to the capability provider, lack of error indicates success
+pub const OP_ROLLBACK_TRANSACTION: &str = "RollbackTransaction";
+
+/// A statement parameter or a value in a result row
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+    Timestamp(Timestamp),
+}
+
+impl SqlValue {
+    pub fn is_null(&self) -> bool {
+        *self == SqlValue::Null
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            SqlValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            SqlValue::Float(f) => Some(*f),
+            SqlValue::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SqlValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(s: &str) -> SqlValue {
+        SqlValue::Text(s.to_string())
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(i: i64) -> SqlValue {
+        SqlValue::Int(i)
+    }
+}
+
+impl From<f64> for SqlValue {
+    fn from(f: f64) -> SqlValue {
+        SqlValue::Float(f)
+    }
+}
+
+impl From<bool> for SqlValue {
+    fn from(b: bool) -> SqlValue {
+        SqlValue::Bool(b)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(v: Option<T>) -> SqlValue {
+        v.map_or(SqlValue::Null, Into::into)
+    }
+}
+
+/// A request to run a statement
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    /// The SQL text, with positional placeholders in the database's syntax (e.g. `$1` or `?`)
+    pub sql: String,
+    #[serde(default)]
+    pub params: Vec<SqlValue>,
+    /// Runs the statement within a transaction started with `OP_BEGIN_TRANSACTION`. None - the
+    /// statement runs in its own transaction
+    #[serde(default)]
+    pub transaction_id: Option<String>,
+    /// The most rows to return, further rows are dropped and the result set is flagged as truncated.
+    /// 0 - the provider's limit
+    #[serde(default)]
+    pub max_rows: u32,
+}
+
+impl Sample for QueryRequest {
+    fn sample() -> Self {
+        QueryRequest::new("SELECT id, name, balance FROM accounts WHERE region = $1")
+            .with_param("eu-west")
+            .with_max_rows(100)
+    }
+}
+
+impl QueryRequest {
+    pub fn new(sql: &str) -> QueryRequest {
+        QueryRequest {
+            sql: sql.to_string(),
+            params: Vec::new(),
+            transaction_id: None,
+            max_rows: 0,
+        }
+    }
+
+    pub fn with_param(mut self, param: impl Into<SqlValue>) -> QueryRequest {
+        self.params.push(param.into());
+        self
+    }
+
+    pub fn in_transaction(mut self, transaction_id: &str) -> QueryRequest {
+        self.transaction_id = Some(transaction_id.to_string());
+        self
+    }
+
+    pub fn with_max_rows(mut self, max_rows: u32) -> QueryRequest {
+        self.max_rows = max_rows;
+        self
+    }
+}
+
+/// Describes a column of a result set
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Column {
+    pub name: String,
+    /// The column's type as the database names it, e.g. `VARCHAR(64)` or `int8`
+    pub data_type: String,
+    #[serde(default)]
+    pub nullable: bool,
+}
+
+/// A row of a result set, with one value per column
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Row {
+    pub values: Vec<SqlValue>,
+}
+
+impl Row {
+    pub fn get(&self, index: usize) -> Option<&SqlValue> {
+        self.values.get(index)
+    }
+}
+
+/// The rows returned by a query
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultSet {
+    pub columns: Vec<Column>,
+    #[serde(default)]
+    pub rows: Vec<Row>,
+    /// Indicates that the query matched more rows than were returned
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl ResultSet {
+    /// The position of the column with the given name, matched case-insensitively
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The value of the named column in the given row
+    pub fn value(&self, row: usize, column: &str) -> Option<&SqlValue> {
+        let index = self.column_index(column)?;
+        self.rows.get(row)?.get(index)
+    }
+}
+
+/// The outcome of a statement that returns no rows
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteResult {
+    pub rows_affected: u64,
+    /// The key generated for an inserted row, for databases that report one
+    #[serde(default)]
+    pub last_insert_id: Option<i64>,
+}
+
+/// The isolation level of a transaction
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    #[default]
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+/// A request to start a transaction
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BeginTransaction {
+    #[serde(default)]
+    pub isolation: IsolationLevel,
+    #[serde(default)]
+    pub read_only: bool,
+    /// How long the transaction may stay open before the provider rolls it back. None - the provider's
+    /// default
+    #[serde(default)]
+    pub timeout: Option<DurationMs>,
+}
+
+/// Identifies a transaction started with `OP_BEGIN_TRANSACTION`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionHandle {
+    pub transaction_id: String,
+}
+
+/// A request to commit or roll back a transaction
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EndTransaction {
+    pub transaction_id: String,
+}
@@ -0,0 +1,150 @@
+//! # Actor State
+//!
+//! This module contains data types for the `wascc:statemachine` capability provider. Event-sourced
+//! actors rebuild their state by replaying events, and periodically persist a snapshot of that state
+//! so replay can begin from the snapshot rather than from the start of the stream. Snapshots are
+//! versioned per actor key, and a provider rejects any snapshot that does not advance the stored
+//! version so that concurrent writers cannot silently overwrite each other
+
+use crate::core::Timestamp;
+use crate::Sample;
+
+/// Guest sends a SaveSnapshot to the capability provider, receives a SaveSnapshotResponse back
+pub const OP_SAVE_SNAPSHOT: &str = "SaveSnapshot";
+/// Guest sends a LoadSnapshotRequest to the capability provider, receives a Snapshot back
+pub const OP_LOAD_SNAPSHOT: &str = "LoadSnapshot";
+/// Guest sends a PurgeSnapshots request to the capability provider, receives a PurgeSnapshotsResponse back
+pub const OP_PURGE_SNAPSHOTS: &str = "PurgeSnapshots";
+
+/// Persists a snapshot of an actor's state
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSnapshot {
+    /// The key identifying the state, typically the actor's public key or an aggregate ID
+    pub actor_key: String,
+    /// The version of the state, usually the sequence number of the last event applied to it. Must be
+    /// greater than the version of the latest stored snapshot
+    pub version: u64,
+    /// The stored version the actor based this snapshot on. When supplied, the save is rejected unless
+    /// it equals the version of the latest stored snapshot (0 if there is none)
+    #[serde(default)]
+    pub expected_version: Option<u64>,
+    /// Identifies the format of `state` so that readers can detect snapshots they need to migrate
+    pub schema_id: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub state: Vec<u8>,
+}
+
+impl Sample for SaveSnapshot {
+    fn sample() -> Self {
+        SaveSnapshot {
+            actor_key: "order-1029".to_string(),
+            version: 42,
+            expected_version: Some(17),
+            schema_id: "order-state.v2".to_string(),
+            state: b"{\"status\":\"shipped\"}".to_vec(),
+        }
+    }
+}
+
+impl SaveSnapshot {
+    /// Indicates whether this snapshot conflicts with the latest stored version (0 if there is no
+    /// snapshot). Providers must reject conflicting snapshots
+    pub fn conflicts_with(&self, stored_version: u64) -> bool {
+        self.version <= stored_version
+            || self
+                .expected_version
+                .is_some_and(|expected| expected != stored_version)
+    }
+}
+
+/// The provider's response to a SaveSnapshot request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSnapshotResponse {
+    /// Indicates whether the snapshot was stored. A snapshot that conflicts with the stored version is
+    /// not stored
+    pub saved: bool,
+    /// The version of the latest stored snapshot after the request was processed
+    pub current_version: u64,
+}
+
+/// Loads a snapshot of an actor's state
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSnapshotRequest {
+    pub actor_key: String,
+    /// A specific version to load. If not supplied, the latest snapshot is returned
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+/// A stored snapshot of an actor's state. If no matching snapshot exists, `exists` will be false and
+/// the remaining fields will hold default values
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub actor_key: String,
+    pub version: u64,
+    pub schema_id: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub state: Vec<u8>,
+    /// The time at which the provider stored the snapshot
+    pub saved_at: Timestamp,
+    pub exists: bool,
+}
+
+impl Snapshot {
+    /// The response a provider returns when no matching snapshot exists
+    pub fn not_found(actor_key: &str) -> Snapshot {
+        Snapshot {
+            actor_key: actor_key.to_string(),
+            version: 0,
+            schema_id: String::new(),
+            state: Vec::new(),
+            saved_at: Timestamp(0),
+            exists: false,
+        }
+    }
+}
+
+/// Removes old snapshots of an actor's state
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeSnapshots {
+    pub actor_key: String,
+    /// The number of most recent snapshots to keep. 0 removes every snapshot for the key
+    pub keep_latest: u32,
+}
+
+/// The provider's response to a PurgeSnapshots request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeSnapshotsResponse {
+    /// The number of snapshots removed
+    pub purged: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::SaveSnapshot;
+    use crate::Sample;
+
+    #[test]
+    fn snapshot_conflict_detection() {
+        let snapshot = SaveSnapshot::sample();
+        assert!(!snapshot.conflicts_with(17));
+        assert!(snapshot.conflicts_with(20));
+        assert!(snapshot.conflicts_with(42));
+
+        let unconditional = SaveSnapshot {
+            expected_version: None,
+            ..SaveSnapshot::sample()
+        };
+        assert!(!unconditional.conflicts_with(0));
+        assert!(!unconditional.conflicts_with(41));
+        assert!(unconditional.conflicts_with(43));
+    }
+}
@@ -0,0 +1,552 @@
+//! Strict decoding used by `deserialize_strict`. The payload is decoded through a proxy deserializer
+//! that sits between the target type and the message pack deserializer. Every struct the target
+//! visits passes its declared field names through the proxy, so a key outside that set is reported
+//! as an unknown field, and the `missing_field` errors raised for required fields are intercepted and
+//! anchored at their path. Optional fields and fields that declare a default may be omitted, exactly
+//! as they may be for `deserialize`
+//!
+//! Serde buffers the contents of flattened structs and of internally tagged enums before decoding
+//! them, so those contents bypass the proxy and are only checked for missing fields
+
+use crate::errors::WasccCodecError;
+use rmps::Deserializer as MsgpackDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::cell::RefCell;
+use std::fmt;
+use std::io::Cursor;
+
+/// Decodes `buf` as `T`, rejecting fields `T` does not declare and reporting omitted required
+/// fields by their path
+pub(crate) fn from_slice<T: DeserializeOwned>(buf: &[u8]) -> Result<T, WasccCodecError> {
+    let state = State::default();
+    let mut de = MsgpackDeserializer::new(Cursor::new(buf));
+    T::deserialize(Wrap::new(&mut de, &state, String::new(), false)).map_err(|e| match e {
+        StrictError::MissingField(field) => WasccCodecError::MissingField {
+            path: field.to_string(),
+        },
+        StrictError::Codec(e) => e,
+    })
+}
+
+/// Error type of the proxy. A missing field is named relative to the struct that reported it until
+/// the map holding that struct anchors it at a full path
+#[derive(Debug)]
+enum StrictError {
+    MissingField(&'static str),
+    Codec(WasccCodecError),
+}
+
+impl StrictError {
+    fn at(self, path: &str) -> StrictError {
+        match self {
+            StrictError::MissingField(field) => StrictError::Codec(WasccCodecError::MissingField {
+                path: join(path, field),
+            }),
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for StrictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictError::MissingField(field) => write!(f, "missing field `{}`", field),
+            StrictError::Codec(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StrictError {}
+
+impl de::Error for StrictError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        StrictError::Codec(WasccCodecError::MalformedPayload {
+            reason: msg.to_string(),
+        })
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        StrictError::MissingField(field)
+    }
+}
+
+/// Shared by every layer of the proxy. Errors raised inside the proxy travel back out through the
+/// message pack deserializer, which can only carry a message, so the typed error is kept here and
+/// recovered on the way out. `key` receives the text of the map key being decoded
+#[derive(Default)]
+struct State {
+    error: RefCell<Option<StrictError>>,
+    key: RefCell<Option<String>>,
+}
+
+impl State {
+    fn stash<E: de::Error>(&self, e: StrictError) -> E {
+        let err = E::custom(&e);
+        *self.error.borrow_mut() = Some(e);
+        err
+    }
+
+    fn recover<E: fmt::Display>(&self, e: E) -> StrictError {
+        self.error.borrow_mut().take().unwrap_or_else(|| {
+            StrictError::Codec(WasccCodecError::MalformedPayload {
+                reason: e.to_string(),
+            })
+        })
+    }
+}
+
+struct Wrap<'a, D> {
+    de: D,
+    state: &'a State,
+    path: String,
+    capture_key: bool,
+}
+
+impl<'a, D> Wrap<'a, D> {
+    fn new(de: D, state: &'a State, path: String, capture_key: bool) -> Self {
+        Wrap {
+            de,
+            state,
+            path,
+            capture_key,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, StrictError> {
+                let state = self.state;
+                let visitor = WrapVisitor::new(visitor, state, self.path, None, self.capture_key);
+                self.de.$method($($arg,)* visitor).map_err(|e| state.recover(e))
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, D: Deserializer<'de>> Deserializer<'de> for Wrap<'a, D> {
+    type Error = StrictError;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, StrictError> {
+        let state = self.state;
+        let visitor = WrapVisitor::new(visitor, state, self.path, Some(fields), self.capture_key);
+        self.de
+            .deserialize_struct(name, fields, visitor)
+            .map_err(|e| state.recover(e))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+struct WrapVisitor<'a, V> {
+    visitor: V,
+    state: &'a State,
+    path: String,
+    fields: Option<&'static [&'static str]>,
+    capture_key: bool,
+}
+
+impl<'a, V> WrapVisitor<'a, V> {
+    fn new(
+        visitor: V,
+        state: &'a State,
+        path: String,
+        fields: Option<&'static [&'static str]>,
+        capture_key: bool,
+    ) -> Self {
+        WrapVisitor {
+            visitor,
+            state,
+            path,
+            fields,
+            capture_key,
+        }
+    }
+
+    fn capture(&self, key: &dyn fmt::Display) {
+        if self.capture_key {
+            *self.state.key.borrow_mut() = Some(key.to_string());
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.capture(&v);
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, V: Visitor<'de>> Visitor<'de> for WrapVisitor<'a, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<V::Value, E> {
+        self.visitor.visit_bytes(v)
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<V::Value, E> {
+        self.visitor.visit_borrowed_bytes(v)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<V::Value, E> {
+        self.visitor.visit_byte_buf(v)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        let state = self.state;
+        self.visitor
+            .visit_some(Wrap::new(deserializer, state, self.path, false))
+            .map_err(|e| state.stash(e))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        let state = self.state;
+        self.visitor
+            .visit_newtype_struct(Wrap::new(deserializer, state, self.path, false))
+            .map_err(|e| state.stash(e))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        let WrapVisitor {
+            visitor,
+            state,
+            path,
+            ..
+        } = self;
+        let access = WrapSeq {
+            seq,
+            state,
+            path: path.clone(),
+            index: 0,
+        };
+        visitor
+            .visit_seq(access)
+            .map_err(|e| state.stash(e.at(&path)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        let WrapVisitor {
+            visitor,
+            state,
+            path,
+            fields,
+            ..
+        } = self;
+        let access = WrapMap {
+            map,
+            state,
+            path: path.clone(),
+            fields,
+            key: String::new(),
+        };
+        visitor
+            .visit_map(access)
+            .map_err(|e| state.stash(e.at(&path)))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        let WrapVisitor {
+            visitor,
+            state,
+            path,
+            ..
+        } = self;
+        let access = WrapEnum {
+            access: data,
+            state,
+            path: path.clone(),
+        };
+        visitor
+            .visit_enum(access)
+            .map_err(|e| state.stash(e.at(&path)))
+    }
+}
+
+struct WrapSeed<'a, S> {
+    seed: S,
+    state: &'a State,
+    path: String,
+    capture_key: bool,
+}
+
+impl<'de, 'a, S: DeserializeSeed<'de>> DeserializeSeed<'de> for WrapSeed<'a, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        let state = self.state;
+        self.seed
+            .deserialize(Wrap::new(deserializer, state, self.path, self.capture_key))
+            .map_err(|e| state.stash(e))
+    }
+}
+
+struct WrapSeq<'a, A> {
+    seq: A,
+    state: &'a State,
+    path: String,
+    index: usize,
+}
+
+impl<'de, 'a, A: SeqAccess<'de>> SeqAccess<'de> for WrapSeq<'a, A> {
+    type Error = StrictError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, StrictError> {
+        let state = self.state;
+        let path = format!("{}[{}]", self.path, self.index);
+        self.index += 1;
+        self.seq
+            .next_element_seed(WrapSeed {
+                seed,
+                state,
+                path,
+                capture_key: false,
+            })
+            .map_err(|e| state.recover(e))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.seq.size_hint()
+    }
+}
+
+struct WrapMap<'a, A> {
+    map: A,
+    state: &'a State,
+    path: String,
+    fields: Option<&'static [&'static str]>,
+    key: String,
+}
+
+impl<'de, 'a, A: MapAccess<'de>> MapAccess<'de> for WrapMap<'a, A> {
+    type Error = StrictError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, StrictError> {
+        let state = self.state;
+        *state.key.borrow_mut() = None;
+        let key = self
+            .map
+            .next_key_seed(WrapSeed {
+                seed,
+                state,
+                path: self.path.clone(),
+                capture_key: true,
+            })
+            .map_err(|e| state.recover(e))?;
+        self.key = state
+            .key
+            .borrow_mut()
+            .take()
+            .unwrap_or_else(|| "?".to_string());
+        match self.fields {
+            Some(fields) if key.is_some() && !fields.contains(&self.key.as_str()) => {
+                Err(StrictError::Codec(WasccCodecError::UnknownField {
+                    path: join(&self.path, &self.key),
+                }))
+            }
+            _ => Ok(key),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, StrictError> {
+        let state = self.state;
+        self.map
+            .next_value_seed(WrapSeed {
+                seed,
+                state,
+                path: join(&self.path, &self.key),
+                capture_key: false,
+            })
+            .map_err(|e| state.recover(e))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+struct WrapEnum<'a, A> {
+    access: A,
+    state: &'a State,
+    path: String,
+}
+
+impl<'de, 'a, A: EnumAccess<'de>> EnumAccess<'de> for WrapEnum<'a, A> {
+    type Error = StrictError;
+    type Variant = WrapVariant<'a, A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), StrictError> {
+        let state = self.state;
+        let (value, variant) = self
+            .access
+            .variant_seed(WrapSeed {
+                seed,
+                state,
+                path: self.path.clone(),
+                capture_key: false,
+            })
+            .map_err(|e| state.recover(e))?;
+        let variant = WrapVariant {
+            variant,
+            state,
+            path: self.path,
+        };
+        Ok((value, variant))
+    }
+}
+
+struct WrapVariant<'a, A> {
+    variant: A,
+    state: &'a State,
+    path: String,
+}
+
+impl<'de, 'a, A: VariantAccess<'de>> VariantAccess<'de> for WrapVariant<'a, A> {
+    type Error = StrictError;
+
+    fn unit_variant(self) -> Result<(), StrictError> {
+        let state = self.state;
+        self.variant.unit_variant().map_err(|e| state.recover(e))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, StrictError> {
+        let state = self.state;
+        self.variant
+            .newtype_variant_seed(WrapSeed {
+                seed,
+                state,
+                path: self.path,
+                capture_key: false,
+            })
+            .map_err(|e| state.recover(e))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, StrictError> {
+        let state = self.state;
+        let visitor = WrapVisitor::new(visitor, state, self.path, None, false);
+        self.variant
+            .tuple_variant(len, visitor)
+            .map_err(|e| state.recover(e))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, StrictError> {
+        let state = self.state;
+        let visitor = WrapVisitor::new(visitor, state, self.path, Some(fields), false);
+        self.variant
+            .struct_variant(fields, visitor)
+            .map_err(|e| state.recover(e))
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
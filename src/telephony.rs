@@ -0,0 +1,136 @@
+//! # Telephony
+//!
+//! This module contains data types for the `wascc:telephony` capability provider, which places and
+//! controls voice calls through a carrier or a Twilio-style API. Actors, such as IVR flows, start calls,
+//! play audio stored in a blob store, and receive call progress and keypad (DTMF) events
+
+use crate::blobstore::BlobReference;
+use crate::core::{DurationMs, Timestamp};
+use crate::Sample;
+
+/// Guest sends an InitiateCall to the capability provider, receives a CallHandle back
+pub const OP_INITIATE_CALL: &str = "InitiateCall";
+/// Provider sends a CallEvent to the guest as a call progresses, including for inbound calls
+pub const OP_DELIVER_CALL_EVENT: &str = "DeliverCallEvent";
+/// Guest sends a PlayAudio to the capability provider, lack of error indicates success
+pub const OP_PLAY_AUDIO: &str = "PlayAudio";
+/// Guest sends a HangupCall to the capability provider, lack of error indicates success
+pub const OP_HANGUP_CALL: &str = "HangupCall";
+
+/// A request to place an outbound call
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InitiateCall {
+    /// The number to call, in E.164 format (e.g. `+14155550100`)
+    pub to: String,
+    /// The caller ID, which must be a number the provider's account owns. Empty - the provider's default
+    #[serde(default)]
+    pub from: String,
+    /// How long to let the call ring before giving up. If not supplied, the carrier's default applies
+    #[serde(default)]
+    pub ring_timeout: Option<DurationMs>,
+    /// Audio played as soon as the call is answered
+    #[serde(default)]
+    pub greeting: Option<BlobReference>,
+}
+
+impl Sample for InitiateCall {
+    fn sample() -> Self {
+        InitiateCall {
+            to: "+14155550100".to_string(),
+            from: "+14155550199".to_string(),
+            ring_timeout: Some(DurationMs::from_secs(30)),
+            greeting: Some(BlobReference::new("prompts", "welcome.wav")),
+        }
+    }
+}
+
+/// Identifies a call placed with `OP_INITIATE_CALL`
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CallHandle {
+    /// The provider's ID for the call, carried by every event of the call
+    pub call_id: String,
+}
+
+/// A change in the state of a call
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CallEvent {
+    pub call_id: String,
+    /// A sequence number that can be used for ordering logic, starting at 0 for each call
+    pub sequence_no: u64,
+    pub occurred_at: Timestamp,
+    pub kind: CallEventKind,
+}
+
+/// What happened on a call
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum CallEventKind {
+    /// An inbound call has arrived at one of the provider's numbers. The guest answers it by playing audio
+    /// or rejects it by hanging up
+    Incoming { from: String, to: String },
+    /// The called party's phone is ringing
+    Ringing,
+    /// The call was answered
+    Answered,
+    /// The caller pressed keys on their keypad
+    Dtmf { digits: String },
+    /// Playback started with `OP_PLAY_AUDIO` finished or was interrupted
+    PlaybackFinished { interrupted: bool },
+    /// The call ended. This is always the final event of a call
+    Hangup { reason: HangupReason },
+}
+
+/// Why a call ended
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HangupReason {
+    /// The call was answered and one of the parties hung up
+    Completed,
+    Busy,
+    NoAnswer,
+    /// The guest hung up before the call was answered
+    Canceled,
+    /// The call could not be connected, e.g. because the number is invalid
+    Failed,
+}
+
+/// A request to play audio into a call
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayAudio {
+    pub call_id: String,
+    /// The audio to play, e.g. a WAV or MP3 file
+    pub audio: BlobReference,
+    /// How often to play the audio. Defaults to once
+    #[serde(default)]
+    pub repeat: Repeat,
+    /// Stop playback as soon as the caller presses a key, e.g. for IVR menus
+    #[serde(default)]
+    pub interrupt_on_dtmf: bool,
+}
+
+/// How often `PlayAudio` plays its audio
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Repeat {
+    /// Play the audio the given number of times in succession
+    Times(u32),
+    /// Loop the audio until the caller interrupts it with a key press or the call ends
+    UntilInterrupted,
+}
+
+impl Default for Repeat {
+    fn default() -> Repeat {
+        Repeat::Times(1)
+    }
+}
+
+/// A request to end a call, or to reject an incoming one
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HangupCall {
+    pub call_id: String,
+}
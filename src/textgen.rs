@@ -0,0 +1,159 @@
+//! # Text Generation
+//!
+//! This module contains data types for the `wascc:textgen` capability provider. Providers backed by
+//! large language models expose text completion (whole or streamed in chunks) and embeddings to actors
+
+use crate::Sample;
+
+/// Guest sends a CompletionRequest to the capability provider, receives a CompletionResponse back
+pub const OP_COMPLETE: &str = "Complete";
+/// Guest sends a CompletionRequest to the capability provider, lack of error indicates success. Guest will
+/// then receive OP_DELIVER_COMPLETION_CHUNK operations from the provider as the completion is generated
+pub const OP_START_COMPLETION_STREAM: &str = "StartCompletionStream";
+/// Provider sends a CompletionChunk to the guest for each piece of a streamed completion
+pub const OP_DELIVER_COMPLETION_CHUNK: &str = "DeliverCompletionChunk";
+/// Guest sends an EmbeddingRequest to the capability provider, receives an EmbeddingResult back
+pub const OP_CREATE_EMBEDDINGS: &str = "CreateEmbeddings";
+
+/// A request to generate text, either from a single prompt or from a conversation
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionRequest {
+    /// An actor-chosen ID that correlates streamed chunks with this request
+    pub request_id: String,
+    /// A plain text prompt. Ignored if `messages` is not empty
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// The conversation to complete, oldest message first
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+    /// The model the actor would prefer. Providers may substitute another model
+    #[serde(default)]
+    pub model_hint: Option<String>,
+    /// The maximum number of tokens to generate, 0 - provider default
+    pub max_tokens: u32,
+    /// Sampling temperature, typically between 0.0 (deterministic) and 2.0
+    pub temperature: f32,
+    /// Sequences at which generation stops
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+impl Sample for CompletionRequest {
+    fn sample() -> Self {
+        CompletionRequest {
+            request_id: "req-1".to_string(),
+            prompt: None,
+            messages: vec![
+                ChatMessage {
+                    role: Role::System,
+                    content: "You are a helpful assistant".to_string(),
+                },
+                ChatMessage {
+                    role: Role::User,
+                    content: "Summarize the waSCC security model".to_string(),
+                },
+            ],
+            model_hint: Some("small".to_string()),
+            max_tokens: 256,
+            temperature: 0.5,
+            stop: vec![],
+        }
+    }
+}
+
+/// The author of a message in a conversation
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+/// A single message in a conversation
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+/// The reason generation stopped
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// The model finished naturally or produced a stop sequence
+    Stop,
+    /// The `max_tokens` limit was reached
+    Length,
+    /// The provider withheld output due to a content policy
+    ContentFilter,
+}
+
+/// Token counts consumed by a request
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+/// The result of a non-streamed completion
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionResponse {
+    pub request_id: String,
+    pub text: String,
+    pub finish_reason: FinishReason,
+    /// The model that produced the completion
+    pub model: String,
+    #[serde(default)]
+    pub usage: TokenUsage,
+}
+
+/// A piece of a streamed completion
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionChunk {
+    pub request_id: String,
+    /// A sequence number that can be used for ordering logic, starting at 0
+    pub sequence_no: u64,
+    /// The text generated since the previous chunk
+    pub delta: String,
+    /// Present only on the final chunk of the completion
+    #[serde(default)]
+    pub finish_reason: Option<FinishReason>,
+    /// Present only on the final chunk of the completion
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+impl CompletionChunk {
+    /// Indicates whether this is the final chunk of the completion
+    pub fn is_last(&self) -> bool {
+        self.finish_reason.is_some()
+    }
+}
+
+/// A request to compute embedding vectors for a set of inputs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingRequest {
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub model_hint: Option<String>,
+}
+
+/// The embedding vectors for a set of inputs, in the same order as the request's inputs
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingResult {
+    pub model: String,
+    /// The length of each embedding vector
+    pub dimensions: u32,
+    #[serde(default)]
+    pub embeddings: Vec<Vec<f32>>,
+    #[serde(default)]
+    pub usage: TokenUsage,
+}
@@ -0,0 +1,178 @@
+//! # Vector Store
+//!
+//! This module contains data types for the `wascc:vectorstore` capability provider, which stores
+//! embedding vectors alongside metadata and answers similarity queries over them, e.g. for
+//! retrieval-augmented generation. Providers are typically backed by Qdrant or pgvector
+
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a CollectionSpec to the capability provider, lack of error indicates success
+pub const OP_CREATE_COLLECTION: &str = "CreateCollection";
+/// Guest sends a Collection to the capability provider, lack of error indicates success
+pub const OP_DELETE_COLLECTION: &str = "DeleteCollection";
+/// Guest sends an empty payload to the capability provider, receives a CollectionList back
+pub const OP_LIST_COLLECTIONS: &str = "ListCollections";
+/// Guest sends an UpsertVectors to the capability provider, receives an UpsertResult back
+pub const OP_UPSERT_VECTORS: &str = "UpsertVectors";
+/// Guest sends a DeleteVectors to the capability provider, lack of error indicates success
+pub const OP_DELETE_VECTORS: &str = "DeleteVectors";
+/// Guest sends a SimilarityQuery to the capability provider, receives QueryMatches back
+pub const OP_QUERY: &str = "Query";
+
+/// The function used to measure how similar two vectors are
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMetric {
+    Cosine,
+    Euclidean,
+    DotProduct,
+}
+
+/// Describes a collection of vectors to be created
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionSpec {
+    pub name: String,
+    /// The length of every vector stored in the collection
+    pub dimensions: u32,
+    pub metric: DistanceMetric,
+}
+
+impl Sample for CollectionSpec {
+    fn sample() -> Self {
+        CollectionSpec {
+            name: "documents".to_string(),
+            dimensions: 384,
+            metric: DistanceMetric::Cosine,
+        }
+    }
+}
+
+/// A reference to an existing collection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub name: String,
+}
+
+impl Collection {
+    pub fn new(name: &str) -> Collection {
+        Collection {
+            name: name.to_string(),
+        }
+    }
+}
+
+/// The collections known to the provider
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionList {
+    #[serde(default)]
+    pub collections: Vec<CollectionSpec>,
+}
+
+/// A single embedding vector and the metadata stored with it
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorRecord {
+    /// Unique within the collection. Upserting a record with an existing ID replaces it
+    pub id: String,
+    pub vector: Vec<f32>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+/// A request to insert or replace vectors in a collection
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertVectors {
+    pub collection: String,
+    pub records: Vec<VectorRecord>,
+}
+
+impl Sample for UpsertVectors {
+    fn sample() -> Self {
+        let mut metadata = HashMap::new();
+        metadata.insert("source".to_string(), "handbook.pdf".to_string());
+        metadata.insert("page".to_string(), "12".to_string());
+        UpsertVectors {
+            collection: "documents".to_string(),
+            records: vec![VectorRecord {
+                id: "handbook-12-0".to_string(),
+                vector: vec![0.12, -0.04, 0.33, 0.9],
+                metadata,
+            }],
+        }
+    }
+}
+
+/// The outcome of an upsert
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertResult {
+    /// The number of records written
+    pub upserted: u64,
+}
+
+/// A request to remove vectors from a collection by ID
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteVectors {
+    pub collection: String,
+    pub ids: Vec<String>,
+}
+
+/// A request for the records in a collection most similar to a given vector
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityQuery {
+    pub collection: String,
+    pub vector: Vec<f32>,
+    /// The maximum number of matches to return
+    pub top_k: u32,
+    /// Only records whose metadata contains every one of these key/value pairs are considered.
+    /// Empty - all records are considered
+    #[serde(default)]
+    pub filter: HashMap<String, String>,
+    /// Whether matches should include the stored vectors, which can be large
+    #[serde(default)]
+    pub include_vectors: bool,
+}
+
+impl Sample for SimilarityQuery {
+    fn sample() -> Self {
+        let mut filter = HashMap::new();
+        filter.insert("source".to_string(), "handbook.pdf".to_string());
+        SimilarityQuery {
+            collection: "documents".to_string(),
+            vector: vec![0.1, -0.02, 0.3, 0.88],
+            top_k: 5,
+            filter,
+            include_vectors: false,
+        }
+    }
+}
+
+/// A record that matched a similarity query
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMatch {
+    pub id: String,
+    /// The similarity between the record and the query vector as computed by the collection's
+    /// metric. For Euclidean distance lower is closer, for the other metrics higher is closer
+    pub score: f32,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Present only if the query asked for vectors to be included
+    #[serde(default)]
+    pub vector: Option<Vec<f32>>,
+}
+
+/// The matches for a similarity query, closest first
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMatches {
+    #[serde(default)]
+    pub matches: Vec<QueryMatch>,
+}
@@ -0,0 +1,200 @@
+//! # Outbound Webhooks
+//!
+//! This module contains data types for the `wascc:push-webhook` capability provider. Actors register
+//! webhook endpoints and publish events, and the provider takes care of delivering each event to the
+//! matching endpoints, signing the deliveries, and retrying failed attempts
+
+use crate::access::pattern_matches;
+use crate::core::{DurationMs, SecretString, Timestamp};
+use crate::Sample;
+use std::collections::HashMap;
+
+/// Guest sends a WebhookRegistration to the capability provider, receives a RegistrationResult back
+pub const OP_REGISTER_WEBHOOK: &str = "RegisterWebhook";
+/// Guest sends an UnregisterWebhook to the capability provider, lack of error indicates success
+pub const OP_UNREGISTER_WEBHOOK: &str = "UnregisterWebhook";
+/// Guest sends a WebhookEvent to the capability provider, receives a PublishResult back
+pub const OP_PUBLISH_EVENT: &str = "PublishWebhookEvent";
+/// Guest sends a DeliveryStatusRequest to the capability provider, receives a DeliveryReport back
+pub const OP_GET_DELIVERY_STATUS: &str = "GetDeliveryStatus";
+/// Provider sends a DeliveryReport to the guest when a delivery succeeds or permanently fails
+pub const OP_DELIVERY_STATUS_CHANGED: &str = "DeliveryStatusChanged";
+
+/// HTTP header carrying the delivery's signature, of the form `sha256=<hex digest>`. The digest is
+/// the HMAC-SHA256, keyed with the registration's secret, of `<timestamp>.<payload>`
+pub const HEADER_SIGNATURE: &str = "X-Wascc-Signature";
+/// HTTP header carrying the delivery's timestamp (milliseconds since the epoch)
+pub const HEADER_TIMESTAMP: &str = "X-Wascc-Timestamp";
+/// HTTP header carrying the delivery ID, which receivers can use to de-duplicate retried deliveries
+pub const HEADER_DELIVERY_ID: &str = "X-Wascc-Delivery";
+/// HTTP header carrying the event type
+pub const HEADER_EVENT: &str = "X-Wascc-Event";
+
+/// Registers an endpoint to receive events
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookRegistration {
+    /// The URL to which events are POSTed
+    pub url: String,
+    /// The secret used to sign deliveries, redacted from `Debug` output
+    pub secret: SecretString,
+    /// Patterns matched against event types, e.g. `order.*`. If empty, all events are delivered
+    #[serde(default)]
+    pub event_filters: Vec<String>,
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// Additional headers sent with every delivery
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl Sample for WebhookRegistration {
+    fn sample() -> Self {
+        WebhookRegistration {
+            url: "https://example.com/hooks/orders".to_string(),
+            secret: SecretString::from("whsec_8f2d7c"),
+            event_filters: vec!["order.*".to_string()],
+            retry_policy: RetryPolicy::default(),
+            headers: HashMap::new(),
+        }
+    }
+}
+
+impl WebhookRegistration {
+    /// Indicates whether events of the given type should be delivered to this endpoint
+    pub fn accepts(&self, event_type: &str) -> bool {
+        self.event_filters.is_empty()
+            || self
+                .event_filters
+                .iter()
+                .any(|f| pattern_matches(f, event_type))
+    }
+}
+
+/// Controls how failed deliveries are retried, using exponential backoff
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first
+    pub max_attempts: u32,
+    /// The delay before the first retry
+    pub initial_backoff: DurationMs,
+    /// The upper bound on the delay between attempts
+    pub max_backoff: DurationMs,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: DurationMs(1_000),
+            max_backoff: DurationMs(300_000),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay to wait after the given (1-based) attempt fails, or `None` if no attempts remain.
+    /// The delay doubles with each attempt
+    pub fn backoff_after(&self, attempt: u32) -> Option<DurationMs> {
+        if attempt == 0 || attempt >= self.max_attempts {
+            return None;
+        }
+        let factor = 1u64.checked_shl(attempt - 1).unwrap_or(u64::MAX);
+        Some(DurationMs(
+            self.initial_backoff
+                .0
+                .saturating_mul(factor)
+                .min(self.max_backoff.0),
+        ))
+    }
+}
+
+/// The provider's response to a registration
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationResult {
+    pub registration_id: String,
+}
+
+/// Removes a registered endpoint. Deliveries already in progress are abandoned
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnregisterWebhook {
+    pub registration_id: String,
+}
+
+/// An event to be delivered to every registered endpoint whose filters accept it
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    pub event_type: String,
+    pub content_type: String,
+    #[serde(with = "serde_bytes")]
+    #[serde(default)]
+    pub payload: Vec<u8>,
+}
+
+/// The provider's response to a published event
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishResult {
+    /// The IDs of the deliveries created for the event, one per matching endpoint
+    #[serde(default)]
+    pub delivery_ids: Vec<String>,
+}
+
+/// A single attempt to deliver an event to an endpoint
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub delivery_id: String,
+    pub registration_id: String,
+    pub event: WebhookEvent,
+    /// The value of the `HEADER_SIGNATURE` header sent with this attempt
+    pub signature: String,
+    pub timestamp: Timestamp,
+    /// The 1-based attempt number
+    pub attempt: u32,
+}
+
+/// A request for the status of a delivery
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryStatusRequest {
+    pub delivery_id: String,
+}
+
+/// The state of a delivery
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// The first attempt has not yet been made
+    Pending,
+    /// An attempt failed and another is scheduled
+    Retrying,
+    /// The endpoint acknowledged the delivery with a 2xx response
+    Succeeded,
+    /// All attempts failed, the delivery has been abandoned
+    Failed,
+}
+
+/// Reports the status of a delivery
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryReport {
+    pub delivery_id: String,
+    pub registration_id: String,
+    pub status: DeliveryStatus,
+    /// The number of attempts made so far
+    pub attempts: u32,
+    /// The HTTP status code returned by the endpoint on the most recent attempt, if it responded
+    #[serde(default)]
+    pub last_status_code: Option<u32>,
+    /// A description of the error from the most recent attempt, if any
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// When the next attempt is scheduled, while the delivery is retrying
+    #[serde(default)]
+    pub next_attempt_at: Option<Timestamp>,
+}